@@ -0,0 +1,62 @@
+use std::time::{Duration, Instant};
+
+use anyhow::{bail, Context, Error};
+use fractal_wgpu_lib::{Backends, Canvas};
+
+use crate::{parse_size, scene::Scene};
+
+/// Size of the rendered frames, unless specified otherwise.
+const SIZE: (u32, u32) = (1024, 1024);
+/// Number of frames measured, unless specified otherwise.
+const FRAMES: u32 = 100;
+
+/// Measures how fast the default view renders headlessly, as described by the command line
+/// arguments following the `benchmark` subcommand: `[<width>x<height>] [<frames>]`. Useful to
+/// compare the performance of changes to the shader.
+pub async fn run(args: &[String]) -> Result<(), Error> {
+    const USAGE: &str = "Usage: benchmark [<width>x<height>] [<frames>]";
+    let ((width, height), frames) = match args {
+        [] => (SIZE, FRAMES),
+        [size] => (parse_size(size).context(USAGE)?, FRAMES),
+        [size, frames] => {
+            let frames = frames
+                .parse()
+                .ok()
+                .filter(|&frames| frames != 0)
+                .with_context(|| {
+                    format!("Number of frames must be a positive integer, not '{frames}'")
+                })?;
+            (parse_size(size).context(USAGE)?, frames)
+        }
+        _ => bail!(USAGE),
+    };
+    let canvas = Canvas::new_headless(width, height, Backends::all())
+        .await
+        .context("Error requesting device for drawing")?;
+    if let Some(info) = canvas.adapter_info() {
+        println!("Rendering on {} ({:?})", info.name, info.backend);
+    }
+    let elapsed = measure(&canvas, frames)?;
+    let frame_time = elapsed / frames;
+    println!(
+        "Rendered {frames} frames of {width}x{height} pixels in {elapsed:.2?}. Average frame time: \
+        {frame_time:.2?}, {:.1} FPS",
+        1. / frame_time.as_secs_f64()
+    );
+    Ok(())
+}
+
+/// Wall-clock time it takes to render `frames` frames of the default view, including the time the
+/// GPU needs to finish them.
+fn measure(canvas: &Canvas, frames: u32) -> Result<Duration, Error> {
+    let state = Scene::new().render_state();
+    // The first frame may include one time costs, like compiling the shader in the driver.
+    canvas.render(&state)?;
+    canvas.wait_until_idle();
+    let start = Instant::now();
+    for _ in 0..frames {
+        canvas.render(&state)?;
+    }
+    canvas.wait_until_idle();
+    Ok(start.elapsed())
+}
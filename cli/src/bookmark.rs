@@ -0,0 +1,93 @@
+use anyhow::{Context, Error};
+use fractal_wgpu_lib::{Canvas, RenderState};
+use image::RgbaImage;
+
+/// Edge length in pixels of the previews of stored views.
+const THUMBNAIL_SIZE: u32 = 128;
+
+/// A location in the Mandelbrot set worth visiting.
+pub struct Bookmark {
+    pub name: &'static str,
+    /// Center of the view in the complex plane.
+    pub position: [f64; 2],
+    pub zoom: f64,
+    pub iterations: f32,
+}
+
+/// Locations the number keys `1` to `9` jump to, in that order.
+pub const BOOKMARKS: [Bookmark; 9] = [
+    Bookmark {
+        name: "Seahorse Valley",
+        position: [-0.7436, 0.1318],
+        zoom: 50.,
+        iterations: 500.,
+    },
+    Bookmark {
+        name: "Elephant Valley",
+        position: [0.2751, 0.0069],
+        zoom: 40.,
+        iterations: 400.,
+    },
+    Bookmark {
+        name: "Triple Spiral Valley",
+        position: [-0.088, 0.654],
+        zoom: 30.,
+        iterations: 400.,
+    },
+    Bookmark {
+        name: "Scepter Valley",
+        position: [-1.36, 0.005],
+        zoom: 20.,
+        iterations: 400.,
+    },
+    Bookmark {
+        name: "Mini Mandelbrot",
+        position: [-1.7549, 0.],
+        zoom: 50.,
+        iterations: 500.,
+    },
+    Bookmark {
+        name: "Double Spiral",
+        position: [-0.7453, 0.1127],
+        zoom: 3000.,
+        iterations: 1000.,
+    },
+    Bookmark {
+        name: "Lightning",
+        position: [-0.170337, 1.06506],
+        zoom: 200.,
+        iterations: 800.,
+    },
+    Bookmark {
+        name: "Dendrite",
+        position: [-0.1011, 0.9563],
+        zoom: 20.,
+        iterations: 500.,
+    },
+    Bookmark {
+        name: "Deep Seahorse",
+        position: [-0.743643887037151, 0.131825904205330],
+        zoom: 1e6,
+        iterations: 2000.,
+    },
+];
+
+/// View stored by the user with `Ctrl` and a number key, which takes the place of the bookmark of
+/// that key.
+pub struct StoredView {
+    pub state: RenderState,
+    /// Small preview of the view, so stored views can be told apart without visiting them.
+    pub thumbnail: RgbaImage,
+}
+
+impl StoredView {
+    /// Stores `state` together with a thumbnail rendered by `canvas`.
+    pub fn capture(canvas: &Canvas, state: RenderState) -> Result<Self, Error> {
+        let pixels = canvas
+            .render_thumbnail(&state, THUMBNAIL_SIZE)
+            .context("Could not render thumbnail of the view")?;
+        let thumbnail = RgbaImage::from_raw(THUMBNAIL_SIZE, THUMBNAIL_SIZE, pixels)
+            .context("Number of pixels does not match the size of the thumbnail")?;
+        Ok(StoredView { state, thumbnail })
+    }
+}
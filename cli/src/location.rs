@@ -0,0 +1,21 @@
+use std::{fs, path::Path};
+
+use anyhow::{Context, Error};
+use fractal_wgpu_lib::Camera;
+
+/// File the current location is saved to and loaded from.
+pub const LOCATION_PATH: &str = "location.json";
+
+/// Writes position and zoom level of `camera` to `path`.
+pub fn save_location(path: &Path, camera: &Camera) -> Result<(), Error> {
+    fs::write(path, camera.to_json())
+        .with_context(|| format!("Could not write location to {}", path.display()))
+}
+
+/// Reads a camera previously saved with [`save_location`] from `path`.
+pub fn load_location(path: &Path) -> Result<Camera, Error> {
+    let json = fs::read_to_string(path)
+        .with_context(|| format!("Could not read location from {}", path.display()))?;
+    Camera::from_json(&json)
+        .with_context(|| format!("{} does not contain a valid location", path.display()))
+}
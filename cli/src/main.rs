@@ -1,59 +1,242 @@
-use anyhow::{Context, Error};
-use controls::Controls;
-use log::error;
+use std::{
+    env, fs,
+    path::Path,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+use anyhow::{bail, Context, Error};
+use log::{error, info, warn};
 use winit::{
     dpi::LogicalSize,
-    event::{Event, WindowEvent},
+    event::{ElementState, Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
-    window::WindowBuilder,
+    window::{Fullscreen, WindowBuilder},
 };
 
-use fractal_wgpu_lib::{Camera, Canvas};
+use fractal_wgpu_lib::{Backends, Canvas, Controls, SceneToken};
+
+use self::{
+    bookmark::{StoredView, BOOKMARKS},
+    scene::Scene,
+};
 
-mod controls;
+mod benchmark;
+mod bookmark;
+mod location;
+mod scene;
+mod screenshot;
+mod zoom_sequence;
 
+/// Size of the window unless specified otherwise with `--width` and `--height`.
 const WIDTH: u32 = 400;
 const HEIGHT: u32 = 400;
 
+/// Pressing F5 replaces the built-in fractal shader with the one in this file, so it can be
+/// changed without rebuilding the application.
+const SHADER_PATH: &str = "shader.wgsl";
+
+/// Minimum time between two log messages reporting how long rendering a frame takes.
+const FRAME_TIME_LOG_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Minimum time between two updates of the window title, which shows zoom level and iterations.
+/// Some platforms redraw the entire title bar each time.
+const TITLE_UPDATE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Number of samples per pixel used to anti-alias the edges of the picture.
+const MSAA_SAMPLE_COUNT: u32 = 4;
+
+/// Size of the stills rendered by pressing `F9`, unless specified otherwise with `--still`.
+const STILL_SIZE: (u32, u32) = (4096, 4096);
+
+/// Stills are rendered in tiles of at most this many pixels in either direction, so they may be
+/// larger than the biggest texture the graphics card supports.
+const STILL_TILE_SIZE: u32 = 1024;
+
+/// Frames per second drawn at most while the picture changes, unless specified otherwise with
+/// `--max-fps`.
+const MAX_FPS: f64 = 60.;
+
 const GREETING: &str = include_str!("greeting.txt");
 
 fn main() -> Result<(), Error> {
     // We need logger to see wgpu error output
     env_logger::init();
 
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("zoom-sequence") {
+        // Renders an animation headlessly, without opening a window.
+        return pollster::block_on(zoom_sequence::run(&args[1..]));
+    }
+    if args.first().map(String::as_str) == Some("benchmark") {
+        return pollster::block_on(benchmark::run(&args[1..]));
+    }
+
+    let options = parse_options(&args)?;
+
     println!("{GREETING}");
 
     // WGP offers async function calls, pollster is a minimal async runtime
-    pollster::block_on(run())
+    pollster::block_on(run(options))
+}
+
+/// Settings given on the command line.
+struct Options {
+    /// Initial width of the window in logical pixels.
+    width: u32,
+    /// Initial height of the window in logical pixels.
+    height: u32,
+    /// Scene shown at startup, instead of the overview of the Mandelbrot set.
+    scene: Option<SceneToken>,
+    /// Factor applied to the resolution the fractal is drawn at, see
+    /// [`Canvas::set_render_scale`].
+    render_scale: f32,
+    /// Width and height in pixels of the stills rendered by pressing `F9`.
+    still_size: (u32, u32),
+    /// Minimum time between two frames drawn while the picture changes.
+    frame_interval: Duration,
+}
+
+/// Parses the command line arguments `--width <pixels>`, `--height <pixels>`, `--scene <token>`,
+/// `--render-scale <factor>`, `--still <width>x<height>` and `--max-fps <fps>`. Width and height
+/// default to [`WIDTH`] and [`HEIGHT`] respectively, the size of stills to [`STILL_SIZE`] and the
+/// frame rate to [`MAX_FPS`]. Tokens are printed by pressing `F8`.
+fn parse_options(args: &[String]) -> Result<Options, Error> {
+    let mut options = Options {
+        width: WIDTH,
+        height: HEIGHT,
+        scene: None,
+        render_scale: 1.,
+        still_size: STILL_SIZE,
+        frame_interval: Duration::from_secs_f64(1. / MAX_FPS),
+    };
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        let size = match arg.as_str() {
+            "--width" => &mut options.width,
+            "--height" => &mut options.height,
+            "--scene" => {
+                let token = args.next().context("Missing token after --scene")?;
+                options.scene = Some(token.parse()?);
+                continue;
+            }
+            "--render-scale" => {
+                let value = args.next().context("Missing factor after --render-scale")?;
+                options.render_scale = value
+                    .parse()
+                    .ok()
+                    .filter(|&factor: &f32| factor.is_finite() && factor > 0.)
+                    .with_context(|| {
+                        format!("--render-scale must be a positive number, not '{value}'")
+                    })?;
+                continue;
+            }
+            "--still" => {
+                let value = args.next().context("Missing size after --still")?;
+                options.still_size = parse_size(value).with_context(|| {
+                    format!("--still must be <width>x<height>, e.g. 4096x4096, not '{value}'")
+                })?;
+                continue;
+            }
+            "--max-fps" => {
+                let value = args.next().context("Missing frame rate after --max-fps")?;
+                let fps = value
+                    .parse()
+                    .ok()
+                    .filter(|&fps: &f64| fps.is_finite() && fps > 0.)
+                    .with_context(|| {
+                        format!("--max-fps must be a positive number, not '{value}'")
+                    })?;
+                // Frame rates close to zero yield intervals too long to be represented.
+                options.frame_interval = Duration::try_from_secs_f64(1. / fps)
+                    .with_context(|| format!("--max-fps {value} is too low"))?;
+                continue;
+            }
+            other => {
+                bail!(
+                    "Unknown argument '{other}'. Usage: [--width <pixels>] [--height <pixels>] \
+                    [--scene <token>] [--render-scale <factor>] [--still <width>x<height>] \
+                    [--max-fps <fps>]"
+                )
+            }
+        };
+        let value = args
+            .next()
+            .with_context(|| format!("Missing number of pixels after {arg}"))?;
+        *size = value
+            .parse()
+            .ok()
+            .filter(|&pixels| pixels != 0)
+            .with_context(|| format!("{arg} must be a positive integer, not '{value}'"))?;
+    }
+    Ok(options)
+}
+
+/// Parses a size in pixels given as `<width>x<height>`, e.g. `4096x4096`. `None` if malformed or
+/// either side is zero.
+fn parse_size(value: &str) -> Option<(u32, u32)> {
+    value
+        .split_once('x')
+        .and_then(|(width, height)| Some((width.parse().ok()?, height.parse().ok()?)))
+        .filter(|&(width, height)| width != 0 && height != 0)
 }
 
-async fn run() -> Result<(), Error> {
+async fn run(options: Options) -> Result<(), Error> {
+    let Options {
+        width,
+        height,
+        scene: shared_scene,
+        render_scale,
+        still_size,
+        frame_interval,
+    } = options;
     // Window message loop.
     let event_loop = EventLoop::new();
-    let window = WindowBuilder::new()
-        .with_title("Fractal WGPU")
-        .with_inner_size(LogicalSize::new(f64::from(WIDTH), f64::from(HEIGHT)))
-        .build(&event_loop)?;
-
-    let mut canvas = unsafe {
-        Canvas::new(WIDTH, HEIGHT, &window)
-            .await
-            .context("Error requesting device for drawing")?
-    };
+    // Shared with the canvas, since both are moved into the event loop.
+    let window = Arc::new(
+        WindowBuilder::new()
+            .with_title("Fractal WGPU")
+            .with_inner_size(LogicalSize::new(f64::from(width), f64::from(height)))
+            // Shown once the first frame has been rendered, so the window does not flash with
+            // whatever the platform fills it with initially.
+            .with_visible(false)
+            .build(&event_loop)?,
+    );
+
+    let mut canvas = Canvas::new(width, height, window.clone(), Backends::all())
+        .await
+        .context("Error requesting device for drawing")?;
+    // Smooths the borders of the picture in fit mode `Contain`. Not worth failing over.
+    if let Err(e) = canvas.set_sample_count(MSAA_SAMPLE_COUNT) {
+        warn!("{e} Rendering without multisampling.");
+    }
+    canvas.set_render_scale(render_scale);
 
     // Keeps track of request redraw request, e.g if the window has been partially hidden behind
     // another window, ro is resized.
     let mut redraw_requested = true;
-    // Camera position and zoom level. Determines which part of the fractal we see
-    let mut camera = Camera::new();
-    // Number of iterations used to determine wether a point converges or not. How fast a point
-    // converges is used to determine the color of a pixel.
-    //
-    // We use a floating point variable to track the number of iterations, so we can easier adapt
-    // the number of iterations smoothly by pressing buttons for a period of time. This implies we
-    // need to keep track of differences smaller than 1 between frames.
-    let mut iterations = 256f32;
+    // The window is hidden until the first frame has been rendered.
+    let mut window_shown = false;
+    // The picture changed during the last frame.
+    let mut in_motion = false;
+    // Changes to the picture, which have not been drawn yet, because the last frame is too recent.
+    let mut changes_pending = false;
+    // Point in time we started drawing the last frame.
+    let mut last_frame = Instant::now();
+    let mut scene = Scene::new();
     let mut controls = Controls::new();
+    if let Some(shared) = shared_scene {
+        scene.restore(&shared.state);
+        controls.select_palette(scene.palette());
+        controls.select_coloring(shared.coloring);
+    }
+    // Point in time we reported the frame time last.
+    let mut last_frame_time_log = Instant::now();
+    // Point in time we updated the title of the window last, and what we set it to.
+    let mut last_title_update = Instant::now();
+    let mut title = String::new();
+    // Views stored with `Ctrl` and a number key, which replace the bookmark of that key.
+    let mut stored_views: [Option<StoredView>; BOOKMARKS.len()] = Default::default();
 
     event_loop.run(move |event, _target, control_flow| match event {
         Event::WindowEvent {
@@ -67,6 +250,8 @@ async fn run() -> Result<(), Error> {
             event: WindowEvent::Resized(physical_size),
         } => {
             canvas.resize(physical_size.width, physical_size.height);
+            // Not every platform requests a redraw after resizing, e.g. after toggling fullscreen.
+            redraw_requested = true;
         }
         Event::WindowEvent {
             window_id: _,
@@ -77,37 +262,286 @@ async fn run() -> Result<(), Error> {
                 },
         } => {
             canvas.resize(new_inner_size.width, new_inner_size.height);
+            // The resized surface holds no picture until we render again.
+            redraw_requested = true;
         }
+        // Some platforms report keys which are already held down as synthetic presses, once the
+        // window gains focus. They have not been pressed for us, so they must not move the camera
+        // either.
         Event::WindowEvent {
             window_id: _,
             event:
                 WindowEvent::KeyboardInput {
                     device_id: _,
                     input,
-                    is_synthetic: _,
+                    is_synthetic,
                 },
-        } => {
+        } if !(is_synthetic && input.state == ElementState::Pressed) => {
             controls.track_button_presses(input);
         }
+        Event::WindowEvent {
+            window_id: _,
+            event: WindowEvent::Focused(false),
+        } => {
+            // We miss the release of any key held while the window is in the background.
+            controls.release_all();
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::CursorMoved {
+                    device_id: _,
+                    position,
+                    ..
+                },
+        } => {
+            controls.track_cursor(position);
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::MouseWheel {
+                    device_id: _,
+                    delta,
+                    ..
+                },
+        } => {
+            controls.track_mouse_wheel(delta);
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::MouseInput {
+                    device_id: _,
+                    state,
+                    button,
+                    ..
+                },
+        } => {
+            controls.track_mouse_button(state, button);
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event: WindowEvent::Touch(touch),
+        } => {
+            controls.track_touch(touch);
+        }
         Event::RedrawRequested(_window_id) => {
             redraw_requested = true;
         }
         Event::MainEventsCleared => {
-            controls.update_scene(&mut camera, &mut iterations);
-            if redraw_requested || controls.picture_changes() {
-                match canvas.render(&camera, iterations.trunc() as i32) {
+            if controls.take_load_location_request() {
+                let path = Path::new(location::LOCATION_PATH);
+                match location::load_location(path) {
+                    Ok(loaded) => {
+                        scene.camera = loaded;
+                        info!("Loaded location from {}", path.display())
+                    }
+                    Err(e) => error!("{e:#}"),
+                }
+            }
+            // While paused, the scene stays as it is and we do not spend energy rendering it.
+            let paused = controls.paused();
+            if !paused {
+                scene.update(&mut controls, canvas.size());
+            }
+            // Key bindings may provide more bookmark keys than there are bookmarks.
+            let bookmark_count = BOOKMARKS.len();
+            if let Some(index) = controls
+                .take_store_bookmark_request()
+                .filter(|&index| index < bookmark_count)
+            {
+                let key = index + 1;
+                match StoredView::capture(&canvas, scene.render_state()) {
+                    Ok(view) => {
+                        // Lets users look through their stored views.
+                        let path = screenshot::preview_path(key);
+                        match view.thumbnail.save(&path) {
+                            Ok(()) => info!(
+                                "Stored view. Press {key} to return to it. Preview saved to {}",
+                                path.display()
+                            ),
+                            Err(e) => error!("Could not write preview of the view: {e}"),
+                        }
+                        stored_views[index] = Some(view);
+                    }
+                    Err(e) => error!("{e:#}"),
+                }
+            }
+            if let Some(index) = controls
+                .take_bookmark_request()
+                .filter(|&index| index < bookmark_count)
+            {
+                // Views stored by the user take the place of the preset bookmarks.
+                if let Some(StoredView { state, .. }) = &stored_views[index] {
+                    // Holding Shift jumps, like it speeds up moving the camera.
+                    scene.recall(state, !controls.fast());
+                    controls.select_palette(scene.palette());
+                    info!("Returned to stored view {}", index + 1);
+                } else {
+                    let bookmark = &BOOKMARKS[index];
+                    scene.go_to(bookmark);
+                    info!("Jumped to {}", bookmark.name);
+                }
+            }
+            if controls.take_fullscreen_request() {
+                let fullscreen = match window.fullscreen() {
+                    Some(_) => None,
+                    None => Some(Fullscreen::Borderless(None)),
+                };
+                window.set_fullscreen(fullscreen);
+            }
+            canvas.set_loupe(controls.loupe());
+            canvas.set_julia_inset(controls.julia_inset());
+            canvas.set_grid(controls.grid());
+            // Formatting the lines is only worth it, if they are displayed.
+            let hud_lines = if controls.hud() {
+                scene.hud_lines()
+            } else {
+                Vec::new()
+            };
+            canvas.set_hud(controls.hud(), &hud_lines);
+            if controls.take_reload_shader_request() {
+                match reload_shader(&mut canvas, Path::new(SHADER_PATH)) {
+                    Ok(()) => info!("Reloaded shader from {SHADER_PATH}"),
+                    // The canvas keeps rendering with the previous shader
+                    Err(e) => error!("{e:#}"),
+                }
+            }
+            if let Some(inverted) = controls.take_inversion_change() {
+                canvas.set_color_inversion(inverted);
+            }
+            if let Some(factor) = controls.take_supersampling_change() {
+                canvas.set_supersampling(factor);
+            }
+            if let Some(coloring) = controls.take_coloring_change() {
+                canvas.set_coloring_mode(coloring);
+            }
+            if let Some(interior) = controls.take_interior_change() {
+                canvas.set_interior_mode(interior);
+            }
+            if let Some(enabled) = controls.take_equalization_change() {
+                canvas.set_histogram_equalization(enabled);
+            }
+            if let Some(fit_mode) = controls.take_fit_mode_change() {
+                canvas.set_fit_mode(fit_mode);
+            }
+            if let Some(speed) = controls.take_color_cycle_change() {
+                canvas.set_color_cycle_speed(speed);
+            }
+            if let Some(enabled) = controls.take_smooth_coloring_change() {
+                canvas.set_smooth_coloring(enabled);
+            }
+            if let Some(enabled) = controls.take_dynamic_resolution_change() {
+                canvas.set_dynamic_resolution(enabled);
+                info!(
+                    "Dynamic resolution {}",
+                    if enabled { "enabled" } else { "disabled" }
+                );
+            }
+            if controls.take_save_location_request() {
+                let path = Path::new(location::LOCATION_PATH);
+                match location::save_location(path, &scene.camera) {
+                    Ok(()) => info!("Saved location to {}", path.display()),
+                    Err(e) => error!("{e:#}"),
+                }
+            }
+            if controls.take_share_request() {
+                let token = SceneToken {
+                    state: scene.render_state(),
+                    coloring: controls.coloring(),
+                };
+                // Printed rather than logged, so it is visible without enabling the logger.
+                println!("Restore this scene with: --scene {token}");
+            }
+            if controls.take_screenshot_request() {
+                let path = screenshot::screenshot_path();
+                let (width, height) = canvas.size();
+                let result = canvas
+                    .capture_frame(&scene.camera, scene.iterations(), &scene.kind)
+                    .map_err(Error::from)
+                    .and_then(|pixels| screenshot::save_png(&path, width, height, pixels));
+                match result {
+                    Ok(()) => info!("Saved screenshot to {}", path.display()),
+                    Err(e) => error!("{e:#}"),
+                }
+            }
+            if controls.take_still_request() {
+                let path = screenshot::still_path();
+                let (width, height) = still_size;
+                info!("Rendering {width}x{height} still. This may take a while.");
+                let result = canvas
+                    .render_tiled_with_progress(
+                        &scene.camera,
+                        scene.iterations(),
+                        &scene.kind,
+                        width,
+                        height,
+                        STILL_TILE_SIZE,
+                        |finished, total| println!("Rendered tile {finished} of {total}"),
+                    )
+                    .map_err(Error::from)
+                    .and_then(|pixels| screenshot::save_png(&path, width, height, pixels));
+                match result {
+                    Ok(()) => info!("Saved still to {}", path.display()),
+                    Err(e) => error!("{e:#}"),
+                }
+            }
+            // Handled after all other requests, so e.g. a screenshot requested in the same frame is
+            // still written to disk.
+            if controls.take_exit_request() {
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+            let picture_changes =
+                !paused && (controls.picture_changes() || scene.picture_changes());
+            // The last frame in motion may have been drawn at reduced resolution, so we draw the
+            // picture at rest once more.
+            let came_to_rest = in_motion && !picture_changes;
+            in_motion = picture_changes;
+            canvas.set_motion(in_motion);
+            // We draw no more frames than the frame rate allows. Changes held back are included
+            // in the next one.
+            changes_pending |= picture_changes || came_to_rest;
+            let frame_due = last_frame.elapsed() >= frame_interval;
+            // A redraw requested by the window, e.g. after resizing, is honoured even while paused,
+            // so the window does not show a stale or empty surface.
+            if redraw_requested || (changes_pending && frame_due && !paused) {
+                last_frame = Instant::now();
+                changes_pending = false;
+                match canvas.render(&scene.render_state()) {
                     Ok(_) => (),
                     // Most errors (Outdated, Timeout) should be resolved by the next frame
                     Err(e) => error!("{e}"),
                 }
+                if !window_shown {
+                    // Even if rendering failed, the user should see the application started.
+                    window.set_visible(true);
+                    window_shown = true;
+                }
+                // Once the picture settles we show the final values immediately, since we may wait
+                // a long time for the next frame.
+                if last_title_update.elapsed() >= TITLE_UPDATE_INTERVAL || !picture_changes {
+                    let new_title = scene.window_title();
+                    if new_title != title {
+                        window.set_title(&new_title);
+                        title = new_title;
+                    }
+                    last_title_update = Instant::now();
+                }
+                if last_frame_time_log.elapsed() >= FRAME_TIME_LOG_INTERVAL {
+                    log_frame_time(&canvas);
+                    last_frame_time_log = Instant::now();
+                }
             }
             redraw_requested = false;
             // If the camera is not moving or zooming, we behave like a "normal" event driver window
             // app patiently waiting for the next event and not waisting CPU cycles in a busy loop.
-            // Should we however change the picture we switch to polling as in a game loop, for
-            // smooth control.
-            *control_flow = if controls.picture_changes() {
-                ControlFlow::Poll
+            // Should we however change the picture we wake up in time for the next frame, like a
+            // game loop limited to a frame rate. This keeps the controls smooth, without spending
+            // a whole CPU core on polling for events.
+            *control_flow = if picture_changes || changes_pending {
+                ControlFlow::WaitUntil(last_frame + frame_interval)
             } else {
                 ControlFlow::Wait
             };
@@ -115,3 +549,23 @@ async fn run() -> Result<(), Error> {
         _ => (),
     });
 }
+
+/// Replaces the fractal shader of `canvas` with the one in the file at `path`.
+fn reload_shader(canvas: &mut Canvas, path: &Path) -> Result<(), Error> {
+    let source = fs::read_to_string(path)
+        .with_context(|| format!("Could not read shader from {}", path.display()))?;
+    pollster::block_on(canvas.reload_shader(&source))?;
+    Ok(())
+}
+
+/// Reports how long rendering the last frame took, if measured.
+fn log_frame_time(canvas: &Canvas) {
+    let Some(frame_time) = canvas.last_frame_time() else {
+        return;
+    };
+    let fps = 1. / frame_time.as_secs_f64();
+    match canvas.last_gpu_time() {
+        Some(gpu_time) => info!("Frame time: {frame_time:.2?} ({fps:.1} FPS), GPU: {gpu_time:.2?}"),
+        None => info!("Frame time: {frame_time:.2?} ({fps:.1} FPS)"),
+    }
+}
@@ -0,0 +1,294 @@
+use std::time::{Duration, Instant};
+
+use fractal_wgpu_lib::{Camera, Controls, FractalKind, RenderState};
+
+use crate::bookmark::Bookmark;
+
+/// Iterations added in automatic mode each time the zoom level grows by a factor of e.
+const ITERATIONS_PER_ZOOM_E_FOLD: f64 = 100.;
+
+/// Time it takes to ease the number of iterations towards the one of a bookmark.
+const ITERATION_EASING_DURATION: Duration = Duration::from_millis(600);
+
+/// Time it takes to fly to a view stored by the user.
+const RECALL_DURATION: Duration = Duration::from_millis(1500);
+
+/// Everything which determines the picture we render.
+pub struct Scene {
+    /// Camera position and zoom level. Determines which part of the fractal we see
+    pub camera: Camera,
+    /// Number of iterations used to determine wether a point converges or not. How fast a point
+    /// converges is used to determine the color of a pixel.
+    ///
+    /// We use a floating point variable to track the number of iterations, so we can easier adapt
+    /// the number of iterations smoothly by pressing buttons for a period of time. This implies we
+    /// need to keep track of differences smaller than 1 between frames.
+    iterations: f32,
+    /// If `true`, the number of iterations grows with the zoom level, using `iterations` as the
+    /// base.
+    auto_iterations: bool,
+    /// Kind of fractal displayed, e.g. Mandelbrot or Julia set.
+    pub kind: FractalKind,
+    /// Index into `PRESET_PALETTES` of the palette the fractal is drawn with. Selected by the user
+    /// through the controls.
+    palette: usize,
+    /// Transition of `iterations` towards the value of a bookmark, while it is ongoing.
+    iteration_easing: Option<IterationEasing>,
+    /// Flight of the camera towards a recalled view, while it is ongoing.
+    camera_flight: Option<CameraFlight>,
+}
+
+impl Scene {
+    pub fn new() -> Self {
+        Scene {
+            camera: Camera::new(),
+            iterations: 256.,
+            auto_iterations: false,
+            kind: FractalKind::Mandelbrot,
+            palette: 0,
+            iteration_easing: None,
+            camera_flight: None,
+        }
+    }
+
+    /// Number of iterations passed to the shader.
+    pub fn iterations(&self) -> i32 {
+        if self.auto_iterations {
+            (f64::from(self.iterations) + extra_iterations(&self.camera)) as i32
+        } else {
+            self.iterations.trunc() as i32
+        }
+    }
+
+    /// Value for `self.iterations`, which results in `total` iterations passed to the shader once
+    /// the camera arrives at `camera`. Stored views and shared scenes hold the total, so in
+    /// automatic mode we must not add the iterations for their zoom level a second time.
+    fn base_iterations(&self, total: i32, camera: &Camera) -> f32 {
+        if self.auto_iterations {
+            // Aiming for the middle between `total` and the next integer, so rounding errors do
+            // not truncate the sum to one less than `total`.
+            (f64::from(total) + 0.5 - extra_iterations(camera)).max(1.) as f32
+        } else {
+            total as f32
+        }
+    }
+
+    /// Everything the canvas needs to render the scene.
+    pub fn render_state(&self) -> RenderState {
+        RenderState::new(self.camera, self.iterations(), self.kind).with_palette(self.palette)
+    }
+
+    /// Index into `PRESET_PALETTES` of the palette the fractal is drawn with. Restoring a view
+    /// changes it, so the controls must pick it up, too.
+    pub fn palette(&self) -> usize {
+        self.palette
+    }
+
+    /// Describes the scene for the overlay on top of the fractal, one line each for zoom level,
+    /// iterations and the coordinates at the center of the view.
+    pub fn hud_lines(&self) -> Vec<String> {
+        let [x, y] = self.camera.position();
+        vec![
+            format!("Zoom: {:.3e}", self.camera.zoom_level()),
+            format!("Iterations: {}", self.iterations()),
+            format!("Center: {x:.12} {y:+.12}i"),
+        ]
+    }
+
+    /// Short summary of zoom level and iterations for the title of the window, e.g.
+    /// `Fractal WGPU — zoom 1.2e3, iter 512`.
+    pub fn window_title(&self) -> String {
+        format!(
+            "Fractal WGPU — zoom {:.1e}, iter {}",
+            self.camera.zoom_level(),
+            self.iterations()
+        )
+    }
+
+    /// Lets the number of iterations grow with the zoom level, like pressing `i` does.
+    pub fn enable_auto_iterations(&mut self) {
+        self.auto_iterations = true;
+    }
+
+    /// Shows the Mandelbrot set at the location of `bookmark`. The number of iterations eases
+    /// towards the one of the bookmark over the next frames, so the colors do not pop.
+    pub fn go_to(&mut self, bookmark: &Bookmark) {
+        self.kind = FractalKind::Mandelbrot;
+        self.camera.set_position(bookmark.position);
+        self.camera.set_zoom(bookmark.zoom);
+        self.camera_flight = None;
+        self.ease_iterations_to(bookmark.iterations, ITERATION_EASING_DURATION);
+    }
+
+    /// Shows the picture described by `state`, e.g. one shared by another user.
+    pub fn restore(&mut self, state: &RenderState) {
+        self.camera = state.camera;
+        self.iterations = self.base_iterations(state.iterations, &state.camera);
+        self.kind = state.kind;
+        self.palette = state.palette.unwrap_or(self.palette);
+        self.iteration_easing = None;
+        self.camera_flight = None;
+    }
+
+    /// Returns to a view stored by the user. If `animate` is `true`, the camera flies there over
+    /// the next frames, while the iterations ease towards the stored ones. Views of another kind of
+    /// fractal are always shown immediately, since there is nothing in between to fly over.
+    pub fn recall(&mut self, state: &RenderState, animate: bool) {
+        if !animate || self.kind != state.kind {
+            self.restore(state);
+            return;
+        }
+        // Blending between palettes is not worth it, so we switch right away.
+        self.palette = state.palette.unwrap_or(self.palette);
+        self.camera_flight = Some(CameraFlight {
+            start: self.camera,
+            target: state.camera,
+            begin: Instant::now(),
+        });
+        let target = self.base_iterations(state.iterations, &state.camera);
+        self.ease_iterations_to(target, RECALL_DURATION);
+    }
+
+    /// `true` while the number of iterations eases towards the one of a bookmark, or the camera
+    /// flies towards a recalled view, so the application should keep rendering.
+    pub fn picture_changes(&self) -> bool {
+        self.iteration_easing.is_some() || self.camera_flight.is_some()
+    }
+
+    /// Applies the user input tracked by `controls`. `canvas_size` is the size of the canvas in
+    /// pixels.
+    pub fn update(&mut self, controls: &mut Controls, canvas_size: (u32, u32)) {
+        let camera = self.camera;
+        controls.update_scene(
+            &mut self.camera,
+            &mut self.iterations,
+            &mut self.kind,
+            canvas_size,
+        );
+        self.auto_iterations = controls.auto_iterations();
+        self.palette = controls.palette_index();
+        if self.camera != camera {
+            // The user took over the camera, so we stop flying it.
+            self.camera_flight = None;
+        }
+        let now = Instant::now();
+        self.ease_iterations(now);
+        self.fly(now);
+    }
+
+    /// Starts easing the number of iterations towards `target` over `duration`.
+    fn ease_iterations_to(&mut self, target: f32, duration: Duration) {
+        let now = Instant::now();
+        self.iteration_easing = Some(IterationEasing {
+            remaining_ln: target.ln() - self.iterations.ln(),
+            last_update: now,
+            end: now + duration,
+        });
+    }
+
+    /// Moves the camera to where the ongoing flight has taken it by `now`.
+    fn fly(&mut self, now: Instant) {
+        let Some(flight) = &self.camera_flight else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(flight.begin);
+        let t = (elapsed.as_secs_f32() / RECALL_DURATION.as_secs_f32()).min(1.);
+        self.camera = flight.start.lerp(&flight.target, t);
+        if t == 1. {
+            // Lands exactly on the stored view, without any rounding errors of the interpolation.
+            self.camera = flight.target;
+            self.camera_flight = None;
+        }
+    }
+
+    /// Moves the number of iterations towards the target of the ongoing easing, by the share of
+    /// the remaining distance the time since the last frame makes up of the remaining duration.
+    fn ease_iterations(&mut self, now: Instant) {
+        let Some(easing) = &mut self.iteration_easing else {
+            return;
+        };
+        let remaining = easing.end.saturating_duration_since(easing.last_update);
+        let elapsed = now.saturating_duration_since(easing.last_update);
+        let share = if elapsed >= remaining {
+            1.
+        } else {
+            elapsed.as_secs_f32() / remaining.as_secs_f32()
+        };
+        // Like `M` and `N` do, we change iterations in log space. Since we only add the step to
+        // the current value, adjusting the iterations manually during the transition still works.
+        let step = easing.remaining_ln * share;
+        self.iterations = (self.iterations.ln() + step).exp();
+        easing.remaining_ln -= step;
+        easing.last_update = now;
+        if share == 1. {
+            self.iteration_easing = None;
+        }
+    }
+}
+
+/// Iterations added in automatic mode at the zoom level of `camera`. Only depends on the zoom
+/// level, so panning does not cause the picture to flicker.
+fn extra_iterations(camera: &Camera) -> f64 {
+    ITERATIONS_PER_ZOOM_E_FOLD * camera.zoom_level().ln().max(0.)
+}
+
+/// Transition of the camera towards a recalled view.
+struct CameraFlight {
+    /// Camera at the point in time the flight began.
+    start: Camera,
+    /// Camera of the recalled view.
+    target: Camera,
+    /// Point in time the flight began.
+    begin: Instant,
+}
+
+/// Transition of the number of iterations towards a target value.
+struct IterationEasing {
+    /// Difference between the natural logarithms of target and current number of iterations, not
+    /// yet applied.
+    remaining_ln: f32,
+    /// Point in time the number of iterations has been moved towards the target last.
+    last_update: Instant,
+    /// Point in time the target is reached.
+    end: Instant,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Scene with auto iterations, zoomed in far enough for them to add up.
+    fn deep_scene() -> Scene {
+        let mut scene = Scene::new();
+        scene.enable_auto_iterations();
+        scene.camera = Camera::try_at([-0.743, 0.126], 1e6).unwrap();
+        scene
+    }
+
+    #[test]
+    fn restoring_a_deep_view_with_auto_iterations_keeps_its_iterations() {
+        let state = deep_scene().render_state();
+
+        let mut scene = Scene::new();
+        scene.enable_auto_iterations();
+        scene.restore(&state);
+
+        assert_eq!(state, scene.render_state());
+    }
+
+    #[test]
+    fn recalling_a_deep_view_with_auto_iterations_keeps_its_iterations() {
+        let state = deep_scene().render_state();
+
+        let mut scene = Scene::new();
+        scene.enable_auto_iterations();
+        scene.recall(&state, true);
+        // Long after the flight has ended
+        let later = Instant::now() + 2 * RECALL_DURATION;
+        scene.ease_iterations(later);
+        scene.fly(later);
+
+        assert!(!scene.picture_changes());
+        assert_eq!(state, scene.render_state());
+    }
+}
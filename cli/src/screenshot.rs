@@ -0,0 +1,43 @@
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Error};
+use image::RgbaImage;
+
+/// File name for a new screenshot, unique per second, e.g. `fractal-1700000000.png`.
+pub fn screenshot_path() -> PathBuf {
+    timestamped_path("fractal")
+}
+
+/// File name for a new high resolution still, unique per second, e.g.
+/// `fractal-still-1700000000.png`. Differs from screenshots, so both can be taken in the same
+/// second.
+pub fn still_path() -> PathBuf {
+    timestamped_path("fractal-still")
+}
+
+/// File name of the preview of the view stored under the number key `key`, e.g. `view-3.png`.
+/// Storing another view under the same key replaces it.
+pub fn preview_path(key: usize) -> PathBuf {
+    PathBuf::from(format!("view-{key}.png"))
+}
+
+/// `<prefix>-<seconds since the epoch>.png`
+fn timestamped_path(prefix: &str) -> PathBuf {
+    let seconds = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|since_epoch| since_epoch.as_secs())
+        .unwrap_or_default();
+    PathBuf::from(format!("{prefix}-{seconds}.png"))
+}
+
+/// Writes tightly packed RGBA8 `pixels` to a PNG file at `path`.
+pub fn save_png(path: &Path, width: u32, height: u32, pixels: Vec<u8>) -> Result<(), Error> {
+    let image = RgbaImage::from_raw(width, height, pixels)
+        .context("Number of pixels does not match the size of the image")?;
+    image
+        .save(path)
+        .with_context(|| format!("Could not write screenshot to {}", path.display()))
+}
@@ -0,0 +1,59 @@
+use std::{fs, path::Path};
+
+use anyhow::{bail, Context, Error};
+use fractal_wgpu_lib::{Backends, Camera, Canvas};
+use log::info;
+
+use crate::{location::load_location, scene::Scene, screenshot::save_png, HEIGHT, WIDTH};
+
+/// Renders the animation described by the command line arguments following the `zoom-sequence`
+/// subcommand: `<start.json> <end.json> <frames> <out_dir>`. Start and end are locations saved by
+/// pressing `k`.
+pub async fn run(args: &[String]) -> Result<(), Error> {
+    let [start, end, frames, out_dir] = args else {
+        bail!("Usage: zoom-sequence <start.json> <end.json> <frames> <out_dir>");
+    };
+    let start = load_location(Path::new(start))?;
+    let end = load_location(Path::new(end))?;
+    let frames: u32 = frames
+        .parse()
+        .ok()
+        .filter(|&count| count > 0)
+        .with_context(|| format!("Number of frames must be a positive integer, not '{frames}'"))?;
+    let canvas = Canvas::new_headless(WIDTH, HEIGHT, Backends::all())
+        .await
+        .context("Error requesting device for drawing")?;
+    render_zoom_sequence(&canvas, &start, &end, frames, Path::new(out_dir))
+}
+
+/// Renders `frames` pictures moving from `start` to `end` and writes them to numbered PNG files in
+/// `out_dir`, e.g. `frame-00000.png`, ready to be assembled into a video. The zoom level changes by
+/// the same factor between any two consecutive frames, so the zoom appears to progress at a
+/// constant speed. Iterations grow with the zoom level, so details remain visible in deep zooms.
+pub fn render_zoom_sequence(
+    canvas: &Canvas,
+    start: &Camera,
+    end: &Camera,
+    frames: u32,
+    out_dir: &Path,
+) -> Result<(), Error> {
+    fs::create_dir_all(out_dir)
+        .with_context(|| format!("Could not create directory {}", out_dir.display()))?;
+    let (width, height) = canvas.size();
+    let mut scene = Scene::new();
+    scene.enable_auto_iterations();
+    for frame in 0..frames {
+        // A single frame shows the start, otherwise first and last frame show start and end.
+        let t = if frames > 1 {
+            frame as f32 / (frames - 1) as f32
+        } else {
+            0.
+        };
+        scene.camera = start.lerp(end, t);
+        let pixels = canvas.capture_frame(&scene.camera, scene.iterations(), &scene.kind)?;
+        let path = out_dir.join(format!("frame-{frame:05}.png"));
+        save_png(&path, width, height, pixels)?;
+        info!("Rendered frame {} of {frames}", frame + 1);
+    }
+    Ok(())
+}
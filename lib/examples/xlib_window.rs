@@ -0,0 +1,76 @@
+//! Draws the Mandelbrot set into a window created with plain Xlib instead of winit. Shows how to
+//! link a [`Canvas`] to a window of any toolkit which can tell its raw handles, e.g. SDL2 or GLFW.
+//!
+//! Requires Linux with a running X server: `cargo run -p fractal-wgpu-lib --example xlib_window`
+
+#[cfg(target_os = "linux")]
+fn main() {
+    xlib::main()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn main() {
+    eprintln!("This example creates its window with Xlib and therefore only runs on Linux.");
+}
+
+#[cfg(target_os = "linux")]
+mod xlib {
+    use std::{
+        ptr, thread,
+        time::{Duration, Instant},
+    };
+
+    use fractal_wgpu_lib::{
+        raw_window_handle::{
+            RawDisplayHandle, RawWindowHandle, XlibDisplayHandle, XlibWindowHandle,
+        },
+        Backends, Canvas, RenderState, SurfaceTarget,
+    };
+    use x11_dl::xlib::Xlib;
+
+    const WIDTH: u32 = 800;
+    const HEIGHT: u32 = 600;
+    /// The example does not process any events of the window, it just shows it for a while.
+    const SHOW_FOR: Duration = Duration::from_secs(5);
+
+    pub fn main() {
+        let xlib = Xlib::open().expect("libX11 must be installed");
+        unsafe {
+            let display = (xlib.XOpenDisplay)(ptr::null());
+            assert!(!display.is_null(), "Could not connect to the X server");
+            let screen = (xlib.XDefaultScreen)(display);
+            let root = (xlib.XRootWindow)(display, screen);
+            let window = (xlib.XCreateSimpleWindow)(display, root, 0, 0, WIDTH, HEIGHT, 0, 0, 0);
+            (xlib.XStoreName)(display, window, c"Fractal WGPU on Xlib".as_ptr());
+            (xlib.XMapWindow)(display, window);
+            (xlib.XFlush)(display);
+
+            let mut window_handle = XlibWindowHandle::empty();
+            window_handle.window = window;
+            let mut display_handle = XlibDisplayHandle::empty();
+            display_handle.display = display.cast();
+            display_handle.screen = screen;
+            // Safety: The window and the connection to the display outlive the canvas, which is
+            // dropped at the end of `show`.
+            let target = SurfaceTarget::from_raw(
+                RawWindowHandle::Xlib(window_handle),
+                RawDisplayHandle::Xlib(display_handle),
+            );
+            show(target);
+
+            (xlib.XDestroyWindow)(display, window);
+            (xlib.XCloseDisplay)(display);
+        }
+    }
+
+    fn show(target: SurfaceTarget<'static>) {
+        let canvas = pollster::block_on(Canvas::new(WIDTH, HEIGHT, target, Backends::all()))
+            .expect("Error requesting device for drawing");
+        let state = RenderState::default();
+        let start = Instant::now();
+        while start.elapsed() < SHOW_FOR {
+            canvas.render(&state).expect("Error rendering frame");
+            thread::sleep(Duration::from_millis(16));
+        }
+    }
+}
@@ -0,0 +1,40 @@
+/// Copies an intermediate render target onto the output. Conversion into the output format (e.g.
+/// quantization to 8 bit) happens when writing the fragment.
+
+@group(0) @binding(0)
+var SOURCE: texture_2d<f32>;
+@group(0) @binding(1)
+var SOURCE_SAMPLER: sampler;
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) tex_coords: vec2<f32>,
+};
+
+/// Emits a single triangle covering the entire output, so we do not need a vertex buffer.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOutput {
+    // (-1, -1), (3, -1), (-1, 3)
+    let x = f32((index << 1u) & 2u) * 2.0 - 1.0;
+    let y = f32(index & 2u) * 2.0 - 1.0;
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(x, y, 0.0, 1.0);
+    // Texture coordinates start at the top left, clip space at the bottom left.
+    out.tex_coords = vec2<f32>((x + 1.0) * 0.5, (1.0 - y) * 0.5);
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return textureSample(SOURCE, SOURCE_SAMPLER, in.tex_coords);
+}
+
+/// Like `fs_main`, but only keeps the circle inscribed into the output rectangle.
+@fragment
+fn fs_circle(in: VertexOutput) -> @location(0) vec4<f32> {
+    let color = textureSample(SOURCE, SOURCE_SAMPLER, in.tex_coords);
+    if (distance(in.tex_coords, vec2<f32>(0.5, 0.5)) > 0.5) {
+        discard;
+    }
+    return color;
+}
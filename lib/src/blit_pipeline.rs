@@ -0,0 +1,352 @@
+use wgpu::{
+    AddressMode, BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout,
+    BindGroupLayoutDescriptor, BindGroupLayoutEntry, BindingResource, BindingType, BlendState,
+    Color, ColorTargetState, ColorWrites, CommandEncoder, Device, FilterMode, FragmentState,
+    LoadOp, MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor, PrimitiveState,
+    PrimitiveTopology, RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline,
+    RenderPipelineDescriptor, Sampler, SamplerBindingType, SamplerDescriptor, ShaderModule,
+    ShaderModuleDescriptor, ShaderSource, ShaderStages, Texture, TextureDescriptor,
+    TextureDimension, TextureFormat, TextureSampleType, TextureUsages, TextureView,
+    TextureViewDescriptor, TextureViewDimension, VertexState,
+};
+
+/// Source used to compile the blit shader at startup
+const BLIT_SHADER_SOURCE: &str = include_str!("blit.wgsl");
+
+/// The texture to copy from and its sampler are bound in the fragment shader stage.
+const SOURCE_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
+    label: Some("Blit Source Bind Group Layout"),
+    entries: &[
+        BindGroupLayoutEntry {
+            // Must match shader index
+            binding: 0,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Texture {
+                sample_type: TextureSampleType::Float { filterable: true },
+                view_dimension: TextureViewDimension::D2,
+                multisampled: false,
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            // Must match shader index
+            binding: 1,
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Sampler(SamplerBindingType::Filtering),
+            count: None,
+        },
+    ],
+};
+
+/// Copies the contents of an intermediate texture to the output, converting it to the format of
+/// the output on the way.
+pub struct BlitPipeline {
+    render_pipeline: RenderPipeline,
+    /// Like `render_pipeline`, but only copies a circular region.
+    circle_pipeline: RenderPipeline,
+    /// Like `render_pipeline`, but blends the source on top of the output using its alpha.
+    overlay_pipeline: RenderPipeline,
+    source_layout: BindGroupLayout,
+    sampler: Sampler,
+}
+
+impl BlitPipeline {
+    /// * `device` is used to create the render pipeline and load the shader.
+    /// * `target_format` is the format of the output of the blit pass.
+    pub fn new(device: &Device, target_format: TextureFormat) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Blit Shader"),
+            source: ShaderSource::Wgsl(BLIT_SHADER_SOURCE.into()),
+        });
+        let source_layout = device.create_bind_group_layout(&SOURCE_LAYOUT);
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Blit Pipeline Layout"),
+            bind_group_layouts: &[&source_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = create_render_pipeline(
+            device,
+            &layout,
+            &shader,
+            "fs_main",
+            target_format,
+            BlendState::REPLACE,
+        );
+        let circle_pipeline = create_render_pipeline(
+            device,
+            &layout,
+            &shader,
+            "fs_circle",
+            target_format,
+            BlendState::REPLACE,
+        );
+        let overlay_pipeline = create_render_pipeline(
+            device,
+            &layout,
+            &shader,
+            "fs_main",
+            target_format,
+            BlendState::ALPHA_BLENDING,
+        );
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("Blit Sampler"),
+            address_mode_u: AddressMode::ClampToEdge,
+            address_mode_v: AddressMode::ClampToEdge,
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..SamplerDescriptor::default()
+        });
+        BlitPipeline {
+            render_pipeline,
+            circle_pipeline,
+            overlay_pipeline,
+            source_layout,
+            sampler,
+        }
+    }
+
+    /// Creates an intermediate render target which can be copied to the output by this pipeline.
+    pub fn intermediate(
+        &self,
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Intermediate {
+        // Copying into the texture allows uploading pictures from the CPU, too.
+        let usage = TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::TEXTURE_BINDING
+            | TextureUsages::COPY_DST;
+        self.create_intermediate(device, width, height, format, usage)
+    }
+
+    /// Like [`Self::intermediate`], but written to as storage texture by a compute shader, rather
+    /// than rendered into.
+    pub fn storage_intermediate(
+        &self,
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+    ) -> Intermediate {
+        let usage = TextureUsages::STORAGE_BINDING | TextureUsages::TEXTURE_BINDING;
+        self.create_intermediate(device, width, height, format, usage)
+    }
+
+    fn create_intermediate(
+        &self,
+        device: &Device,
+        width: u32,
+        height: u32,
+        format: TextureFormat,
+        usage: TextureUsages,
+    ) -> Intermediate {
+        let texture = device.create_texture(&TextureDescriptor {
+            label: Some("Intermediate Render Target"),
+            size: wgpu::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: TextureDimension::D2,
+            format,
+            usage,
+            view_formats: &[],
+        });
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let bind_group = device.create_bind_group(&BindGroupDescriptor {
+            label: Some("Blit Source Bind Group"),
+            layout: &self.source_layout,
+            entries: &[
+                BindGroupEntry {
+                    binding: 0,
+                    resource: BindingResource::TextureView(&view),
+                },
+                BindGroupEntry {
+                    binding: 1,
+                    resource: BindingResource::Sampler(&self.sampler),
+                },
+            ],
+        });
+        Intermediate {
+            width,
+            height,
+            texture,
+            view,
+            bind_group,
+        }
+    }
+
+    /// Records copying `source` into `output`.
+    pub fn draw_to(
+        &self,
+        source: &Intermediate,
+        output: &TextureView,
+        encoder: &mut CommandEncoder,
+    ) {
+        let rpd = RenderPassDescriptor {
+            label: Some("Blit Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Clear(Color::BLACK),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        };
+        let mut render_pass = encoder.begin_render_pass(&rpd);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, &source.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Records copying `source` into the rectangle `viewport` (`[x, y, width, height]` in pixels)
+    /// of `output`. The rest of `output` is cleared with `background`, or preserved if it is
+    /// `None`.
+    pub fn draw_placed_to(
+        &self,
+        source: &Intermediate,
+        output: &TextureView,
+        viewport: [f32; 4],
+        background: Option<Color>,
+        encoder: &mut CommandEncoder,
+    ) {
+        let rpd = RenderPassDescriptor {
+            label: Some("Blit Placed Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: background.map_or(LoadOp::Load, LoadOp::Clear),
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        };
+        let [x, y, width, height] = viewport;
+        let mut render_pass = encoder.begin_render_pass(&rpd);
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_viewport(x, y, width, height, 0., 1.);
+        render_pass.set_bind_group(0, &source.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Records copying the circle inscribed in `source` into the square `viewport` (`[x, y,
+    /// size]` in pixels) of `output`. The rest of `output` is preserved.
+    pub fn draw_circle_to(
+        &self,
+        source: &Intermediate,
+        output: &TextureView,
+        viewport: [u32; 3],
+        encoder: &mut CommandEncoder,
+    ) {
+        let rpd = RenderPassDescriptor {
+            label: Some("Blit Circle Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        };
+        let [x, y, size] = viewport;
+        let mut render_pass = encoder.begin_render_pass(&rpd);
+        render_pass.set_pipeline(&self.circle_pipeline);
+        render_pass.set_viewport(x as f32, y as f32, size as f32, size as f32, 0., 1.);
+        render_pass.set_bind_group(0, &source.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+
+    /// Records blending `source` on top of the rectangle `viewport` (`[x, y, width, height]` in
+    /// pixels) of `output`, using the alpha channel of `source`. The rest of `output` is preserved.
+    pub fn draw_overlay_to(
+        &self,
+        source: &Intermediate,
+        output: &TextureView,
+        viewport: [u32; 4],
+        encoder: &mut CommandEncoder,
+    ) {
+        let rpd = RenderPassDescriptor {
+            label: Some("Blit Overlay Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        };
+        let [x, y, width, height] = viewport.map(|value| value as f32);
+        let mut render_pass = encoder.begin_render_pass(&rpd);
+        render_pass.set_pipeline(&self.overlay_pipeline);
+        render_pass.set_viewport(x, y, width, height, 0., 1.);
+        render_pass.set_bind_group(0, &source.bind_group, &[]);
+        render_pass.draw(0..3, 0..1);
+    }
+}
+
+fn create_render_pipeline(
+    device: &Device,
+    layout: &PipelineLayout,
+    shader: &ShaderModule,
+    fragment_entry_point: &str,
+    target_format: TextureFormat,
+    blend: BlendState,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Blit Render Pipeline"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[],
+        },
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point: fragment_entry_point,
+            targets: &[Some(ColorTargetState {
+                format: target_format,
+                blend: Some(blend),
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            ..PrimitiveState::default()
+        },
+        depth_stencil: None,
+        multiview: None,
+        multisample: MultisampleState::default(),
+    })
+}
+
+/// A texture the fractal is rendered into, before it is copied to the output by the
+/// [`BlitPipeline`].
+pub struct Intermediate {
+    /// Width of the texture in pixels.
+    pub width: u32,
+    /// Height of the texture in pixels.
+    pub height: u32,
+    /// Owns the memory `view` refers to.
+    texture: Texture,
+    /// Render target for the canvas render pipeline.
+    pub view: TextureView,
+    /// Binds the texture as source to the blit pipeline.
+    bind_group: BindGroup,
+}
+
+impl Intermediate {
+    /// The texture holding the picture, e.g. to upload pixels into it.
+    pub fn texture(&self) -> &Texture {
+        &self.texture
+    }
+}
@@ -1,7 +1,32 @@
+use std::fmt::{self, Display, Formatter};
+
+use log::warn;
+
+use crate::FractalKind;
+
+/// Default for the maximum zoom level. Beyond it the emulated double precision of the shader is no
+/// longer able to resolve neighbouring pixels.
+pub const DEFAULT_MAX_ZOOM: f64 = 1e11;
+/// Zooming out further would make the fractal vanish in a single pixel and eventually lead to
+/// overflows.
+const MIN_ZOOM: f64 = 1e-3;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(try_from = "CameraFields")
+)]
 pub struct Camera {
-    pos_x: f32,
-    pos_y: f32,
-    zoom: f32,
+    // Position and zoom are kept in double precision, so we can zoom deeper than single precision
+    // floats could resolve. They are split into pairs of single precision floats for the shader.
+    pos_x: f64,
+    pos_y: f64,
+    zoom: f64,
+    /// Zooming stops at this level, rather than breaking down the picture. Not part of a saved
+    /// location.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    max_zoom: f64,
 }
 
 impl Camera {
@@ -10,30 +35,359 @@ impl Camera {
             pos_x: -0.5,
             pos_y: 0.0,
             zoom: 1.0,
+            max_zoom: DEFAULT_MAX_ZOOM,
+        }
+    }
+
+    /// Camera centered on `(x, y)` in the coordinate system of the fractal, zoomed in by `zoom`.
+    /// Unlike [`Self::at`] this always succeeds, clamping `zoom` like [`Self::set_zoom`] does.
+    pub fn new_at(x: f64, y: f64, zoom: f64) -> Self {
+        let mut camera = Camera::new();
+        camera.set_position([x, y]);
+        camera.set_zoom(zoom);
+        camera
+    }
+
+    /// Camera centered on `position` in the coordinate system of the fractal, zoomed in by `zoom`.
+    /// Fails if this would result in a broken view, i.e. if any value is not finite or `zoom` is
+    /// not positive. Neither clamps nor otherwise alters its arguments, so the camera holds exactly
+    /// the values passed, e.g. to test the coordinate transformations with arbitrary cameras.
+    pub fn try_at(position: [f64; 2], zoom: f64) -> Result<Camera, InvalidCamera> {
+        let [pos_x, pos_y] = position;
+        if !pos_x.is_finite() || !pos_y.is_finite() {
+            return Err(InvalidCamera::Position(position));
+        }
+        if !zoom.is_finite() || zoom <= 0. {
+            return Err(InvalidCamera::Zoom(zoom));
+        }
+        Ok(Camera {
+            pos_x,
+            pos_y,
+            zoom,
+            max_zoom: DEFAULT_MAX_ZOOM,
+        })
+    }
+
+    /// Returns to the initial position and zoom level of [`Self::new`]. Keeps the maximum zoom
+    /// level.
+    pub fn reset(&mut self) {
+        self.show_overview(&FractalKind::Mandelbrot);
+    }
+
+    /// Moves to the position and zoom level of [`Self::overview`]. Keeps the maximum zoom level.
+    pub fn show_overview(&mut self, kind: &FractalKind) {
+        *self = Camera {
+            max_zoom: self.max_zoom,
+            ..Camera::overview(kind)
+        };
+    }
+
+    /// Camera showing the interesting region of `kind` in its entirety.
+    pub fn overview(kind: &FractalKind) -> Self {
+        match kind {
+            // The ship is larger than the Mandelbrot set and lies mostly below the real axis.
+            FractalKind::BurningShip => Camera {
+                pos_x: -0.4,
+                pos_y: -0.5,
+                zoom: 0.6,
+                max_zoom: DEFAULT_MAX_ZOOM,
+            },
+            // The arms of the tricorn reach further up and down than the Mandelbrot set.
+            FractalKind::Tricorn => Camera {
+                pos_x: -0.2,
+                pos_y: 0.0,
+                zoom: 0.8,
+                max_zoom: DEFAULT_MAX_ZOOM,
+            },
+            // The three roots lie on the unit circle around the origin.
+            FractalKind::Newton => Camera {
+                pos_x: 0.0,
+                pos_y: 0.0,
+                zoom: 0.6,
+                max_zoom: DEFAULT_MAX_ZOOM,
+            },
+            FractalKind::Mandelbrot | FractalKind::Julia { .. } => Camera::new(),
         }
     }
 
     /// Inverse view matrix, transforms from canvas space, to the space of the coordinate system.
     ///
-    /// Translates and zooms. Columnwise defined.
+    /// Translates and zooms. Columnwise defined. The translation is rounded to single precision,
+    /// use [`Self::position`] if you need it exactly.
     pub fn inv_view(&self) -> [[f32; 2]; 3] {
+        self.inv_view_for(1, 1)
+    }
+
+    /// Like [`Self::inv_view`], but corrects for the aspect ratio of a canvas with the given size
+    /// in pixels, so circles stay circular. The shorter axis spans the same range as in
+    /// [`Self::inv_view`], the longer one is extended. Identical to [`Self::inv_view`] for square
+    /// canvases.
+    pub fn inv_view_for(&self, width: u32, height: u32) -> [[f32; 2]; 3] {
         // [ 1/z  0   tx]    | x |   | x/z + tx |
         // [  0  1/z  ty]  x | y | = | y/z - ty |
         //                   | 1 |
+        let [scale_x, scale_y] = aspect_scale(width, height);
+        let zoom = self.zoom as f32;
         [
-            [1. / self.zoom, 0.],
-            [0., 1. / self.zoom],
-            [self.pos_x, self.pos_y],
+            [scale_x / zoom, 0.],
+            [0., scale_y / zoom],
+            [self.pos_x as f32, self.pos_y as f32],
         ]
     }
 
+    /// Position of the center of the view in the coordinate system of the fractal.
+    pub fn position(&self) -> [f64; 2] {
+        [self.pos_x, self.pos_y]
+    }
+
+    /// Moves the center of the view to `position` in the coordinate system of the fractal.
+    /// Positions which are not finite are ignored, see [`Self::try_at`].
+    pub fn set_position(&mut self, position: [f64; 2]) {
+        if !position.iter().all(|coordinate| coordinate.is_finite()) {
+            warn!("Ignoring invalid camera position {position:?}.");
+            return;
+        }
+        [self.pos_x, self.pos_y] = position;
+    }
+
+    /// Transforms normalized device coordinates (`-1..1` in both axes, `y` pointing up) of a
+    /// canvas with the given size in pixels into the complex plane. Inverse of
+    /// [`Self::complex_to_screen`].
+    pub fn screen_to_complex(&self, ndc: [f32; 2], width: u32, height: u32) -> [f64; 2] {
+        let [scale_x, scale_y] = aspect_scale(width, height);
+        [
+            f64::from(ndc[0] * scale_x) / self.zoom + self.pos_x,
+            f64::from(ndc[1] * scale_y) / self.zoom + self.pos_y,
+        ]
+    }
+
+    /// Smallest and largest coordinates of the complex plane visible on a canvas with the given
+    /// size in pixels, as `([min_re, min_im], [max_re, max_im])`. Accounts for the aspect ratio
+    /// like [`Self::inv_view_for`] does.
+    pub fn visible_bounds(&self, width: u32, height: u32) -> ([f64; 2], [f64; 2]) {
+        let min = self.screen_to_complex([-1., -1.], width, height);
+        let max = self.screen_to_complex([1., 1.], width, height);
+        (min, max)
+    }
+
+    /// Transforms a point of the complex plane into normalized device coordinates of a canvas with
+    /// the given size in pixels. Inverse of [`Self::screen_to_complex`].
+    pub fn complex_to_screen(&self, complex: [f64; 2], width: u32, height: u32) -> [f32; 2] {
+        let [scale_x, scale_y] = aspect_scale(width, height);
+        [
+            ((complex[0] - self.pos_x) * self.zoom) as f32 / scale_x,
+            ((complex[1] - self.pos_y) * self.zoom) as f32 / scale_y,
+        ]
+    }
+
+    /// A camera centered on the point under `ndc` of a canvas with the given size in pixels,
+    /// zoomed in further by `magnification`.
+    pub fn magnified_at(
+        &self,
+        ndc: [f32; 2],
+        width: u32,
+        height: u32,
+        magnification: f32,
+    ) -> Camera {
+        let [pos_x, pos_y] = self.screen_to_complex(ndc, width, height);
+        Camera {
+            pos_x,
+            pos_y,
+            zoom: self.clamp_zoom(self.zoom * f64::from(magnification)),
+            max_zoom: self.max_zoom,
+        }
+    }
+
+    /// A camera showing only a rectangular section of the picture this camera renders onto a
+    /// canvas of `width` times `height` pixels. The section starts at pixel `(x, y)`, counted from
+    /// the top left, and spans `tile_width` times `tile_height` pixels. Rendering the returned
+    /// camera onto a canvas of the section's size yields exactly these pixels of the full picture,
+    /// so sections can be stitched together seamlessly.
+    pub fn tile(
+        &self,
+        width: u32,
+        height: u32,
+        x: u32,
+        y: u32,
+        tile_width: u32,
+        tile_height: u32,
+    ) -> Camera {
+        // The shorter side of a canvas spans two units at zoom level one. We compute the offset of
+        // the tile from integer pixel positions, so neighbouring tiles line up exactly.
+        let pixel_size = 2. / (f64::from(width.min(height)) * self.zoom);
+        let center_x = f64::from(x) + f64::from(tile_width) / 2. - f64::from(width) / 2.;
+        let center_y = f64::from(y) + f64::from(tile_height) / 2. - f64::from(height) / 2.;
+        Camera {
+            pos_x: self.pos_x + center_x * pixel_size,
+            // Pixel rows grow downwards, while the imaginary axis points upwards.
+            pos_y: self.pos_y - center_y * pixel_size,
+            zoom: self.zoom * f64::from(width.min(height)) / f64::from(tile_width.min(tile_height)),
+            max_zoom: self.max_zoom,
+        }
+    }
+
+    /// Camera in between `self` (`t = 0`) and `target` (`t = 1`). Zoom is interpolated
+    /// geometrically, so zooming at a constant rate of `t` feels uniform. The position moves in
+    /// proportion to the change of the visible extent, so the center of `target` approaches the
+    /// center of the view steadily. Useful to animate the transition between two views.
+    pub fn lerp(&self, target: &Camera, t: f32) -> Camera {
+        let t = f64::from(t);
+        let lerp = |from: f64, to: f64| from * (1. - t) + to * t;
+        // Like the iterations in `Controls`, we interpolate zoom in log space.
+        let zoom = lerp(self.zoom.ln(), target.zoom.ln()).exp();
+        // Moving linearly would race past the target while the view is still wide, and leave it
+        // out of sight while zooming in.
+        let extent_change = 1. / self.zoom - 1. / target.zoom;
+        let progress = if extent_change == 0. {
+            t
+        } else {
+            (1. / self.zoom - 1. / zoom) / extent_change
+        };
+        let move_towards = |from: f64, to: f64| from + (to - from) * progress;
+        Camera {
+            pos_x: move_towards(self.pos_x, target.pos_x),
+            pos_y: move_towards(self.pos_y, target.pos_y),
+            zoom,
+            max_zoom: self.max_zoom,
+        }
+    }
+
+    /// Zoom by `factor` towards the center of the view. The zoom level stays within a sensible
+    /// range, see [`Self::set_max_zoom`].
     pub fn zoom(&mut self, factor: f32) {
-        self.zoom *= factor;
+        self.zoom = self.clamp_zoom(self.zoom * f64::from(factor));
+    }
+
+    /// Zooming in stops at this level, rather than breaking down the picture once the shader can no
+    /// longer resolve neighbouring pixels. Defaults to [`DEFAULT_MAX_ZOOM`]. Values below the
+    /// smallest zoom level are raised to it, values which are not finite are ignored.
+    pub fn set_max_zoom(&mut self, max_zoom: f64) {
+        if !max_zoom.is_finite() {
+            warn!("Ignoring invalid maximum zoom level {max_zoom}.");
+            return;
+        }
+        self.max_zoom = max_zoom.max(MIN_ZOOM);
+        self.zoom = self.zoom.min(self.max_zoom);
+    }
+
+    /// Zooming in stops at this level. See [`Self::set_max_zoom`].
+    pub fn max_zoom(&self) -> f64 {
+        self.max_zoom
+    }
+
+    /// Current zoom level. `1.0` for the initial view, larger values mean we are zoomed in.
+    pub fn zoom_level(&self) -> f64 {
+        self.zoom
+    }
+
+    /// Jumps to the zoom level `zoom`, keeping the center of the view. Like [`Self::zoom`] the
+    /// level stays within a sensible range. `NaN` is ignored.
+    pub fn set_zoom(&mut self, zoom: f64) {
+        if zoom.is_nan() {
+            warn!("Ignoring invalid zoom level {zoom}.");
+            return;
+        }
+        self.zoom = self.clamp_zoom(zoom);
+    }
+
+    /// Zoom by `factor`, keeping the point `complex` (in the complex plane) at the same position on
+    /// screen.
+    pub fn zoom_around(&mut self, factor: f32, complex: [f64; 2]) {
+        let zoom = self.clamp_zoom(self.zoom * f64::from(factor));
+        // Clamping may reduce the factor, so we pin `complex` using the factor actually applied.
+        let factor = zoom / self.zoom;
+        self.pos_x = complex[0] + (self.pos_x - complex[0]) / factor;
+        self.pos_y = complex[1] + (self.pos_y - complex[1]) / factor;
+        self.zoom = zoom;
+    }
+
+    /// Zoom by `factor`, keeping the point under `cursor` at the same position on screen. `cursor`
+    /// is given in normalized device coordinates of a canvas with the given size in pixels.
+    pub fn zoom_about(&mut self, factor: f32, cursor: [f32; 2], width: u32, height: u32) {
+        let complex = self.screen_to_complex(cursor, width, height);
+        self.zoom_around(factor, complex);
     }
 
     pub fn change_pos(&mut self, delta_x: f32, delta_y: f32) {
-        self.pos_x += delta_x / self.zoom;
-        self.pos_y += delta_y / self.zoom
+        self.pos_x += f64::from(delta_x) / self.zoom;
+        self.pos_y += f64::from(delta_y) / self.zoom
+    }
+
+    /// Restricts `zoom` to the range between [`MIN_ZOOM`] and `max_zoom`. Warns once the maximum is
+    /// reached, rather than on every attempt to exceed it.
+    fn clamp_zoom(&self, zoom: f64) -> f64 {
+        if zoom > self.max_zoom && self.zoom < self.max_zoom {
+            warn!("Maximum zoom level {:e} reached.", self.max_zoom);
+        }
+        zoom.clamp(MIN_ZOOM, self.max_zoom)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Camera {
+    /// Serializes position and zoom level, so the location can be restored later using
+    /// [`Self::from_json`].
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("Camera must be serializable to JSON")
+    }
+
+    /// Restores a camera saved with [`Self::to_json`]. Fails if the JSON is malformed, or if
+    /// position or zoom level would result in a broken view, e.g. because they are not finite.
+    pub fn from_json(json: &str) -> Result<Camera, serde_json::Error> {
+        serde_json::from_str(json)
+    }
+}
+
+/// Unvalidated fields of a deserialized [`Camera`].
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CameraFields {
+    pos_x: f64,
+    pos_y: f64,
+    zoom: f64,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<CameraFields> for Camera {
+    type Error = InvalidCamera;
+
+    fn try_from(fields: CameraFields) -> Result<Self, Self::Error> {
+        let CameraFields { pos_x, pos_y, zoom } = fields;
+        Camera::try_at([pos_x, pos_y], zoom)
+    }
+}
+
+/// Reasons position and zoom level may not make up a [`Camera`], see [`Camera::try_at`].
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InvalidCamera {
+    /// The position is not finite.
+    Position([f64; 2]),
+    /// The zoom level is not finite or not positive.
+    Zoom(f64),
+}
+
+impl Display for InvalidCamera {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            InvalidCamera::Position([x, y]) => {
+                write!(f, "Camera position ({x}, {y}) must be finite.")
+            }
+            InvalidCamera::Zoom(zoom) => {
+                write!(f, "Zoom level {zoom} must be finite and positive.")
+            }
+        }
+    }
+}
+
+impl std::error::Error for InvalidCamera {}
+
+/// Factors by which the x and y axis are stretched, so a canvas with the given size shows
+/// undistorted pictures. The shorter axis is never stretched.
+fn aspect_scale(width: u32, height: u32) -> [f32; 2] {
+    if width >= height {
+        [width as f32 / height as f32, 1.]
+    } else {
+        [1., height as f32 / width as f32]
     }
 }
 
@@ -42,3 +396,299 @@ impl Default for Camera {
         Self::new()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::{Camera, InvalidCamera, DEFAULT_MAX_ZOOM, MIN_ZOOM};
+
+    /// Cameras around the fractals, from entirely zoomed out to the deepest zoom level.
+    fn any_camera() -> impl Strategy<Value = Camera> {
+        (
+            -4.0..4.0,
+            -4.0..4.0,
+            MIN_ZOOM.log10()..DEFAULT_MAX_ZOOM.log10(),
+        )
+            .prop_map(|(x, y, zoom_exponent)| {
+                Camera::try_at([x, y], 10f64.powf(zoom_exponent)).unwrap()
+            })
+    }
+
+    proptest! {
+        #[test]
+        fn try_at_accepts_exactly_finite_views(
+            x in any::<f64>(),
+            y in any::<f64>(),
+            zoom in any::<f64>(),
+        ) {
+            let valid = x.is_finite() && y.is_finite() && zoom.is_finite() && zoom > 0.;
+
+            let camera = Camera::try_at([x, y], zoom);
+
+            prop_assert_eq!(valid, camera.is_ok());
+            if let Ok(camera) = camera {
+                prop_assert_eq!([x, y], camera.position());
+                prop_assert_eq!(zoom, camera.zoom_level());
+            }
+        }
+
+        #[test]
+        fn setters_keep_camera_valid(
+            camera in any_camera(),
+            position in [any::<f64>(), any::<f64>()],
+            zoom in any::<f64>(),
+            max_zoom in any::<f64>(),
+        ) {
+            let mut camera = camera;
+
+            camera.set_max_zoom(max_zoom);
+            camera.set_position(position);
+            camera.set_zoom(zoom);
+
+            prop_assert!(Camera::try_at(camera.position(), camera.zoom_level()).is_ok());
+            prop_assert!(camera.max_zoom().is_finite());
+            prop_assert!(camera.zoom_level() <= camera.max_zoom());
+        }
+
+        #[test]
+        fn screen_coordinates_survive_round_trip_through_complex_plane(
+            camera in any_camera(),
+            ndc in [-1f32..1., -1f32..1.],
+            width in 1u32..4096,
+            height in 1u32..4096,
+        ) {
+            let complex = camera.screen_to_complex(ndc, width, height);
+            let [x, y] = camera.complex_to_screen(complex, width, height);
+
+            prop_assert!((x - ndc[0]).abs() < 1e-3, "{x} != {}", ndc[0]);
+            prop_assert!((y - ndc[1]).abs() < 1e-3, "{y} != {}", ndc[1]);
+        }
+
+        #[test]
+        fn visible_bounds_contain_every_point_on_screen(
+            camera in any_camera(),
+            ndc in [-1f32..1., -1f32..1.],
+            width in 1u32..4096,
+            height in 1u32..4096,
+        ) {
+            let ([min_re, min_im], [max_re, max_im]) = camera.visible_bounds(width, height);
+            let [re, im] = camera.screen_to_complex(ndc, width, height);
+
+            prop_assert!(min_re <= re && re <= max_re);
+            prop_assert!(min_im <= im && im <= max_im);
+            prop_assert!(camera.inv_view_for(width, height).iter().flatten().all(|v| v.is_finite()));
+        }
+    }
+
+    #[test]
+    fn try_at_tells_why_view_is_broken() {
+        assert_eq!(
+            Err(InvalidCamera::Position([f64::INFINITY, 0.])),
+            Camera::try_at([f64::INFINITY, 0.], 1.)
+        );
+        assert_eq!(Err(InvalidCamera::Zoom(0.)), Camera::try_at([0., 0.], 0.));
+    }
+
+    #[test]
+    fn square_canvas_leaves_inverse_view_unchanged() {
+        let mut camera = Camera::new();
+        camera.zoom(3.);
+        camera.change_pos(0.25, -0.5);
+
+        assert_eq!(camera.inv_view(), camera.inv_view_for(640, 640));
+    }
+
+    #[test]
+    fn zooming_about_cursor_keeps_point_under_it_pinned() {
+        let mut camera = Camera::new();
+        let cursor = [0.6, -0.3];
+        let pinned = camera.screen_to_complex(cursor, 800, 600);
+
+        for _ in 0..50 {
+            camera.zoom_about(1.1, cursor, 800, 600);
+        }
+
+        let [x, y] = camera.screen_to_complex(cursor, 800, 600);
+        assert!((x - pinned[0]).abs() < 1e-12);
+        assert!((y - pinned[1]).abs() < 1e-12);
+    }
+
+    #[test]
+    fn complex_point_survives_round_trip_through_screen() {
+        let mut camera = Camera::new();
+        camera.zoom(250.);
+        camera.change_pos(-80., 30.);
+        let complex = [-0.745, 0.113];
+
+        let ndc = camera.complex_to_screen(complex, 1024, 768);
+        let [x, y] = camera.screen_to_complex(ndc, 1024, 768);
+
+        assert!((x - complex[0]).abs() < 1e-6);
+        assert!((y - complex[1]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn tile_maps_its_pixels_like_the_full_picture() {
+        let mut camera = Camera::new();
+        camera.zoom(30.);
+        camera.change_pos(-7., 4.);
+        let (width, height) = (300, 200);
+        // Bottom right tile, cut short by the border of the picture.
+        let (x, y, tile_width, tile_height) = (256, 128, 44, 72);
+
+        let tile = camera.tile(width, height, x, y, tile_width, tile_height);
+
+        // Top left corner of the tile in normalized device coordinates of either canvas.
+        let full = [
+            2. * x as f32 / width as f32 - 1.,
+            1. - 2. * y as f32 / height as f32,
+        ];
+        let expected = camera.screen_to_complex(full, width, height);
+        let actual = tile.screen_to_complex([-1., 1.], tile_width, tile_height);
+        assert!((expected[0] - actual[0]).abs() < 1e-6);
+        assert!((expected[1] - actual[1]).abs() < 1e-6);
+    }
+
+    #[test]
+    fn camera_try_at_rejects_broken_views() {
+        let camera = Camera::try_at([-0.743, 0.126], 2000.).unwrap();
+        assert_eq!([-0.743, 0.126], camera.position());
+        assert_eq!(2000., camera.zoom_level());
+
+        assert!(Camera::try_at([f64::NAN, 0.], 1.).is_err());
+        assert!(Camera::try_at([0., f64::INFINITY], 1.).is_err());
+        assert!(Camera::try_at([0., 0.], 0.).is_err());
+    }
+
+    #[test]
+    fn lerp_starts_at_self_and_ends_at_target() {
+        let start = Camera::new();
+        let target = Camera::try_at([-0.743, 0.126], 2000.).unwrap();
+
+        let at_start = start.lerp(&target, 0.);
+        let at_end = start.lerp(&target, 1.);
+        let halfway = start.lerp(&target, 0.5);
+
+        assert_eq!(start.position(), at_start.position());
+        assert!((at_start.zoom_level() - start.zoom_level()).abs() < 1e-12);
+        assert_eq!(target.position(), at_end.position());
+        assert!((at_end.zoom_level() / target.zoom_level() - 1.).abs() < 1e-12);
+        // Geometric mean of both zoom levels
+        assert!((halfway.zoom_level() - 2000f64.sqrt()).abs() < 1e-9);
+    }
+
+    #[test]
+    fn visible_bounds_of_default_view() {
+        let camera = Camera::new();
+
+        let (min, max) = camera.visible_bounds(400, 400);
+        let (wide_min, wide_max) = camera.visible_bounds(800, 400);
+
+        // Square canvas shows the main cardioid and the period-2 bulb
+        assert_eq!(([-1.5, -1.], [0.5, 1.]), (min, max));
+        // Longer side of the canvas shows more of the plane
+        assert_eq!(([-2.5, -1.], [1.5, 1.]), (wide_min, wide_max));
+    }
+
+    #[test]
+    fn lerp_keeps_target_in_view_while_zooming_in() {
+        let start = Camera::new();
+        let target = Camera::try_at([-0.743, 0.126], 2000.).unwrap();
+        // Distance of the target to the center of the view, in multiples of the visible extent.
+        let offset = |camera: &Camera| {
+            let [x, y] = camera.position();
+            let [target_x, target_y] = target.position();
+            (x - target_x).hypot(y - target_y) * camera.zoom_level()
+        };
+
+        let mut previous = offset(&start);
+        for step in 1..=10 {
+            let current = offset(&start.lerp(&target, step as f32 / 10.));
+            assert!(current <= previous);
+            previous = current;
+        }
+    }
+
+    #[test]
+    fn zoom_stays_within_limits() {
+        let mut camera = Camera::new();
+        camera.set_max_zoom(1e5);
+
+        for _ in 0..100 {
+            camera.zoom_around(2., [-0.7, 0.2]);
+        }
+        assert_eq!(1e5, camera.zoom_level());
+        camera.reset();
+        for _ in 0..100 {
+            camera.zoom(0.5);
+        }
+        assert!(camera.zoom_level() > 0.);
+        assert!(camera.inv_view()[0][0].is_finite());
+    }
+
+    #[test]
+    fn max_zoom_below_min_zoom_is_raised() {
+        let mut camera = Camera::new();
+        camera.set_max_zoom(0.);
+        camera.set_max_zoom(f64::NAN);
+
+        camera.zoom(2.);
+
+        assert_eq!(MIN_ZOOM, camera.max_zoom());
+        assert_eq!(MIN_ZOOM, camera.zoom_level());
+    }
+
+    #[test]
+    fn reset_returns_to_initial_view() {
+        let mut camera = Camera::new();
+        camera.zoom(1e6);
+        camera.change_pos(3., -2.);
+
+        camera.reset();
+
+        assert_eq!(Camera::new(), camera);
+    }
+
+    #[test]
+    fn setters_place_camera() {
+        let mut camera = Camera::new();
+
+        camera.set_position([-0.75, 0.1]);
+        camera.set_zoom(50.);
+
+        assert_eq!([-0.75, 0.1], camera.position());
+        assert_eq!(50., camera.zoom_level());
+        camera.set_zoom(1e30);
+        assert_eq!(camera.max_zoom(), camera.zoom_level());
+    }
+
+    #[test]
+    fn new_at_clamps_zoom() {
+        let camera = Camera::new_at(0.25, -0.5, 1e-9);
+
+        assert_eq!([0.25, -0.5], camera.position());
+        assert_eq!(MIN_ZOOM, camera.zoom_level());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn camera_survives_json_round_trip() {
+        let mut camera = Camera::new();
+        camera.zoom(42.);
+        camera.change_pos(0.5, 0.25);
+
+        let restored = Camera::from_json(&camera.to_json()).unwrap();
+
+        assert_eq!(camera.inv_view(), restored.inv_view());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn reject_cameras_with_broken_view() {
+        assert!(Camera::from_json(r#"{"pos_x":1e400,"pos_y":0,"zoom":1}"#).is_err());
+        assert!(Camera::from_json(r#"{"pos_x":0,"pos_y":0,"zoom":0}"#).is_err());
+        assert!(Camera::from_json(r#"{"pos_x":0,"pos_y":0,"zoom":-2}"#).is_err());
+        assert!(Camera::from_json(r#"{"pos_x":0,"pos_y":null,"zoom":1}"#).is_err());
+    }
+}
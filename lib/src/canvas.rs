@@ -1,80 +1,242 @@
-use anyhow::Error;
-use std::iter::once;
+use crate::{
+    blit_pipeline::{BlitPipeline, Intermediate},
+    canvas_render_pipeline::{CanvasRenderPipeline, HISTOGRAM_FORMAT},
+    capture::{offscreen_texture, read_f32, read_rgba},
+    compute_pipeline::{ComputePipeline, ComputeTarget, ITERATIONS_HEADER_SIZE},
+    equalization::{EqualizationArgs, HISTOGRAM_SIZE},
+    frame_timer::FrameTimer,
+    grid_pipeline::GridPipeline,
+    hud::Hud,
+    Camera, CanvasBuilder, CanvasError, ColorMapping, ColoringMode, FractalKind, InteriorMode,
+    JuliaInset, Loupe, RenderState, SurfaceTarget, DEFAULT_ESCAPE_RADIUS, MAX_PALETTE_COLORS,
+    PRESET_PALETTES,
+};
+use log::{error, warn};
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    iter::once,
+    sync::Arc,
+    time::Duration,
+};
 use wgpu::{
-    CommandEncoderDescriptor, CompositeAlphaMode, Device, DeviceDescriptor, Features, Limits,
-    PresentMode, Queue, RequestAdapterOptions, Surface, SurfaceConfiguration, SurfaceError,
-    TextureFormat, TextureUsages, TextureViewDescriptor,
+    Adapter, AdapterInfo, Backends, BufferAsyncError, Color, CommandEncoder,
+    CommandEncoderDescriptor, CompositeAlphaMode, Device, DeviceDescriptor, Extent3d, Face,
+    Maintain, PresentMode, Queue, Surface, SurfaceConfiguration, SurfaceError, Texture,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
+    TextureFormatFeatures, TextureUsages, TextureView, TextureViewDescriptor,
 };
-use winit::window::Window;
 
-use crate::{canvas_render_pipeline::CanvasRenderPipeline, Camera};
+/// Width and height of the canvas are divided by this, to get the resolution the fractal is drawn at
+/// while in motion, see [`Canvas::set_dynamic_resolution`].
+const DYNAMIC_RESOLUTION_DIVISOR: u32 = 2;
 
-pub struct Canvas {
+/// Renders fractals to a window or an internal texture. `'w` is the lifetime of the window the
+/// canvas is linked to, see [`SurfaceTarget`]. Headless canvases are not bound by any window.
+pub struct Canvas<'w> {
     /// Width of output surface in pixels.
     width: u32,
     /// Height of output surface in pixels.
     height: u32,
-    /// The surface we are rendering to. It is linked to the inner part of the window passed in the
-    /// constructor.
-    surface: Surface,
+    /// What we are rendering to. Either a surface linked to a window, a texture for headless
+    /// canvases, or nothing if the application provides the views to render into.
+    target: Target,
     /// The format of the texture. It is acquired using the preferred format of the adapter and we
     /// remember it, so we can recreate the surface if it becomes invalid.
     format: TextureFormat,
     /// A device is used to create buffers (for exchanging data with the GPU) among other things.
-    device: Device,
+    /// Shared, in case the canvas is part of a larger application, see [`Self::from_device`].
+    device: Arc<Device>,
     /// Used to send command generated by the render pipline to the GPU and write to buffers.
-    queue: Queue,
+    queue: Arc<Queue>,
+    /// We keep the adapter around, to query which features and formats it supports. `None` if the
+    /// canvas has been created from an existing device.
+    adapter: Option<Adapter>,
     render_pipeline: CanvasRenderPipeline,
+    /// Copies the intermediate render target to the output, in case we render to one.
+    blit_pipeline: BlitPipeline,
+    /// Draws axes and gridlines of the complex plane on top of the fractal.
+    grid_pipeline: GridPipeline,
+    /// Whether axes and gridlines are drawn.
+    grid: bool,
+    /// If set, the fractal is rendered into a texture of this format first and is only converted
+    /// into the surface format as the final step. `None` renders directly to the surface.
+    internal_format: Option<TextureFormat>,
+    /// Factor by which the edges of the texture the fractal is drawn into are longer than those of
+    /// the surface. See [`Self::set_render_scale`].
+    render_scale: f32,
+    /// Intermediate render target of the surface size times `render_scale`. Exists if
+    /// `internal_format` is set, or `render_scale` is not `1`.
+    intermediate: Option<Intermediate>,
+    /// Draw the fractal at reduced resolution while the picture is in motion.
+    dynamic_resolution: bool,
+    /// The picture changes from frame to frame, see [`Self::set_motion`].
+    in_motion: bool,
+    /// Render target of reduced resolution, the fractal is drawn into while in motion. Exists if
+    /// `dynamic_resolution` is enabled.
+    reduced_target: Option<Intermediate>,
+    /// Decides how frames are synchronized with the display, when presented to the surface.
+    present_mode: PresentMode,
+    /// Magnifying glass rendered on top of the fractal, if enabled.
+    loupe: Option<Loupe>,
+    /// Render target for the magnified fractal within the loupe. Exists if `loupe` is set.
+    loupe_target: Option<Intermediate>,
+    /// Preview of the Julia set for the point under the cursor, if enabled.
+    julia_inset: Option<JuliaInset>,
+    /// Render target for the Julia set within the inset. Exists if `julia_inset` is set.
+    julia_inset_target: Option<Intermediate>,
+    /// Text displayed on top of the fractal, if enabled.
+    hud: Option<Hud>,
+    /// Measures how long rendering takes, see [`Self::last_frame_time`].
+    frame_timer: FrameTimer,
+    /// How the picture is fitted into canvases which are not square.
+    fit_mode: FitMode,
+    /// Storage texture the compute pipeline wrote the last frame into, if it is in use. Kept
+    /// between frames, as long as the size of the picture does not change.
+    compute_target: RefCell<Option<ComputeTarget>>,
+    /// Multisampled render targets the fractal is drawn into before being resolved, by size in
+    /// pixels. Empty unless multisampling is enabled, see [`Self::set_sample_count`].
+    multisample_targets: RefCell<HashMap<(u32, u32), TextureView>>,
+    /// Index into [`PRESET_PALETTES`] of the palette the last [`RenderState`] asked for. `None` if
+    /// the palette has been set with [`Self::set_palette`] since, so the next state naming a
+    /// preset switches to it.
+    preset_palette: Cell<Option<usize>>,
+    /// Window `target` presents to, if any. We hold on to it so it stays valid as long as the
+    /// surface. Declared after `target`, so the surface is dropped first.
+    _window: Option<SurfaceTarget<'w>>,
 }
 
-impl Canvas {
+impl<'w> Canvas<'w> {
     /// Construct a new canvas and link it to a window. Height and width are specified in pixels.
+    /// `backends` restricts the graphics APIs the adapter is chosen from. Pass [`Backends::all`]
+    /// to run on any platform. Use [`CanvasBuilder`] to configure further details.
     ///
-    /// # Safety
-    ///
-    /// * `window` must remain valid until canvas is dropped.
-    pub async unsafe fn new(width: u32, height: u32, window: &Window) -> Result<Self, Error> {
-        let instance = wgpu::Instance::default();
-        let surface = unsafe { instance.create_surface(&window)? };
-        let adapter = instance
-            .request_adapter(&RequestAdapterOptions {
-                power_preference: wgpu::PowerPreference::HighPerformance,
-                force_fallback_adapter: false,
-                compatible_surface: Some(&surface),
-            })
+    /// `window` is either borrowed for `'w`, or shared with the canvas by passing an
+    /// [`std::sync::Arc`]. See [`SurfaceTarget`].
+    pub async fn new(
+        width: u32,
+        height: u32,
+        window: impl Into<SurfaceTarget<'w>>,
+        backends: Backends,
+    ) -> Result<Self, CanvasError> {
+        CanvasBuilder::new(width, height)
+            .backends(backends)
+            .build(window)
+            .await
+    }
+
+    /// Construct a new canvas without any window. It renders into an internal texture instead,
+    /// use [`Self::capture_frame`] to read back the picture. Height and width are specified in
+    /// pixels. `backends` restricts the graphics APIs the adapter is chosen from.
+    pub async fn new_headless(
+        width: u32,
+        height: u32,
+        backends: Backends,
+    ) -> Result<Self, CanvasError> {
+        CanvasBuilder::new(width, height)
+            .backends(backends)
+            .build_headless()
             .await
-            .unwrap();
+    }
+
+    /// Construct a canvas drawing with a `device` and `queue` owned by a larger application, e.g. as
+    /// one layer among many. The canvas does not own a surface, use [`Self::render_into`] to draw
+    /// into views of textures with `format` provided by the application. Height and width are
+    /// specified in pixels and should match the size of these textures.
+    ///
+    /// Fails with [`CanvasError::ShaderCompile`] if the device does not support the fractal
+    /// shader.
+    pub async fn from_device(
+        width: u32,
+        height: u32,
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        format: TextureFormat,
+    ) -> Result<Canvas<'static>, CanvasError> {
+        Canvas::with_device(width, height, Target::External, None, device, queue, format).await
+    }
+
+    /// Creates device, queue and pipelines shared by windowed and headless canvases. Renders into
+    /// `surface`, or an internal texture if it is `None`. `window` is the window `surface` has
+    /// been created for.
+    pub(crate) async fn with_target(
+        width: u32,
+        height: u32,
+        surface: Option<Surface>,
+        window: Option<SurfaceTarget<'w>>,
+        adapter: Adapter,
+        format: TextureFormat,
+        device_descriptor: &DeviceDescriptor<'_>,
+    ) -> Result<Self, CanvasError> {
         // Can be used for API call tracing if that feature is enabled.
         let trace_path = None;
-        let limits = if cfg!(target_arch = "wasm32") {
-            Limits::downlevel_webgl2_defaults()
-        } else {
-            Limits::default()
-        };
         let (device, queue) = adapter
-            .request_device(
-                &DeviceDescriptor {
-                    label: None,
-                    features: Features::empty(),
-                    limits,
-                },
-                trace_path,
-            )
+            .request_device(device_descriptor, trace_path)
             .await?;
-        let caps = surface.get_capabilities(&adapter);
-        // The first format in the array is the prefered one.
-        let format = caps.formats[0];
+        let target = match surface {
+            Some(surface) => Target::Surface(surface),
+            None => Target::Texture(offscreen_texture(&device, width, height, format)),
+        };
 
-        let render_pipeline = CanvasRenderPipeline::new(&device, format);
+        let mut canvas = Self::with_device(
+            width,
+            height,
+            target,
+            Some(adapter),
+            Arc::new(device),
+            Arc::new(queue),
+            format,
+        )
+        .await?;
+        canvas._window = window;
+        Ok(canvas)
+    }
+
+    /// Creates the pipelines rendering into `target` using an existing device.
+    async fn with_device(
+        width: u32,
+        height: u32,
+        target: Target,
+        adapter: Option<Adapter>,
+        device: Arc<Device>,
+        queue: Arc<Queue>,
+        format: TextureFormat,
+    ) -> Result<Self, CanvasError> {
+        let render_pipeline = CanvasRenderPipeline::new(&device, format).await?;
+        let blit_pipeline = BlitPipeline::new(&device, format);
+        let grid_pipeline = GridPipeline::new(&device, render_pipeline.inv_view_layout(), format);
+        let frame_timer = FrameTimer::new(&device, &queue);
 
         let canvas = Self {
             width,
             height,
-            surface,
+            target,
             device,
             queue,
+            adapter,
             format,
             render_pipeline,
+            blit_pipeline,
+            grid_pipeline,
+            grid: false,
+            internal_format: None,
+            render_scale: 1.,
+            intermediate: None,
+            dynamic_resolution: false,
+            in_motion: false,
+            reduced_target: None,
+            present_mode: PresentMode::AutoVsync,
+            loupe: None,
+            loupe_target: None,
+            julia_inset: None,
+            julia_inset_target: None,
+            hud: None,
+            frame_timer,
+            fit_mode: FitMode::default(),
+            compute_target: RefCell::new(None),
+            multisample_targets: RefCell::new(HashMap::new()),
+            preset_palette: Cell::new(None),
+            _window: None,
         };
         canvas.configure_surface();
 
@@ -85,49 +247,1357 @@ impl Canvas {
     pub fn resize(&mut self, width: u32, height: u32) {
         // May be resized to an empty surface in case window is minimized. This would crash the
         // application, so we ignore resizing to an empty texture.
-        if width != 0 || height != 0 {
+        if !is_empty(width, height) {
             self.width = width;
             self.height = height;
+            if let Target::Texture(texture) = &mut self.target {
+                *texture = offscreen_texture(&self.device, width, height, self.format);
+            }
             self.configure_surface();
+            self.intermediate = self.create_intermediate(width, height);
+            self.reduced_target = self.create_reduced_target();
+            self.multisample_targets.get_mut().clear();
+            self.update_loupe_target();
+            self.update_julia_inset_target();
+        }
+    }
+
+    /// Renders the fractal into an intermediate texture of `format` (e.g.
+    /// [`TextureFormat::Rgba16Float`]) rather than directly to the surface. This preserves color
+    /// precision through the coloring math. Quantization into the surface format happens only as
+    /// the final step of presenting or exporting a frame. Pass `None` to render directly to the
+    /// surface again.
+    ///
+    /// Fails with [`CanvasError::UnsupportedFormat`] if the adapter can not render to and sample
+    /// from textures of `format`, or does not support the sample count in use for it. Canvases
+    /// created from an existing device only accept formats every adapter supports for this.
+    pub fn set_internal_format(
+        &mut self,
+        format: Option<TextureFormat>,
+    ) -> Result<(), CanvasError> {
+        if let Some(format) = format {
+            let features = self.format_features(format);
+            let required_usages = TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING;
+            if !features.allowed_usages.contains(required_usages)
+                || !features
+                    .flags
+                    .contains(TextureFormatFeatureFlags::FILTERABLE)
+                || !supports_sample_count(&features, self.render_pipeline.sample_count())
+            {
+                return Err(CanvasError::UnsupportedFormat(format));
+            }
+        }
+        self.internal_format = format;
+        self.render_pipeline
+            .set_target_format(&self.device, format.unwrap_or(self.format));
+        self.multisample_targets.get_mut().clear();
+        self.intermediate = self.create_intermediate(self.width, self.height);
+        self.reduced_target = self.create_reduced_target();
+        // Format of the loupe and inset targets must match the render pipeline
+        self.loupe_target = None;
+        self.update_loupe_target();
+        self.julia_inset_target = None;
+        self.update_julia_inset_target();
+        Ok(())
+    }
+
+    /// Draws the fractal into a texture whose edges are `scale` times as long as those of the
+    /// canvas, which is then scaled to the size of the canvas. Values below `1` trade sharpness
+    /// for speed, values above `1` trade speed for smoother edges. Downsampling blends the four
+    /// nearest pixels, so scales above `2` cost more than they improve the picture. The default
+    /// `1` draws the fractal at the resolution of the canvas. The scale is clamped, so the texture
+    /// has at least one pixel and does not exceed the maximum texture size of the device.
+    ///
+    /// Both edges are scaled by the same factor, so the picture keeps the aspect ratio of the
+    /// canvas and is not distorted. The size of the texture is rounded to whole pixels though, so
+    /// its aspect ratio may deviate from the one of the canvas by the fraction of a pixel. The
+    /// fractal is corrected for the aspect ratio of the texture. In fit mode
+    /// [`FitMode::Contain`] the square picture is scaled along with the bars next to it.
+    ///
+    /// Applies to captured frames, thumbnails and tiles as well. The loupe, the Julia inset and
+    /// overlays are always drawn at the resolution of the canvas.
+    pub fn set_render_scale(&mut self, scale: f32) {
+        self.render_scale = if scale.is_finite() && scale > 0. {
+            scale
+        } else {
+            1.
+        };
+        self.intermediate = self.create_intermediate(self.width, self.height);
+        self.reduced_target = self.create_reduced_target();
+        self.multisample_targets.get_mut().clear();
+    }
+
+    /// Keeps interaction smooth on slow devices, by drawing the fractal at a fraction of the
+    /// resolution of the canvas while it is in motion and scaling it up to the full size. Once the
+    /// picture comes to rest, it is drawn at full resolution again. Overlays, the loupe and captured
+    /// frames always have full resolution. Disabled by default.
+    ///
+    /// The canvas can not tell on its own whether the picture moves, see [`Self::set_motion`].
+    pub fn set_dynamic_resolution(&mut self, enabled: bool) {
+        self.dynamic_resolution = enabled;
+        self.reduced_target = self.create_reduced_target();
+    }
+
+    /// Tells the canvas whether the picture changes from one frame to the next, e.g. because the
+    /// user is panning, see [`crate::Controls::picture_changes`]. Only matters if dynamic
+    /// resolution is enabled. Render one more frame once the motion stopped, so the final picture
+    /// is drawn at full resolution.
+    pub fn set_motion(&mut self, in_motion: bool) {
+        self.in_motion = in_motion;
+    }
+
+    /// Anti-aliases the fractal with multisampling (MSAA), using `count` samples per pixel. The
+    /// fractal is drawn into a multisampled texture, which is resolved into the output at the end
+    /// of the render pass. The default count `1` disables multisampling.
+    ///
+    /// The fractal is computed once per pixel by the fragment shader, regardless of the number of
+    /// samples. So multisampling only smooths the edges of the drawn geometry, i.e. the borders of
+    /// the picture if it does not fill the entire canvas, not the structure within the fractal. Use
+    /// [`Self::set_supersampling`] for that. Overlays like the grid are drawn onto the resolved
+    /// picture and are not multisampled. The compute pipeline ignores the sample count.
+    ///
+    /// Fails with [`CanvasError::UnsupportedSampleCount`] if the adapter does not support `count`
+    /// for the format the fractal is rendered into. Canvases created from an existing device only
+    /// accept counts every adapter supports. In that case the previous count stays in use.
+    pub fn set_sample_count(&mut self, count: u32) -> Result<(), CanvasError> {
+        let format = self.internal_format.unwrap_or(self.format);
+        if !supports_sample_count(&self.format_features(format), count) {
+            return Err(CanvasError::UnsupportedSampleCount(count));
+        }
+        self.render_pipeline
+            .set_sample_count(&self.device, format, count);
+        self.multisample_targets.get_mut().clear();
+        Ok(())
+    }
+
+    /// Enabled by default, the back faces of triangles are not drawn. Disable culling if nothing is
+    /// drawn on a new backend, to tell whether the winding order of the vertices is to blame.
+    pub fn set_back_face_culling(&mut self, enabled: bool) {
+        let format = self.internal_format.unwrap_or(self.format);
+        let cull_mode = enabled.then_some(Face::Back);
+        self.render_pipeline
+            .set_cull_mode(&self.device, format, cull_mode);
+    }
+
+    /// Replaces the built-in fractal shader with one compiled from the WGSL `source`, e.g. to
+    /// experiment with the shader without rebuilding the application. The shader must declare the
+    /// same entry points and bind groups as the built-in one.
+    ///
+    /// Fails with [`CanvasError::ShaderCompile`] if the shader can not be compiled or does not fit
+    /// the canvas. In that case the canvas keeps rendering with the previous shader.
+    pub async fn reload_shader(&mut self, source: &str) -> Result<(), CanvasError> {
+        let format = self.internal_format.unwrap_or(self.format);
+        self.render_pipeline
+            .reload_shader(&self.device, source, format)
+            .await
+    }
+
+    /// Chooses whether the fractal is drawn by a fragment shader (the default) or a compute shader,
+    /// which writes into a storage texture that is copied to the output afterwards. Both produce
+    /// the same picture, the compute shader may be faster on some devices. The loupe is always
+    /// drawn by the fragment shader.
+    ///
+    /// Fails with [`CanvasError::ComputePipeline`] if the device does not support the compute
+    /// shader. In that case the canvas keeps drawing with the fragment shader.
+    pub async fn set_pipeline_mode(&mut self, mode: PipelineMode) -> Result<(), CanvasError> {
+        self.render_pipeline
+            .set_pipeline_mode(&self.device, mode)
+            .await?;
+        if mode == PipelineMode::Fragment {
+            *self.compute_target.get_mut() = None;
+        }
+        Ok(())
+    }
+
+    /// Replaces the colors used to draw the fractal. The first color is used for points which never
+    /// escape, the others are spaced evenly over the escape values and blended linearly. See
+    /// [`crate::DEFAULT_PALETTE`] for the palette in use unless specified otherwise. Rendering a
+    /// [`RenderState`] which names one of the preset palettes switches to that one instead.
+    ///
+    /// Fails with [`CanvasError::PaletteSize`] if `colors` is empty or holds more than
+    /// [`crate::MAX_PALETTE_COLORS`] colors.
+    pub fn set_palette(&mut self, colors: &[[f32; 3]]) -> Result<(), CanvasError> {
+        if colors.is_empty() || colors.len() > MAX_PALETTE_COLORS {
+            return Err(CanvasError::PaletteSize(colors.len()));
+        }
+        self.render_pipeline.set_palette(&self.queue, colors);
+        self.preset_palette.set(None);
+        Ok(())
+    }
+
+    /// Switches to the preset palette `state` asks for, unless it is already in use.
+    fn apply_palette(&self, state: &RenderState) {
+        let Some(index) = state.palette.map(|index| index % PRESET_PALETTES.len()) else {
+            return;
+        };
+        if self.preset_palette.get() != Some(index) {
+            self.render_pipeline
+                .set_palette(&self.queue, PRESET_PALETTES[index]);
+            self.preset_palette.set(Some(index));
         }
     }
 
-    pub fn render(&self, camera: &Camera, iterations: i32) -> Result<(), SurfaceError> {
-        let output = match self.surface.get_current_texture() {
-            Ok(output) => output,
-            // Surface Lost => Reconfigure surface
-            Err(SurfaceError::Lost) => {
-                self.configure_surface();
-                self.surface.get_current_texture()?
+    /// Inverts every color of the picture, e.g. to turn a dark background light. Applies on top of
+    /// the palette and any coloring mode. Disabled by default.
+    pub fn set_color_inversion(&mut self, enabled: bool) {
+        self.render_pipeline
+            .set_color_inversion(&self.queue, enabled);
+    }
+
+    /// Smooths the edges of the fractal by computing each pixel from `factor * factor` samples
+    /// arranged in a regular grid, and averaging their colors. The default factor `1` computes a
+    /// single sample at the center of each pixel. Frame time grows with the number of samples. A
+    /// factor of `0` is treated like `1`.
+    pub fn set_supersampling(&mut self, factor: u32) {
+        self.render_pipeline.set_supersampling(factor.max(1));
+    }
+
+    /// Exponent `p` of the iteration `z = z^p + c`. `2` (the default) yields the classic Mandelbrot
+    /// and Julia sets, larger powers add more lobes (Multibrot). Non-integer powers are supported.
+    /// Powers other than `2` are computed in single precision, so they do not support zooming as
+    /// deep. `p` must be finite and larger than `1`, since smooth coloring divides by `log2(p)`.
+    /// Other values are ignored.
+    pub fn set_power(&mut self, p: f32) {
+        if !(p.is_finite() && p > 1.) {
+            warn!("Ignoring power {p}. Powers must be finite and larger than 1.");
+            return;
+        }
+        self.render_pipeline.set_power(p);
+    }
+
+    /// Switches between coloring pixels by escape time using the palette (the default),
+    /// highlighting the boundary of the set using distance estimation, and orbit traps. See
+    /// [`ColoringMode`].
+    pub fn set_coloring_mode(&mut self, coloring: ColoringMode) {
+        self.render_pipeline.set_coloring_mode(coloring);
+    }
+
+    /// Maps escape values to the palette either linearly (the default) or logarithmically, which
+    /// reveals more structure far from the set. Applies to coloring by escape time. See
+    /// [`ColorMapping`].
+    pub fn set_color_mapping(&mut self, mapping: ColorMapping) {
+        self.render_pipeline.set_color_mapping(mapping);
+    }
+
+    /// Colors the points within the set, which never escape, to reveal structure inside of it.
+    /// [`InteriorMode::Black`] by default. Applies to escape time coloring and distance
+    /// estimation, orbit traps always color the interior.
+    pub fn set_interior_mode(&mut self, interior: InteriorMode) {
+        self.render_pipeline.set_interior_mode(interior);
+    }
+
+    /// Enabled by default, colors blend continuously between iteration counts when coloring by
+    /// escape time. Disabling it yields the classic look with distinct bands of color, one per
+    /// iteration.
+    pub fn set_smooth_coloring(&mut self, enabled: bool) {
+        self.render_pipeline.set_smooth_coloring(enabled);
+    }
+
+    /// Decides how the picture is fitted into a canvas which is not square. [`FitMode::Stretch`]
+    /// by default.
+    pub fn set_fit_mode(&mut self, fit_mode: FitMode) {
+        self.fit_mode = fit_mode;
+    }
+
+    /// Spreads the colors of the palette evenly over the picture when coloring by escape time.
+    /// Otherwise most of the palette is spent on rare escape values, while the majority of pixels
+    /// share few colors. Each frame then gathers a histogram of escape values in an additional,
+    /// downscaled render pass, which is read back to compute their cumulative distribution. This
+    /// costs a round trip to the GPU per frame. Disabled by default.
+    pub fn set_histogram_equalization(&mut self, enabled: bool) {
+        self.render_pipeline.set_histogram_equalization(enabled);
+    }
+
+    /// Cycles the colors of escaping points through the palette over time. `speed` is the number of
+    /// cycles through the entire palette per second, negative values cycle backwards. `0` (the
+    /// default) keeps the picture static, with the palette in its original place. While cycling,
+    /// the canvas must be rendered continuously to animate.
+    pub fn set_color_cycle_speed(&mut self, speed: f32) {
+        self.render_pipeline.set_color_cycle_speed(speed);
+    }
+
+    /// Gamma of the display. The shader raises colors to the power of `1 / gamma`, so the
+    /// gradients of the palette look even on screen. Defaults to `2.2` for surfaces which store
+    /// colors linearly and to `1.0` for sRGB surfaces, which encode colors in hardware.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.render_pipeline.set_gamma(gamma);
+    }
+
+    /// Sequences are considered escaping once their magnitude exceeds `radius`. Larger radii, e.g.
+    /// `128`, cost a few more iterations, but reduce banding of the smooth coloring by escape time.
+    /// Radii below the default of `2` are raised to it, since sequences within it may still
+    /// converge.
+    pub fn set_escape_radius(&mut self, radius: f32) {
+        self.render_pipeline
+            .set_escape_radius(radius.max(DEFAULT_ESCAPE_RADIUS));
+    }
+
+    /// Color the canvas is cleared with before the fractal is drawn. It may become visible briefly
+    /// while the canvas is resized. Black by default.
+    pub fn set_background(&mut self, color: Color) {
+        self.render_pipeline.set_background(color);
+    }
+
+    /// Decides how frames are synchronized with the display. E.g. [`PresentMode::Immediate`]
+    /// disables VSync, to measure the uncapped frame rate. Falls back to
+    /// [`PresentMode::AutoVsync`] if the surface does not support `mode`. Returns the mode in use
+    /// afterwards. Canvases without a surface accept any mode, since they do not present frames.
+    pub fn set_present_mode(&mut self, mode: PresentMode) -> PresentMode {
+        self.present_mode = match (&self.target, &self.adapter) {
+            (Target::Surface(surface), Some(adapter))
+                if !surface
+                    .get_capabilities(adapter)
+                    .present_modes
+                    .contains(&mode) =>
+            {
+                warn!("Present mode {mode:?} is not supported. Falling back to AutoVsync.");
+                PresentMode::AutoVsync
+            }
+            _ => mode,
+        };
+        self.configure_surface();
+        self.present_mode
+    }
+
+    /// Draws the real and imaginary axes, as well as gridlines at integer coordinates, on top of the
+    /// fractal. Like the HUD, they are not part of captured frames. Disabled by default.
+    pub fn set_grid(&mut self, enabled: bool) {
+        self.grid = enabled;
+    }
+
+    /// Show a magnifying glass on top of the fractal. `None` hides it.
+    pub fn set_loupe(&mut self, loupe: Option<Loupe>) {
+        self.loupe = loupe;
+        self.update_loupe_target();
+    }
+
+    /// Show the Julia set belonging to the point under the cursor in a small inset, while the
+    /// Mandelbrot set is drawn. Its constant `c` follows the cursor. `None` hides it.
+    pub fn set_julia_inset(&mut self, inset: Option<JuliaInset>) {
+        self.julia_inset = inset;
+        self.update_julia_inset_target();
+    }
+
+    /// Displays `lines` of text in the top left corner of the canvas, e.g. zoom level and
+    /// coordinates. The overlay is drawn after the fractal by [`Self::render`] and
+    /// [`Self::render_into`], and is not part of captured frames. Text is only rasterized again if
+    /// `lines` change, so passing the same lines each frame is cheap. Pass `enabled = false` to
+    /// hide the overlay, which then costs nothing. If the text does not fit into the canvas, it is
+    /// not displayed.
+    pub fn set_hud(&mut self, enabled: bool, lines: &[String]) {
+        if !enabled || lines.is_empty() {
+            self.hud = None;
+            return;
+        }
+        match &mut self.hud {
+            Some(hud) => hud.update(&self.device, &self.queue, &self.blit_pipeline, lines),
+            None => {
+                self.hud = Some(Hud::new(
+                    &self.device,
+                    &self.queue,
+                    &self.blit_pipeline,
+                    lines,
+                ))
+            }
+        }
+    }
+
+    /// Draws the fractal described by `state` and presents it, switching to its palette first if it
+    /// names one. Headless canvases draw into their internal texture instead. Canvases created with [`Self::from_device`] have no target of
+    /// their own and draw nothing, use [`Self::render_into`] for them.
+    pub fn render(&self, state: &RenderState) -> Result<(), SurfaceError> {
+        self.apply_palette(state);
+        self.render_frame(&state.camera, state.iterations, &state.kind)
+    }
+
+    /// Like [`Self::render`], with the parts of the render state passed individually.
+    #[deprecated(note = "Pass a `RenderState` to `Canvas::render` instead")]
+    pub fn render_with(
+        &self,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> Result<(), SurfaceError> {
+        self.render_frame(camera, iterations, kind)
+    }
+
+    fn render_frame(
+        &self,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> Result<(), SurfaceError> {
+        self.present_with(|view| self.render_into(view, camera, iterations, kind))
+    }
+
+    /// Draws two fractals next to each other, e.g. the Mandelbrot set on the left and a Julia set
+    /// on the right, for comparison. Each fills its half of the canvas, with the aspect ratio of
+    /// that half, independent of the fit mode. Loupe, inset, grid and HUD are not drawn, and
+    /// neither is dynamic resolution applied. Presents the picture like [`Self::render`] does.
+    pub fn render_split(
+        &self,
+        left: &RenderState,
+        right: &RenderState,
+    ) -> Result<(), SurfaceError> {
+        self.present_with(|view| self.render_split_into(view, left, right))
+    }
+
+    /// Acquires the view to draw into, calls `draw` with it and presents the result.
+    fn present_with(&self, draw: impl FnOnce(&TextureView)) -> Result<(), SurfaceError> {
+        let (output, view) = match &self.target {
+            Target::Surface(surface) => {
+                let output = match surface.get_current_texture() {
+                    Ok(output) => output,
+                    // Surface lost or no longer matching the window => Reconfigure surface
+                    Err(SurfaceError::Lost | SurfaceError::Outdated) => {
+                        self.configure_surface();
+                        surface.get_current_texture()?
+                    }
+                    Err(other) => return Err(other),
+                };
+                let view = output
+                    .texture
+                    .create_view(&TextureViewDescriptor::default());
+                (Some(output), view)
+            }
+            Target::Texture(texture) => {
+                (None, texture.create_view(&TextureViewDescriptor::default()))
             }
-            Err(other) => return Err(other),
+            Target::External => return Ok(()),
         };
-        let view = output
-            .texture
-            .create_view(&TextureViewDescriptor::default());
+        draw(&view);
+        if let Some(output) = output {
+            output.present();
+        }
+        Ok(())
+    }
+
+    /// Draws the fractal as seen through `camera` into `view`, including loupe and HUD. `view`
+    /// must belong to a texture of the size of the canvas, with the format the canvas has been
+    /// created for and [`TextureUsages::RENDER_ATTACHMENT`]. Allows drawing the fractal as one
+    /// layer among many, into textures owned by the application. The commands are submitted to
+    /// the queue of the canvas, before this method returns.
+    pub fn render_into(
+        &self,
+        view: &TextureView,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) {
+        let placement = self.fit_mode.placement(self.width, self.height);
+        self.update_equalization(camera, placement.viewport_size(), iterations, kind);
+        let timer_begin = self.frame_timer.begin(&self.device);
+        let measured = timer_begin.is_some();
+        let encoder = match self.reduced_target.as_ref().filter(|_| self.in_motion) {
+            Some(reduced) => {
+                // Fitted into the reduced target just like into the canvas, so scaling it up
+                // yields the same picture.
+                let reduced_placement = self.fit_mode.placement(reduced.width, reduced.height);
+                let mut encoder = self.encode_draw(
+                    &reduced.view,
+                    None,
+                    reduced_placement,
+                    camera,
+                    iterations,
+                    kind,
+                );
+                self.blit_pipeline.draw_to(reduced, view, &mut encoder);
+                encoder
+            }
+            None => self.encode_draw(
+                view,
+                self.intermediate.as_ref(),
+                placement,
+                camera,
+                iterations,
+                kind,
+            ),
+        };
+        self.queue
+            .submit(timer_begin.into_iter().chain(once(encoder.finish())));
+        if self.grid {
+            // Must be submitted before the loupe replaces the inverse view matrix.
+            let encoder = self.encode_grid(view, placement, camera);
+            self.queue.submit(once(encoder.finish()));
+        }
+        if let (Some(loupe), Some(loupe_target)) = (&self.loupe, &self.loupe_target) {
+            // The loupe requires different shader arguments, so we must submit it separately.
+            let encoder = self.encode_loupe(view, loupe, loupe_target, camera, iterations, kind);
+            self.queue.submit(once(encoder.finish()));
+        }
+        if let (Some(inset), Some(inset_target), FractalKind::Mandelbrot) =
+            (&self.julia_inset, &self.julia_inset_target, kind)
+        {
+            // Like the loupe, the inset requires shader arguments of its own.
+            let encoder = self.encode_julia_inset(view, inset, inset_target, camera, iterations);
+            self.queue.submit(once(encoder.finish()));
+        }
+        if let Some(hud) = &self.hud {
+            if let Some(viewport) = hud.viewport(self.width, self.height) {
+                let mut encoder = self
+                    .device
+                    .create_command_encoder(&CommandEncoderDescriptor {
+                        label: Some("HUD Encoder"),
+                    });
+                self.blit_pipeline
+                    .draw_overlay_to(hud.target(), view, viewport, &mut encoder);
+                self.queue.submit(once(encoder.finish()));
+            }
+        }
+        if let Some(timer_end) = self.frame_timer.end(&self.device, measured) {
+            self.queue.submit(once(timer_end));
+            self.frame_timer.read_back();
+        }
+    }
+
+    /// Draws `left` and `right` into the respective halves of `view`, see [`Self::render_split`].
+    /// `view` must meet the requirements of [`Self::render_into`].
+    pub fn render_split_into(&self, view: &TextureView, left: &RenderState, right: &RenderState) {
+        if is_empty(self.width, self.height) {
+            return;
+        }
+        // Honors render scale and internal format, just like the undivided picture.
+        let (target, target_size) = match &self.intermediate {
+            Some(intermediate) => (
+                &intermediate.view,
+                (intermediate.width, intermediate.height),
+            ),
+            None => (view, (self.width, self.height)),
+        };
+        let left_width = self.width / 2;
+        let halves = [
+            (left, 0, left_width),
+            (right, left_width, self.width - left_width),
+        ];
+        for (index, (state, x, width)) in halves.into_iter().enumerate() {
+            let placement = Placement {
+                target_size: (self.width, self.height),
+                viewport: Some([x as f32, 0., width as f32, self.height as f32]),
+            }
+            .scaled_to(target_size.0, target_size.1);
+            let [x, y, width, height] = placement
+                .viewport
+                .unwrap()
+                .map(|value| value.round() as u32);
+            // Rounding must not push the scissor rectangle beyond the edge of the target.
+            let width = width.min(target_size.0 - x);
+            let height = height.min(target_size.1 - y);
+            self.update_equalization(
+                &state.camera,
+                (width, height),
+                state.iterations,
+                &state.kind,
+            );
+            // Each half has its own camera, so we must submit the halves separately, like the
+            // loupe.
+            self.render_pipeline.update_buffers(
+                &self.queue,
+                &state.camera,
+                (width, height),
+                state.iterations,
+                &state.kind,
+            );
+            let mut encoder = self
+                .device
+                .create_command_encoder(&CommandEncoderDescriptor {
+                    label: Some("Split Encoder"),
+                });
+            if !is_empty(width, height) {
+                self.draw_fractal(
+                    target,
+                    target_size,
+                    placement.viewport,
+                    Some([x, y, width, height]),
+                    &mut encoder,
+                );
+            }
+            if let (Some(intermediate), 1) = (&self.intermediate, index) {
+                self.blit_pipeline.draw_to(intermediate, view, &mut encoder);
+            }
+            self.queue.submit(once(encoder.finish()));
+        }
+    }
+
+    /// Wall-clock time between the submissions of the last two frames rendered with
+    /// [`Self::render`]. `None` until two frames have been rendered.
+    pub fn last_frame_time(&self) -> Option<Duration> {
+        self.frame_timer.frame_time()
+    }
+
+    /// Time the GPU spent on the most recent frame whose measurement has been read back. Always
+    /// `None` if the device does not support timestamp queries. In that case fall back to
+    /// [`Self::last_frame_time`].
+    pub fn last_gpu_time(&self) -> Option<Duration> {
+        self.frame_timer.gpu_time()
+    }
+
+    /// Blocks until the GPU finished all work submitted so far, e.g. to measure how long rendering
+    /// a number of frames takes, rather than how long submitting them takes.
+    pub fn wait_until_idle(&self) {
+        self.device.poll(Maintain::Wait);
+    }
+
+    /// Name, backend and driver of the graphics adapter the canvas renders with. Useful for
+    /// diagnosing performance issues. `None` if the canvas has been created from an existing
+    /// device, since the adapter is not known to it.
+    pub fn adapter_info(&self) -> Option<AdapterInfo> {
+        self.adapter.as_ref().map(Adapter::get_info)
+    }
+
+    /// Width and height of the canvas in pixels.
+    pub fn size(&self) -> (u32, u32) {
+        (self.width, self.height)
+    }
+
+    /// Renders the fractal as seen through `camera` into an offscreen texture of the same size as
+    /// the canvas. Returns tightly packed RGBA8 bytes with `width * height` pixels, row by row
+    /// starting at the top left. Works independently of whether a frame is currently presented.
+    ///
+    /// Blocks until the GPU finished rendering the frame.
+    pub fn capture_frame(
+        &self,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> Result<Vec<u8>, BufferAsyncError> {
+        let placement = self.fit_mode.placement(self.width, self.height);
+        self.update_equalization(camera, placement.viewport_size(), iterations, kind);
+        self.render_offscreen(placement, camera, iterations, kind)
+    }
+
+    /// Number of iterations after which the sequence of each pixel escapes, for the fractal as seen
+    /// through `camera`. Unlike the colors returned by [`Self::capture_frame`], these are the raw
+    /// values, e.g. to analyse their distribution. Their fractional part changes continuously
+    /// between neighbouring pixels, like the colors of the smooth coloring do. Pixels which never
+    /// escape are reported as `-1`. For the Newton fractal, the values count the steps until a
+    /// pixel converges to a root instead.
+    ///
+    /// Returns `width * height` values for the size of the canvas, row by row starting at the top
+    /// left. The picture fills the entire canvas regardless of the fit mode, and each pixel is
+    /// sampled once at its center regardless of supersampling.
+    ///
+    /// Fails with [`CanvasError::NoComputePipeline`] unless the fractal is drawn with
+    /// [`PipelineMode::Compute`], since the iterations are computed by a compute shader, and with
+    /// [`CanvasError::BufferTooLarge`] if the canvas has more pixels than the device can hold in a
+    /// storage buffer. Blocks until the GPU finished computing them.
+    pub fn read_iterations(
+        &self,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> Result<Vec<f32>, CanvasError> {
+        let (width, height) = (self.width, self.height);
+        let compute_pipeline = self
+            .render_pipeline
+            .compute_pipeline()
+            .ok_or(CanvasError::NoComputePipeline)?;
+        let target = self
+            .render_pipeline
+            .iterations_target(&self.device, width, height)?
+            .ok_or(CanvasError::NoComputePipeline)?;
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Iterations Encoder"),
+            });
+        self.render_pipeline
+            .update_buffers(&self.queue, camera, (width, height), iterations, kind);
+        compute_pipeline.dispatch_iterations(&target, &mut encoder);
+        let values = read_f32(
+            &self.device,
+            &self.queue,
+            encoder,
+            &target.buffer,
+            ITERATIONS_HEADER_SIZE,
+            width as usize * height as usize,
+        )?;
+        Ok(values)
+    }
+
+    /// Renders a small square preview of `state`, independent of the surface, e.g. to show next to
+    /// a bookmark. Returns tightly packed RGBA8 bytes with `size * size` pixels, row by row
+    /// starting at the top left. Like [`Self::capture_frame`] this returns raw bytes, rather than an
+    /// image type, so the library does not depend on an image crate. E.g.
+    /// `image::RgbaImage::from_raw(size, size, pixels)` turns them into an image.
+    ///
+    /// Blocks until the GPU finished rendering the thumbnail.
+    pub fn render_thumbnail(
+        &self,
+        state: &RenderState,
+        size: u32,
+    ) -> Result<Vec<u8>, BufferAsyncError> {
+        self.apply_palette(state);
+        let RenderState {
+            camera,
+            iterations,
+            kind,
+            palette: _,
+        } = state;
+        self.update_equalization(camera, (size, size), *iterations, kind);
+        self.render_offscreen(Placement::full(size, size), camera, *iterations, kind)
+    }
+
+    /// Renders the fractal as seen through `camera` into a picture of `width` times `height`
+    /// pixels, which may exceed the maximum texture size of the device. The picture is rendered in
+    /// square tiles of at most `tile` pixels edge length, which are read back one after another
+    /// and stitched together. Returns tightly packed RGBA8 bytes, row by row starting at the top
+    /// left.
+    ///
+    /// Blocks until the GPU finished rendering all tiles.
+    pub fn render_tiled(
+        &self,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+        width: u32,
+        height: u32,
+        tile: u32,
+    ) -> Result<Vec<u8>, BufferAsyncError> {
+        self.render_tiled_with_progress(camera, iterations, kind, width, height, tile, |_, _| ())
+    }
+
+    /// Like [`Self::render_tiled`], but calls `progress` each time a tile has been read back, with
+    /// the number of tiles finished so far and the total number of tiles. Large pictures take a
+    /// while, so this allows reporting progress to the user.
+    #[allow(clippy::too_many_arguments)]
+    pub fn render_tiled_with_progress(
+        &self,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+        width: u32,
+        height: u32,
+        tile: u32,
+        mut progress: impl FnMut(usize, usize),
+    ) -> Result<Vec<u8>, BufferAsyncError> {
+        const BYTES_PER_PIXEL: usize = 4;
+        let tile = tile.clamp(1, self.device.limits().max_texture_dimension_2d);
+        let total = width.div_ceil(tile) as usize * height.div_ceil(tile) as usize;
+        let mut finished = 0;
+        let mut pixels = vec![0; width as usize * height as usize * BYTES_PER_PIXEL];
+        // Tiles share the distribution of the entire picture, so colors match across seams.
+        self.update_equalization(camera, (width, height), iterations, kind);
+        for y in (0..height).step_by(tile as usize) {
+            for x in (0..width).step_by(tile as usize) {
+                // Tiles at the right and bottom border are cut short.
+                let tile_width = tile.min(width - x);
+                let tile_height = tile.min(height - y);
+                let tile_camera = camera.tile(width, height, x, y, tile_width, tile_height);
+                // Tiles are part of one large picture, so they must not be fitted individually.
+                let placement = Placement::full(tile_width, tile_height);
+                let tile_pixels =
+                    self.render_offscreen(placement, &tile_camera, iterations, kind)?;
+                let tile_row_len = tile_width as usize * BYTES_PER_PIXEL;
+                for (row, tile_row) in tile_pixels.chunks_exact(tile_row_len).enumerate() {
+                    let start =
+                        ((y as usize + row) * width as usize + x as usize) * BYTES_PER_PIXEL;
+                    pixels[start..start + tile_row_len].copy_from_slice(tile_row);
+                }
+                finished += 1;
+                progress(finished, total);
+            }
+        }
+        Ok(pixels)
+    }
+
+    /// Renders into an offscreen texture of the size of `placement` and reads back its contents as
+    /// RGBA8.
+    fn render_offscreen(
+        &self,
+        placement: Placement,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> Result<Vec<u8>, BufferAsyncError> {
+        let (width, height) = placement.target_size;
+        let texture = offscreen_texture(&self.device, width, height, self.format);
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let intermediate = self.create_intermediate(width, height);
+        let encoder = self.encode_draw(
+            &view,
+            intermediate.as_ref(),
+            placement,
+            camera,
+            iterations,
+            kind,
+        );
+        read_rgba(
+            &self.device,
+            &self.queue,
+            encoder,
+            &texture,
+            width,
+            height,
+            self.format,
+        )
+    }
+
+    /// Gathers the distribution of escape values in the view of `camera` on a picture with the
+    /// aspect ratio of `size`, if histogram equalization is enabled. If reading it back fails, the
+    /// previous distribution stays in use.
+    fn update_equalization(
+        &self,
+        camera: &Camera,
+        (width, height): (u32, u32),
+        iterations: i32,
+        kind: &FractalKind,
+    ) {
+        if !self.render_pipeline.equalizes(kind) || is_empty(width, height) {
+            return;
+        }
+        // Downscale the longer side to `HISTOGRAM_SIZE`, keeping the aspect ratio.
+        let scale = HISTOGRAM_SIZE as f32 / width.max(height) as f32;
+        let histogram_width = ((width as f32 * scale) as u32).max(1);
+        let histogram_height = ((height as f32 * scale) as u32).max(1);
+        let texture = offscreen_texture(
+            &self.device,
+            histogram_width,
+            histogram_height,
+            HISTOGRAM_FORMAT,
+        );
+        let view = texture.create_view(&TextureViewDescriptor::default());
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Histogram Encoder"),
+            });
+        self.render_pipeline.update_histogram_buffers(
+            &self.queue,
+            camera,
+            (histogram_width, histogram_height),
+            iterations,
+            kind,
+        );
+        self.render_pipeline.draw_histogram_to(&view, &mut encoder);
+        match read_rgba(
+            &self.device,
+            &self.queue,
+            encoder,
+            &texture,
+            histogram_width,
+            histogram_height,
+            HISTOGRAM_FORMAT,
+        ) {
+            Ok(escape_values) => self.render_pipeline.set_distribution(
+                &self.queue,
+                &EqualizationArgs::from_escape_values(&escape_values),
+            ),
+            Err(e) => error!("Could not read back histogram: {e}"),
+        }
+    }
+
+    /// Updates the shader arguments and records drawing the fractal into `view`. If
+    /// `intermediate` is specified, the fractal is drawn into it first and then copied to `view`.
+    /// `placement` describes where within `view` the fractal is drawn.
+    fn encode_draw(
+        &self,
+        view: &TextureView,
+        intermediate: Option<&Intermediate>,
+        placement: Placement,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> CommandEncoder {
         let mut encoder = self
             .device
             .create_command_encoder(&CommandEncoderDescriptor {
                 label: Some("Render Encoder"),
             });
+        // The intermediate may differ in size from `view`, see `Self::set_render_scale`.
+        let drawn = intermediate.map_or(placement, |intermediate| {
+            placement.scaled_to(intermediate.width, intermediate.height)
+        });
+        self.render_pipeline.update_buffers(
+            &self.queue,
+            camera,
+            drawn.viewport_size(),
+            iterations,
+            kind,
+        );
+        if let Some(compute_pipeline) = self.render_pipeline.compute_pipeline() {
+            // The storage texture keeps the full precision of the colors, so we do not need
+            // another intermediate. It is scaled to the viewport, just like the intermediate.
+            let resolution = drawn.viewport_size();
+            self.encode_compute(compute_pipeline, view, placement, resolution, &mut encoder);
+        } else if let Some(intermediate) = intermediate {
+            self.draw_fractal(
+                &intermediate.view,
+                drawn.target_size,
+                drawn.viewport,
+                None,
+                &mut encoder,
+            );
+            self.blit_pipeline.draw_to(intermediate, view, &mut encoder);
+        } else {
+            self.draw_fractal(
+                view,
+                placement.target_size,
+                placement.viewport,
+                None,
+                &mut encoder,
+            );
+        }
+        encoder
+    }
+
+    /// Records drawing the fractal into `view` of `width` times `height` pixels. Draws into a
+    /// multisampled target of the same size first and resolves it into `view`, if multisampling is
+    /// enabled. See [`CanvasRenderPipeline::draw_to`] for `viewport` and `scissor`.
+    fn draw_fractal(
+        &self,
+        view: &TextureView,
+        (width, height): (u32, u32),
+        viewport: Option<[f32; 4]>,
+        scissor: Option<[u32; 4]>,
+        encoder: &mut CommandEncoder,
+    ) {
+        let sample_count = self.render_pipeline.sample_count();
+        if sample_count == 1 {
+            self.render_pipeline
+                .draw_to(view, None, viewport, scissor, encoder);
+            return;
+        }
+        let mut targets = self.multisample_targets.borrow_mut();
+        let multisampled = targets.entry((width, height)).or_insert_with(|| {
+            self.device
+                .create_texture(&TextureDescriptor {
+                    label: Some("Multisampled Target"),
+                    size: Extent3d {
+                        width,
+                        height,
+                        depth_or_array_layers: 1,
+                    },
+                    mip_level_count: 1,
+                    sample_count,
+                    dimension: TextureDimension::D2,
+                    format: self.internal_format.unwrap_or(self.format),
+                    usage: TextureUsages::RENDER_ATTACHMENT,
+                    view_formats: &[],
+                })
+                .create_view(&TextureViewDescriptor::default())
+        });
         self.render_pipeline
-            .update_buffers(&self.queue, camera.inv_view(), iterations);
-        self.render_pipeline.draw_to(&view, &mut encoder);
-        self.queue.submit(once(encoder.finish()));
-        output.present();
-        Ok(())
+            .draw_to(view, Some(multisampled), viewport, scissor, encoder);
+    }
+
+    /// Records computing the fractal into a storage texture of `resolution` and copying it to
+    /// `view`, at the place described by `placement`.
+    fn encode_compute(
+        &self,
+        compute_pipeline: &ComputePipeline,
+        view: &TextureView,
+        placement: Placement,
+        resolution: (u32, u32),
+        encoder: &mut CommandEncoder,
+    ) {
+        let (width, height) = resolution;
+        let mut compute_target = self.compute_target.borrow_mut();
+        let up_to_date = compute_target.as_ref().is_some_and(|target| {
+            target.intermediate.width == width && target.intermediate.height == height
+        });
+        if !up_to_date {
+            *compute_target = self.render_pipeline.compute_target(
+                &self.device,
+                &self.blit_pipeline,
+                width,
+                height,
+            );
+        }
+        // The compute pipeline is in use, so it provides a target
+        let target = compute_target.as_ref().unwrap();
+        compute_pipeline.dispatch(target, encoder);
+        let viewport = placement
+            .viewport
+            .unwrap_or([0., 0., width as f32, height as f32]);
+        self.blit_pipeline.draw_placed_to(
+            &target.intermediate,
+            view,
+            viewport,
+            Some(self.render_pipeline.background()),
+            encoder,
+        );
+    }
+
+    /// Records drawing axes and gridlines on top of the fractal in `view`. Uses the inverse view
+    /// matrix the fractal has been drawn with.
+    fn encode_grid(
+        &self,
+        view: &TextureView,
+        placement: Placement,
+        camera: &Camera,
+    ) -> CommandEncoder {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Grid Encoder"),
+            });
+        let vertices =
+            self.grid_pipeline
+                .update_lines(&self.queue, camera, placement.viewport_size());
+        self.grid_pipeline.draw_to(
+            view,
+            placement.viewport,
+            self.render_pipeline.inv_view_bind_group(),
+            vertices,
+            &mut encoder,
+        );
+        encoder
+    }
+
+    /// Records drawing the magnified fractal into the loupe on top of `view`.
+    fn encode_loupe(
+        &self,
+        view: &TextureView,
+        loupe: &Loupe,
+        loupe_target: &Intermediate,
+        camera: &Camera,
+        iterations: i32,
+        kind: &FractalKind,
+    ) -> CommandEncoder {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Loupe Encoder"),
+            });
+        let loupe_camera = camera.magnified_at(
+            loupe.cursor_ndc(self.width, self.height),
+            self.width,
+            self.height,
+            loupe.magnification,
+        );
+        // The loupe is square
+        self.render_pipeline
+            .update_buffers(&self.queue, &loupe_camera, (1, 1), iterations, kind);
+        let size = (loupe_target.width, loupe_target.height);
+        self.draw_fractal(&loupe_target.view, size, None, None, &mut encoder);
+        self.blit_pipeline.draw_circle_to(
+            loupe_target,
+            view,
+            loupe.viewport(self.width, self.height),
+            &mut encoder,
+        );
+        encoder
+    }
+
+    /// Records drawing the Julia set for the point under the cursor into the inset on top of `view`.
+    fn encode_julia_inset(
+        &self,
+        view: &TextureView,
+        inset: &JuliaInset,
+        inset_target: &Intermediate,
+        camera: &Camera,
+        iterations: i32,
+    ) -> CommandEncoder {
+        let mut encoder = self
+            .device
+            .create_command_encoder(&CommandEncoderDescriptor {
+                label: Some("Julia Inset Encoder"),
+            });
+        let [re, im] = self
+            .fit_mode
+            .placement(self.width, self.height)
+            .screen_to_complex(camera, inset.cursor);
+        let julia = FractalKind::Julia {
+            c: [re as f32, im as f32],
+        };
+        // The inset is square
+        self.render_pipeline.update_buffers(
+            &self.queue,
+            &Camera::overview(&julia),
+            (1, 1),
+            iterations,
+            &julia,
+        );
+        let size = (inset_target.width, inset_target.height);
+        self.draw_fractal(&inset_target.view, size, None, None, &mut encoder);
+        let [x, y, size] = inset.viewport(self.width, self.height);
+        self.blit_pipeline.draw_placed_to(
+            inset_target,
+            view,
+            [x, y, size, size].map(|value| value as f32),
+            None,
+            &mut encoder,
+        );
+        encoder
+    }
+
+    /// (Re)creates the render target of the loupe, if its size changed.
+    fn update_loupe_target(&mut self) {
+        let size = self
+            .loupe
+            .map(|loupe| loupe.viewport(self.width, self.height)[2]);
+        let current = self.loupe_target.take();
+        self.loupe_target = self.square_target(current, size);
+    }
+
+    /// (Re)creates the render target of the Julia inset, if its size changed.
+    fn update_julia_inset_target(&mut self) {
+        let size = self
+            .julia_inset
+            .map(|inset| inset.viewport(self.width, self.height)[2]);
+        let current = self.julia_inset_target.take();
+        self.julia_inset_target = self.square_target(current, size);
     }
 
+    /// Square render target with an edge length of `size` pixels, reusing `current` if it already
+    /// has that size. `None` if `size` is `None` or zero.
+    fn square_target(
+        &self,
+        current: Option<Intermediate>,
+        size: Option<u32>,
+    ) -> Option<Intermediate> {
+        let size = size.filter(|&size| size != 0)?;
+        match current {
+            Some(target) if target.width == size && target.height == size => Some(target),
+            _ => {
+                let format = self.internal_format.unwrap_or(self.format);
+                Some(
+                    self.blit_pipeline
+                        .intermediate(&self.device, size, size, format),
+                )
+            }
+        }
+    }
+
+    /// Intermediate render target for an output of the given size, in case an internal format or
+    /// a render scale is set.
+    fn create_intermediate(&self, width: u32, height: u32) -> Option<Intermediate> {
+        if self.internal_format.is_none() && self.render_scale == 1. {
+            return None;
+        }
+        let (width, height) = self.scaled_size(width, height);
+        let format = self.internal_format.unwrap_or(self.format);
+        Some(
+            self.blit_pipeline
+                .intermediate(&self.device, width, height, format),
+        )
+    }
+
+    /// Size of the texture the fractal is drawn into, for an output of `width` times `height`
+    /// pixels. See [`Self::set_render_scale`].
+    fn scaled_size(&self, width: u32, height: u32) -> (u32, u32) {
+        let max = self.device.limits().max_texture_dimension_2d;
+        let scale =
+            |length: u32| ((length as f32 * self.render_scale).round() as u32).clamp(1, max);
+        (scale(width), scale(height))
+    }
+
+    /// Features the adapter supports for textures of `format`. Canvases without an adapter assume
+    /// the features guaranteed by every adapter.
+    fn format_features(&self, format: TextureFormat) -> TextureFormatFeatures {
+        match &self.adapter {
+            Some(adapter) => adapter.get_texture_format_features(format),
+            None => format.describe().guaranteed_format_features,
+        }
+    }
+
+    /// Render target for drawing the fractal in motion, in case dynamic resolution is enabled. Its
+    /// edges are [`DYNAMIC_RESOLUTION_DIVISOR`] times shorter than those of the scaled canvas, see
+    /// [`Self::set_render_scale`]. Uses the
+    /// internal format, if set, since nothing is quantized before it is copied to the output.
+    fn create_reduced_target(&self) -> Option<Intermediate> {
+        if !self.dynamic_resolution {
+            return None;
+        }
+        let (width, height) = self.scaled_size(self.width, self.height);
+        let width = (width / DYNAMIC_RESOLUTION_DIVISOR).max(1);
+        let height = (height / DYNAMIC_RESOLUTION_DIVISOR).max(1);
+        let format = self.internal_format.unwrap_or(self.format);
+        Some(
+            self.blit_pipeline
+                .intermediate(&self.device, width, height, format),
+        )
+    }
+
+    /// Configures the surface for the current size. Does nothing for headless canvases.
     fn configure_surface(&self) {
+        let Target::Surface(surface) = &self.target else {
+            return;
+        };
         let config = SurfaceConfiguration {
             usage: TextureUsages::RENDER_ATTACHMENT,
             format: self.format,
             width: self.width,
             height: self.height,
-            present_mode: PresentMode::AutoVsync,
+            present_mode: self.present_mode,
             alpha_mode: CompositeAlphaMode::Opaque,
             view_formats: vec![],
         };
-        self.surface.configure(&self.device, &config)
+        surface.configure(&self.device, &config)
+    }
+}
+
+/// Decides how the picture is fitted into a canvas which is not square.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum FitMode {
+    /// The picture fills the entire canvas without distortion. Its shorter side shows the same
+    /// part of the fractal as a square canvas would, its longer side shows more of it.
+    #[default]
+    Stretch,
+    /// The picture shows the same part of the fractal as a square canvas would, centered within the
+    /// canvas. The remaining area on either side is filled with the background color.
+    Contain,
+}
+
+impl FitMode {
+    /// Where the fractal is drawn on a target of `width` times `height` pixels.
+    fn placement(self, width: u32, height: u32) -> Placement {
+        match self {
+            FitMode::Stretch => Placement::full(width, height),
+            FitMode::Contain => {
+                // Points keep the position they have with `Stretch`, only the area outside of the
+                // centered square is left blank. So cursor positions map to the same points.
+                let side = width.min(height);
+                let viewport = [
+                    ((width - side) / 2) as f32,
+                    ((height - side) / 2) as f32,
+                    side as f32,
+                    side as f32,
+                ];
+                Placement {
+                    target_size: (width, height),
+                    viewport: Some(viewport),
+                }
+            }
+        }
+    }
+}
+
+/// Which kind of shader draws the fractal, see [`Canvas::set_pipeline_mode`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PipelineMode {
+    /// Each pixel is colored by the fragment shader while rasterizing the canvas.
+    #[default]
+    Fragment,
+    /// Each pixel is colored by a compute shader writing into a storage texture, which is copied
+    /// to the output afterwards.
+    Compute,
+}
+
+/// Where on a render target the fractal is drawn.
+#[derive(Clone, Copy)]
+struct Placement {
+    /// Size of the entire render target in pixels.
+    target_size: (u32, u32),
+    /// `x`, `y`, `width` and `height` in pixels of the rectangle the fractal is drawn into. `None`
+    /// draws onto the entire target.
+    viewport: Option<[f32; 4]>,
+}
+
+impl Placement {
+    /// Fractal covers the entire target of `width` times `height` pixels.
+    fn full(width: u32, height: u32) -> Self {
+        Placement {
+            target_size: (width, height),
+            viewport: None,
+        }
+    }
+
+    /// Same placement on a target of `width` times `height` pixels, with the viewport scaled
+    /// accordingly.
+    fn scaled_to(self, width: u32, height: u32) -> Self {
+        let (target_width, target_height) = self.target_size;
+        let scale_x = width as f32 / target_width as f32;
+        let scale_y = height as f32 / target_height as f32;
+        Placement {
+            target_size: (width, height),
+            viewport: self
+                .viewport
+                .map(|[x, y, w, h]| [x * scale_x, y * scale_y, w * scale_x, h * scale_y]),
+        }
+    }
+
+    /// Size of the area the fractal is drawn into in pixels, used to correct for its aspect ratio.
+    fn viewport_size(&self) -> (u32, u32) {
+        match self.viewport {
+            Some([_, _, width, height]) => (width.round() as u32, height.round() as u32),
+            None => self.target_size,
+        }
+    }
+
+    /// Point of the complex plane `camera` shows at `position`, given in pixels relative to the top
+    /// left of the target. Positions outside of the viewport, e.g. on the bars next to a contained
+    /// picture, are moved onto its closest edge, since only the viewport shows the fractal.
+    fn screen_to_complex(&self, camera: &Camera, position: [f32; 2]) -> [f64; 2] {
+        let (target_width, target_height) = self.target_size;
+        let [x, y, width, height] =
+            self.viewport
+                .unwrap_or([0., 0., target_width as f32, target_height as f32]);
+        let ndc = [
+            (2.0 * (position[0] - x) / width - 1.0).clamp(-1., 1.),
+            (1.0 - 2.0 * (position[1] - y) / height).clamp(-1., 1.),
+        ];
+        let (width, height) = self.viewport_size();
+        camera.screen_to_complex(ndc, width, height)
+    }
+}
+
+/// What a [`Canvas`] renders to.
+enum Target {
+    /// Surface linked to the inner part of a window.
+    Surface(Surface),
+    /// Texture of the same size as the canvas, used by headless canvases.
+    Texture(Texture),
+    /// The application provides the views to render into, see [`Canvas::render_into`].
+    External,
+}
+
+/// `true` if textures with `features` can be multisampled with `count` samples per pixel and
+/// resolved afterwards. A single sample is always supported.
+fn supports_sample_count(features: &TextureFormatFeatures, count: u32) -> bool {
+    count == 1
+        || (features.flags.sample_count_supported(count)
+            && features
+                .flags
+                .contains(TextureFormatFeatureFlags::MULTISAMPLE_RESOLVE))
+}
+
+/// `true` if a surface with these dimensions would not contain any pixel. Configuring such a
+/// surface crashes wgpu.
+fn is_empty(width: u32, height: u32) -> bool {
+    width == 0 || height == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Camera;
+
+    use super::{is_empty, FitMode};
+
+    #[test]
+    fn surfaces_with_one_zero_dimension_are_empty() {
+        assert!(is_empty(400, 0));
+        assert!(is_empty(0, 300));
+        assert!(is_empty(0, 0));
+        assert!(!is_empty(400, 300));
+    }
+
+    #[test]
+    fn contained_picture_maps_pixels_like_a_stretched_one() {
+        let camera = Camera::new();
+        let contain = FitMode::Contain.placement(300, 100);
+        let stretch = FitMode::Stretch.placement(300, 100);
+
+        for position in [[150., 50.], [120., 10.], [199., 99.]] {
+            let [stretch_re, stretch_im] = stretch.screen_to_complex(&camera, position);
+            let [contain_re, contain_im] = contain.screen_to_complex(&camera, position);
+            assert!((stretch_re - contain_re).abs() < 1e-6);
+            assert!((stretch_im - contain_im).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn positions_on_the_bars_pick_the_closest_edge_of_the_picture() {
+        let camera = Camera::new();
+        let placement = FitMode::Contain.placement(300, 100);
+
+        assert_eq!(
+            placement.screen_to_complex(&camera, [100., 50.]),
+            placement.screen_to_complex(&camera, [10., 50.])
+        );
+        assert_eq!(
+            placement.screen_to_complex(&camera, [200., 0.]),
+            placement.screen_to_complex(&camera, [290., 0.])
+        );
     }
 }
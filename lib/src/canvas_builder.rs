@@ -0,0 +1,196 @@
+use crate::{Canvas, CanvasError, SurfaceTarget};
+use log::{info, warn};
+use wgpu::{
+    Adapter, Backends, DeviceDescriptor, Features, Instance, InstanceDescriptor, Limits,
+    PowerPreference, PresentMode, RequestAdapterOptions, Surface, TextureFormat,
+};
+
+/// Configures how a [`Canvas`] chooses and sets up its graphics device.
+///
+/// ```no_run
+/// # async fn example(window: &winit::window::Window) -> Result<(), fractal_wgpu_lib::CanvasError> {
+/// use fractal_wgpu_lib::{Backends, CanvasBuilder, PowerPreference};
+///
+/// let canvas = CanvasBuilder::new(800, 600)
+///     .backends(Backends::VULKAN)
+///     .power_preference(PowerPreference::LowPower)
+///     .build(window)
+///     .await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct CanvasBuilder {
+    width: u32,
+    height: u32,
+    backends: Backends,
+    power_preference: PowerPreference,
+    present_mode: PresentMode,
+    limits: Limits,
+    label: Option<String>,
+    back_face_culling: bool,
+}
+
+impl CanvasBuilder {
+    /// Starts configuring a canvas with the given size in pixels. By default any backend is used,
+    /// the high performance adapter is preferred and frames are synchronized with the display.
+    pub fn new(width: u32, height: u32) -> Self {
+        let limits = if cfg!(target_arch = "wasm32") {
+            Limits::downlevel_webgl2_defaults()
+        } else {
+            Limits::default()
+        };
+        CanvasBuilder {
+            width,
+            height,
+            backends: Backends::all(),
+            power_preference: PowerPreference::HighPerformance,
+            present_mode: PresentMode::AutoVsync,
+            limits,
+            label: None,
+            back_face_culling: true,
+        }
+    }
+
+    /// Graphics APIs the adapter is chosen from.
+    pub fn backends(mut self, backends: Backends) -> Self {
+        self.backends = backends;
+        self
+    }
+
+    /// Whether to prefer the fast or the energy efficient adapter, if there are several. On laptops
+    /// [`PowerPreference::LowPower`] usually picks the integrated GPU, saving battery. Defaults to
+    /// [`PowerPreference::HighPerformance`].
+    pub fn power_preference(mut self, power_preference: PowerPreference) -> Self {
+        self.power_preference = power_preference;
+        self
+    }
+
+    /// See [`Canvas::set_present_mode`].
+    pub fn present_mode(mut self, present_mode: PresentMode) -> Self {
+        self.present_mode = present_mode;
+        self
+    }
+
+    /// Limits requested from the device. Defaults to [`Limits::downlevel_webgl2_defaults`] in the
+    /// browser and [`Limits::default`] everywhere else.
+    pub fn limits(mut self, limits: Limits) -> Self {
+        self.limits = limits;
+        self
+    }
+
+    /// Label of the device, to identify it in debugging tools and error messages.
+    pub fn label(mut self, label: impl Into<String>) -> Self {
+        self.label = Some(label.into());
+        self
+    }
+
+    /// See [`Canvas::set_back_face_culling`]. Disabling it helps debugging backends which draw
+    /// nothing at all. Enabled by default.
+    pub fn back_face_culling(mut self, enabled: bool) -> Self {
+        self.back_face_culling = enabled;
+        self
+    }
+
+    /// Creates a canvas linked to `window`. See [`Canvas::new`].
+    pub async fn build<'w>(
+        self,
+        window: impl Into<SurfaceTarget<'w>>,
+    ) -> Result<Canvas<'w>, CanvasError> {
+        let window = window.into();
+        let instance = self.instance();
+        // Safety: The canvas holds on to `window` until it is dropped, so the window outlives the
+        // surface.
+        let surface = unsafe { instance.create_surface(&window.window())? };
+        let adapter = self.request_adapter(&instance, Some(&surface)).await?;
+        let caps = surface.get_capabilities(&adapter);
+        // The first format in the array is the prefered one.
+        let format = caps.formats[0];
+        self.finish(Some(surface), Some(window), adapter, format)
+            .await
+    }
+
+    /// Creates a canvas without any window. See [`Canvas::new_headless`].
+    pub async fn build_headless(self) -> Result<Canvas<'static>, CanvasError> {
+        let instance = self.instance();
+        let adapter = self.request_adapter(&instance, None).await?;
+        let format = TextureFormat::Rgba8UnormSrgb;
+        self.finish(None, None, adapter, format).await
+    }
+
+    fn instance(&self) -> Instance {
+        Instance::new(InstanceDescriptor {
+            backends: self.backends,
+            ..InstanceDescriptor::default()
+        })
+    }
+
+    /// Chooses the adapter used for rendering. Pass the surface we want to render to, if any. Falls
+    /// back to a software adapter, if no hardware adapter is available, e.g. on machines without a
+    /// GPU driver.
+    async fn request_adapter(
+        &self,
+        instance: &Instance,
+        compatible_surface: Option<&Surface>,
+    ) -> Result<Adapter, CanvasError> {
+        let request = |force_fallback_adapter| {
+            instance.request_adapter(&RequestAdapterOptions {
+                power_preference: self.power_preference,
+                force_fallback_adapter,
+                compatible_surface,
+            })
+        };
+        let adapter = match request(false).await {
+            Some(adapter) => adapter,
+            None => {
+                let adapter = request(true)
+                    .await
+                    .ok_or(CanvasError::NoAdapter(self.backends))?;
+                warn!("No hardware adapter available. Falling back to a slow software adapter.");
+                adapter
+            }
+        };
+        // Which adapter a power preference results in depends on the machine, so we report it.
+        let info = adapter.get_info();
+        info!(
+            "Rendering on {} ({:?}) using {:?}, preferring {:?}. Driver: {} {}",
+            info.name,
+            info.device_type,
+            info.backend,
+            self.power_preference,
+            info.driver,
+            info.driver_info
+        );
+        Ok(adapter)
+    }
+
+    async fn finish<'w>(
+        self,
+        surface: Option<Surface>,
+        window: Option<SurfaceTarget<'w>>,
+        adapter: Adapter,
+        format: TextureFormat,
+    ) -> Result<Canvas<'w>, CanvasError> {
+        // Timestamp queries allow measuring GPU time, but we do without them if unsupported.
+        let features = adapter.features() & Features::TIMESTAMP_QUERY;
+        let device_descriptor = DeviceDescriptor {
+            label: self.label.as_deref(),
+            features,
+            limits: self.limits.clone(),
+        };
+        let mut canvas = Canvas::with_target(
+            self.width,
+            self.height,
+            surface,
+            window,
+            adapter,
+            format,
+            &device_descriptor,
+        )
+        .await?;
+        canvas.set_present_mode(self.present_mode);
+        if !self.back_face_culling {
+            canvas.set_back_face_culling(false);
+        }
+        Ok(canvas)
+    }
+}
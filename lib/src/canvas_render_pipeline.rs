@@ -1,13 +1,33 @@
+use std::cell::Cell;
+
+// `std::time::Instant` panics in the browser, `instant` falls back to `performance.now()` there.
+use instant::Instant;
 use wgpu::{
     util::{BufferInitDescriptor, DeviceExt},
-    BindGroup, BlendState, Buffer, BufferUsages, Color, ColorTargetState, ColorWrites,
-    CommandEncoder, Device, FragmentState, MultisampleState, Operations, PipelineLayoutDescriptor,
-    PrimitiveState, PrimitiveTopology, Queue, RenderPassColorAttachment, RenderPassDescriptor,
-    RenderPipeline, RenderPipelineDescriptor, ShaderModuleDescriptor, ShaderSource, TextureFormat,
-    TextureView, VertexState,
+    BindGroup, BindGroupLayout, BlendState, Buffer, BufferUsages, Color, ColorTargetState,
+    ColorWrites, CommandEncoder, Device, ErrorFilter, Face, FragmentState, FrontFace,
+    MultisampleState, Operations, PipelineLayout, PipelineLayoutDescriptor, PrimitiveState,
+    PrimitiveTopology, Queue, RenderPass, RenderPassColorAttachment, RenderPassDescriptor,
+    RenderPipeline, RenderPipelineDescriptor, ShaderModule, ShaderModuleDescriptor, ShaderSource,
+    TextureFormat, TextureView, VertexState,
 };
 
-use crate::shader::{inv_view_uniform, iterations_uniform, Vertex, CANVAS_SHADER_SOURCE, inv_view_to_bytes};
+use crate::{
+    blit_pipeline::BlitPipeline,
+    compute_pipeline::{ComputePipeline, ComputeTarget, IterationsTarget},
+    equalization::EqualizationArgs,
+    shader::{
+        fractal_uniform, fragment_uniform, inv_view_to_bytes, inv_view_uniform, palette_uniform,
+        ColorSettings, Equalization, FractalArgs, FragmentArgs, PaletteArgs, Vertex,
+        CANVAS_SHADER_SOURCE, FRACTAL_LAYOUT, FRAGMENT_LAYOUT, INV_VIEW_LAYOUT, PALETTE_LAYOUT,
+    },
+    Camera, CanvasError, ColorMapping, ColoringMode, FractalKind, InteriorMode, PipelineMode,
+    DEFAULT_ESCAPE_RADIUS, DEFAULT_PALETTE,
+};
+
+/// Format of the render target of the histogram pass. Escape values are written with 8 Bit
+/// precision, one byte per bin of the histogram.
+pub const HISTOGRAM_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
 
 /// A specialised render pipeline for our 2D canvas.
 ///
@@ -15,6 +35,20 @@ use crate::shader::{inv_view_uniform, iterations_uniform, Vertex, CANVAS_SHADER_
 /// input buffers to them.
 pub struct CanvasRenderPipeline {
     render_pipeline: RenderPipeline,
+    /// Same shader as `render_pipeline`, but rendering into targets of [`HISTOGRAM_FORMAT`]. Used
+    /// to gather the escape values for histogram equalization.
+    histogram_pipeline: RenderPipeline,
+    /// Compiled canvas shader. Kept around so we can recreate `render_pipeline` for a different
+    /// target format.
+    shader: ShaderModule,
+    /// WGSL source of `shader`, so the compute pipeline can be created from the same source.
+    source: String,
+    /// Draws the fractal with a compute shader instead of `render_pipeline`, if set. Shares the
+    /// buffers of this pipeline.
+    compute_pipeline: Option<ComputePipeline>,
+    /// Layout of the bind groups. Kept around so we can recreate `render_pipeline` for a different
+    /// target format.
+    layout: PipelineLayout,
     /// Used to pass the coordinates of the canvas to the shader in each render pass.
     vertex_buffer: Buffer,
     /// We hold the buffer explicitly, so we can manipulate its contents between frames to change
@@ -23,12 +57,61 @@ pub struct CanvasRenderPipeline {
     /// Used to pass the inverse view matrix in `inv_view_buffer` to the vertex shader in each
     /// render pass.
     inv_view_bind_group: BindGroup,
-    /// We hold the buffer explicitly, so we can manipulate its contents between frames to how much
-    /// elements of the sequence we calculate before we consider it convergent.
-    iter_buffer: Buffer,
-    /// Used to pass the number of iterations in `iter_buffer` to the fragment shader in each render
+    /// Layout of `inv_view_bind_group`, so other pipelines can share the inverse view matrix.
+    inv_view_layout: BindGroupLayout,
+    /// We hold the buffer explicitly, so we can manipulate its contents between frames to change
+    /// how much elements of the sequence we calculate before we consider it convergent, and the
+    /// precise camera position.
+    fragment_buffer: Buffer,
+    /// Used to pass the arguments in `fragment_buffer` to the fragment shader in each render pass.
+    fragment_bind_group: BindGroup,
+    /// Number of samples per pixel along each axis.
+    supersampling: u32,
+    /// Number of samples per pixel of the multisampled target `render_pipeline` draws into. `1`
+    /// disables multisampling.
+    sample_count: u32,
+    /// Faces of triangles which are not drawn. `None` draws both, see [`Self::set_cull_mode`].
+    cull_mode: Option<Face>,
+    /// Color the output is cleared with, before the fractal is drawn.
+    background: Color,
+    /// Exponent `z` is raised to in each iteration.
+    power: f32,
+    /// How the color of a pixel is derived from its sequence.
+    coloring: ColoringMode,
+    /// How escape values are mapped to positions in the palette.
+    mapping: ColorMapping,
+    /// How points which never escape are colored.
+    interior: InteriorMode,
+    /// Escape values are continuous, rather than whole numbers of iterations.
+    smooth_coloring: bool,
+    /// Gamma of the output. Colors are raised to the power of `1 / gamma` by the shader.
+    gamma: f32,
+    /// Sequences are considered escaping once their magnitude exceeds this radius.
+    escape_radius: f32,
+    /// Point in time the color cycle speed has been changed last. The cycle progresses from there.
+    cycle_start: Instant,
+    /// Shift of the palette at `cycle_start`.
+    cycle_start_offset: f32,
+    /// Number of times per second the colors cycle through the entire palette.
+    color_cycle_speed: f32,
+    /// Spread escape values evenly over the palette, using their cumulative distribution.
+    equalize: bool,
+    /// We hold the buffer explicitly, so we can switch the kind of fractal between frames.
+    fractal_buffer: Buffer,
+    /// Used to pass the kind of fractal in `fractal_buffer` to the fragment shader in each render
     /// pass.
-    iter_bind_group: BindGroup,
+    fractal_bind_group: BindGroup,
+    /// We hold the buffer explicitly, so we can change the colors of the fractal.
+    palette_buffer: Buffer,
+    /// Contents of `palette_buffer`, so colors and inversion can be changed independently. A cell,
+    /// since rendering a [`crate::RenderState`] may switch the palette.
+    palette: Cell<PaletteArgs>,
+    /// We hold the buffer explicitly, so we can update the distribution of escape values for
+    /// histogram equalization between frames.
+    equalization_buffer: Buffer,
+    /// Used to pass the color palette in `palette_buffer` and the distribution in
+    /// `equalization_buffer` to the fragment shader in each render pass.
+    palette_bind_group: BindGroup,
 }
 
 impl CanvasRenderPipeline {
@@ -38,11 +121,19 @@ impl CanvasRenderPipeline {
     ///
     /// * `device` is used to create the render pipeline, load shaders and bind buffers.
     /// * `surface_format` is the format of the target (output) for the render pipeline.
-    pub fn new(device: &Device, surface_format: TextureFormat) -> Self {
+    ///
+    /// Fails with [`CanvasError::ShaderCompile`] if the canvas shader can not be compiled.
+    pub async fn new(device: &Device, surface_format: TextureFormat) -> Result<Self, CanvasError> {
+        // Capture validation errors during shader creation, rather than letting the default error
+        // handler panic with an opaque message.
+        device.push_error_scope(ErrorFilter::Validation);
         let shader = device.create_shader_module(ShaderModuleDescriptor {
             label: Some("Canvas Shader"),
             source: ShaderSource::Wgsl(CANVAS_SHADER_SOURCE.into()),
         });
+        if let Some(error) = device.pop_error_scope().await {
+            return Err(CanvasError::ShaderCompile(error.to_string()));
+        }
 
         let vertex_buffer = device.create_buffer_init(&BufferInitDescriptor {
             label: Some("Canvas vertices"),
@@ -54,118 +145,627 @@ impl CanvasRenderPipeline {
         let (inv_view_layout, inv_view_buffer, inv_view_bind_group) =
             inv_view_uniform(device, initial_inv_view);
 
-        let (iter_layout, iter_buffer, iter_group) = iterations_uniform(device, 1);
+        let (fragment_layout, fragment_buffer, fragment_bind_group) = fragment_uniform(
+            device,
+            &FragmentArgs::new(1, 1, [0., 0.], &ColorSettings::default()),
+        );
+
+        let (fractal_layout, fractal_buffer, fractal_bind_group) =
+            fractal_uniform(device, &FractalKind::Mandelbrot);
+
+        let palette = PaletteArgs::new(DEFAULT_PALETTE);
+        let (palette_layout, palette_buffer, equalization_buffer, palette_bind_group) =
+            palette_uniform(device, &palette);
 
         let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
             label: Some("Render Pipeline Layout"),
-            bind_group_layouts: &[&inv_view_layout, &iter_layout],
+            bind_group_layouts: &[
+                &inv_view_layout,
+                &fragment_layout,
+                &fractal_layout,
+                &palette_layout,
+            ],
             push_constant_ranges: &[],
         });
 
-        let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
-            label: Some("Canvas Render Pipeline"),
-            layout: Some(&layout),
-            vertex: VertexState {
-                module: &shader,
-                entry_point: "vs_main",
-                buffers: &[Vertex::DESC],
-            },
-            fragment: Some(FragmentState {
-                module: &shader,
-                entry_point: "fs_main",
-                targets: &[Some(ColorTargetState {
-                    format: surface_format,
-                    blend: Some(BlendState::REPLACE),
-                    write_mask: ColorWrites::ALL,
-                })],
-            }),
-            primitive: PrimitiveState {
-                topology: PrimitiveTopology::TriangleStrip,
-                strip_index_format: None,
-                front_face: wgpu::FrontFace::Ccw,
-                cull_mode: Some(wgpu::Face::Back),
-                // Requires Features::DEPTH_CLIP_CONTROL
-                unclipped_depth: false,
-                // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
-                polygon_mode: wgpu::PolygonMode::Fill,
-                // Requires Features::CONSERVATIVE_RASTERIZATION
-                conservative: false,
-            },
-            depth_stencil: None,
-            multiview: None,
-            multisample: MultisampleState {
-                count: 1,
-                mask: !0,
-                alpha_to_coverage_enabled: false,
-            },
-        });
+        let cull_mode = Some(Face::Back);
+        let render_pipeline =
+            create_render_pipeline(device, &layout, &shader, surface_format, 1, cull_mode);
+        let histogram_pipeline =
+            create_render_pipeline(device, &layout, &shader, HISTOGRAM_FORMAT, 1, cull_mode);
 
-        CanvasRenderPipeline {
+        Ok(CanvasRenderPipeline {
             render_pipeline,
+            histogram_pipeline,
+            shader,
+            source: CANVAS_SHADER_SOURCE.to_owned(),
+            compute_pipeline: None,
+            layout,
             inv_view_buffer,
             vertex_buffer,
             inv_view_bind_group,
-            iter_buffer,
-            iter_bind_group: iter_group,
+            inv_view_layout,
+            fragment_buffer,
+            fragment_bind_group,
+            supersampling: 1,
+            sample_count: 1,
+            cull_mode,
+            background: Color::BLACK,
+            power: 2.,
+            coloring: ColoringMode::EscapeTime,
+            mapping: ColorMapping::Linear,
+            interior: InteriorMode::Black,
+            smooth_coloring: true,
+            gamma: default_gamma(surface_format),
+            escape_radius: DEFAULT_ESCAPE_RADIUS,
+            cycle_start: Instant::now(),
+            cycle_start_offset: 0.,
+            color_cycle_speed: 0.,
+            equalize: false,
+            fractal_buffer,
+            fractal_bind_group,
+            palette_buffer,
+            palette: Cell::new(palette),
+            equalization_buffer,
+            palette_bind_group,
+        })
+    }
+
+    /// Recreates the pipeline so it renders into targets of `format`. Buffers and their contents
+    /// are kept.
+    pub fn set_target_format(&mut self, device: &Device, format: TextureFormat) {
+        self.render_pipeline = create_render_pipeline(
+            device,
+            &self.layout,
+            &self.shader,
+            format,
+            self.sample_count,
+            self.cull_mode,
+        );
+    }
+
+    /// Recreates the pipeline so it renders into multisampled targets of `format` with `count`
+    /// samples per pixel. The caller must ensure the device supports `count` for `format`.
+    pub fn set_sample_count(&mut self, device: &Device, format: TextureFormat, count: u32) {
+        self.sample_count = count;
+        self.set_target_format(device, format);
+    }
+
+    /// Recreates the pipelines so they skip triangles facing the other way than `cull_mode`, or
+    /// draw all triangles if `None`. Drawing all of them helps to tell whether a blank picture is
+    /// caused by the winding of the vertices, e.g. on a new backend.
+    pub fn set_cull_mode(
+        &mut self,
+        device: &Device,
+        format: TextureFormat,
+        cull_mode: Option<Face>,
+    ) {
+        self.cull_mode = cull_mode;
+        self.histogram_pipeline = create_render_pipeline(
+            device,
+            &self.layout,
+            &self.shader,
+            HISTOGRAM_FORMAT,
+            1,
+            cull_mode,
+        );
+        self.set_target_format(device, format);
+    }
+
+    /// Number of samples per pixel of the targets passed to [`Self::draw_to`] as `multisampled`.
+    pub fn sample_count(&self) -> u32 {
+        self.sample_count
+    }
+
+    /// Replaces the canvas shader with one compiled from the WGSL `source` and recreates the
+    /// pipeline so it renders into targets of `format`. The shader must declare the same entry
+    /// points and bind groups as the built-in one.
+    ///
+    /// Fails with [`CanvasError::ShaderCompile`] if the shader can not be compiled or does not fit
+    /// the pipeline. Fails with [`CanvasError::ComputePipeline`] if the compute pipeline is in use
+    /// and can not be created from the shader. In either case the previous shader stays in use.
+    pub async fn reload_shader(
+        &mut self,
+        device: &Device,
+        source: &str,
+        format: TextureFormat,
+    ) -> Result<(), CanvasError> {
+        // Creating the pipeline validates entry points and bindings of the shader, so we capture
+        // errors from both steps.
+        device.push_error_scope(ErrorFilter::Validation);
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Canvas Shader"),
+            source: ShaderSource::Wgsl(source.into()),
+        });
+        let render_pipeline = create_render_pipeline(
+            device,
+            &self.layout,
+            &shader,
+            format,
+            self.sample_count,
+            self.cull_mode,
+        );
+        let histogram_pipeline = create_render_pipeline(
+            device,
+            &self.layout,
+            &shader,
+            HISTOGRAM_FORMAT,
+            1,
+            self.cull_mode,
+        );
+        if let Some(error) = device.pop_error_scope().await {
+            return Err(CanvasError::ShaderCompile(error.to_string()));
+        }
+        let compute_pipeline = match self.compute_pipeline {
+            Some(_) => Some(self.create_compute_pipeline(device, source).await?),
+            None => None,
+        };
+        self.shader = shader;
+        self.source = source.to_owned();
+        self.render_pipeline = render_pipeline;
+        self.histogram_pipeline = histogram_pipeline;
+        self.compute_pipeline = compute_pipeline;
+        Ok(())
+    }
+
+    /// Switches between drawing the fractal with the fragment shader and the compute shader. See
+    /// [`Self::compute_pipeline`].
+    ///
+    /// Fails with [`CanvasError::ComputePipeline`] if the compute pipeline can not be created. In
+    /// that case the fragment shader stays in use.
+    pub async fn set_pipeline_mode(
+        &mut self,
+        device: &Device,
+        mode: PipelineMode,
+    ) -> Result<(), CanvasError> {
+        match mode {
+            PipelineMode::Fragment => self.compute_pipeline = None,
+            PipelineMode::Compute => {
+                if self.compute_pipeline.is_none() {
+                    self.compute_pipeline =
+                        Some(self.create_compute_pipeline(device, &self.source).await?);
+                }
+            }
         }
+        Ok(())
+    }
+
+    /// Compute pipeline, if the fractal is drawn with the compute shader rather than
+    /// [`Self::draw_to`]. Reads the arguments written by [`Self::update_buffers`], too.
+    pub fn compute_pipeline(&self) -> Option<&ComputePipeline> {
+        self.compute_pipeline.as_ref()
+    }
+
+    /// Storage texture of `width` times `height` pixels for [`Self::compute_pipeline`] to write
+    /// into. `None` unless the compute pipeline is in use.
+    pub fn compute_target(
+        &self,
+        device: &Device,
+        blit: &BlitPipeline,
+        width: u32,
+        height: u32,
+    ) -> Option<ComputeTarget> {
+        let buffers = [&self.palette_buffer, &self.equalization_buffer];
+        self.compute_pipeline
+            .as_ref()
+            .map(|compute| compute.target(device, blit, width, height, &buffers))
+    }
+
+    /// Storage buffer for [`Self::compute_pipeline`] to write the smooth iteration counts of
+    /// `width` times `height` pixels into. `None` unless the compute pipeline is in use. Fails if
+    /// the buffer would exceed the limits of the device.
+    pub fn iterations_target(
+        &self,
+        device: &Device,
+        width: u32,
+        height: u32,
+    ) -> Result<Option<IterationsTarget>, CanvasError> {
+        let buffers = [&self.palette_buffer, &self.equalization_buffer];
+        self.compute_pipeline
+            .as_ref()
+            .map(|compute| compute.iterations_target(device, width, height, &buffers))
+            .transpose()
+    }
+
+    /// Compute pipeline for the canvas shader `source`, bound to the buffers of this pipeline.
+    async fn create_compute_pipeline(
+        &self,
+        device: &Device,
+        source: &str,
+    ) -> Result<ComputePipeline, CanvasError> {
+        ComputePipeline::new(
+            device,
+            source,
+            &[
+                (&INV_VIEW_LAYOUT, &[&self.inv_view_buffer]),
+                (&FRAGMENT_LAYOUT, &[&self.fragment_buffer]),
+                (&FRACTAL_LAYOUT, &[&self.fractal_buffer]),
+                (
+                    &PALETTE_LAYOUT,
+                    &[&self.palette_buffer, &self.equalization_buffer],
+                ),
+            ],
+        )
+        .await
     }
 
-    /// Updates the buffers submitted to the shaders in each frame.
-    pub fn update_buffers(&self, queue: &Queue, inv_view_matrix: [[f32; 2]; 3], iterations: i32) {
+    /// Updates the buffers submitted to the shaders in each frame. `(width, height)` is the size of
+    /// the render target in pixels, used to correct for its aspect ratio.
+    pub fn update_buffers(
+        &self,
+        queue: &Queue,
+        camera: &Camera,
+        size: (u32, u32),
+        iterations: i32,
+        kind: &FractalKind,
+    ) {
+        let equalization = if self.equalizes(kind) {
+            Equalization::On
+        } else {
+            Equalization::Off
+        };
+        self.write_buffers(queue, camera, size, iterations, kind, equalization);
+    }
+
+    /// Updates the buffers for the histogram pass, see [`Self::draw_histogram_to`].
+    pub fn update_histogram_buffers(
+        &self,
+        queue: &Queue,
+        camera: &Camera,
+        size: (u32, u32),
+        iterations: i32,
+        kind: &FractalKind,
+    ) {
+        self.write_buffers(
+            queue,
+            camera,
+            size,
+            iterations,
+            kind,
+            Equalization::Histogram,
+        );
+    }
+
+    fn write_buffers(
+        &self,
+        queue: &Queue,
+        camera: &Camera,
+        (width, height): (u32, u32),
+        iterations: i32,
+        kind: &FractalKind,
+        equalization: Equalization,
+    ) {
         queue.write_buffer(
             &self.inv_view_buffer,
             0,
-            inv_view_to_bytes(&inv_view_matrix).as_slice()
+            inv_view_to_bytes(&camera.inv_view_for(width, height)).as_slice(),
         );
-        let mut iterations_padded = [0i32; 4];
-        iterations_padded[0] = iterations;
         queue.write_buffer(
-            &self.iter_buffer,
+            &self.fragment_buffer,
+            0,
+            bytemuck::bytes_of(&FragmentArgs::new(
+                iterations,
+                self.supersampling,
+                camera.position(),
+                &ColorSettings {
+                    coloring: self.coloring,
+                    mapping: self.mapping,
+                    interior: self.interior,
+                    smooth: self.smooth_coloring,
+                    gamma: self.gamma,
+                    color_offset: self.color_offset(),
+                    equalization,
+                    escape_radius: self.escape_radius,
+                },
+            )),
+        );
+        queue.write_buffer(
+            &self.fractal_buffer,
+            0,
+            bytemuck::bytes_of(&FractalArgs::new(kind, self.power)),
+        );
+    }
+
+    /// Number of samples per pixel along each axis, used from the next call to `update_buffers`
+    /// on.
+    pub fn set_supersampling(&mut self, factor: u32) {
+        self.supersampling = factor;
+    }
+
+    /// Exponent `z` is raised to in each iteration, used from the next call to `update_buffers` on.
+    /// Must be finite and larger than `1`.
+    pub fn set_power(&mut self, power: f32) {
+        debug_assert!(power.is_finite() && power > 1.);
+        self.power = power;
+    }
+
+    /// How the color of a pixel is derived, used from the next call to `update_buffers` on.
+    pub fn set_coloring_mode(&mut self, coloring: ColoringMode) {
+        self.coloring = coloring;
+    }
+
+    /// How escape values are mapped to the palette, used from the next call to `update_buffers`
+    /// on.
+    pub fn set_color_mapping(&mut self, mapping: ColorMapping) {
+        self.mapping = mapping;
+    }
+
+    /// How points which never escape are colored, used from the next call to `update_buffers` on.
+    pub fn set_interior_mode(&mut self, interior: InteriorMode) {
+        self.interior = interior;
+    }
+
+    /// Whether escape values are continuous or whole numbers of iterations, used from the next
+    /// call to `update_buffers` on.
+    pub fn set_smooth_coloring(&mut self, enabled: bool) {
+        self.smooth_coloring = enabled;
+    }
+
+    /// Number of times per second the colors cycle through the entire palette. `0` returns the
+    /// palette to its original place.
+    pub fn set_color_cycle_speed(&mut self, speed: f32) {
+        // Continue the cycle from the current offset, so changing the speed does not make colors
+        // jump.
+        self.cycle_start_offset = if speed == 0. { 0. } else { self.color_offset() };
+        self.cycle_start = Instant::now();
+        self.color_cycle_speed = speed;
+    }
+
+    /// Current shift of the palette as a fraction of its length.
+    fn color_offset(&self) -> f32 {
+        let elapsed = self.cycle_start.elapsed().as_secs_f32();
+        (self.cycle_start_offset + elapsed * self.color_cycle_speed).rem_euclid(1.)
+    }
+
+    /// Spread escape values evenly over the palette, used from the next call to `update_buffers`
+    /// on.
+    pub fn set_histogram_equalization(&mut self, enabled: bool) {
+        self.equalize = enabled;
+    }
+
+    /// `true` if drawing `kind` requires the distribution of escape values. Equalization only
+    /// applies to escape time coloring, which the Newton fractal does not use.
+    pub fn equalizes(&self, kind: &FractalKind) -> bool {
+        self.equalize && self.coloring == ColoringMode::EscapeTime && *kind != FractalKind::Newton
+    }
+
+    /// Replaces the distribution escape values are mapped through if equalization is enabled.
+    pub fn set_distribution(&self, queue: &Queue, distribution: &EqualizationArgs) {
+        queue.write_buffer(
+            &self.equalization_buffer,
             0,
-            bytemuck::cast_slice(&iterations_padded),
+            bytemuck::bytes_of(distribution),
         );
     }
 
-    pub fn draw_to(&self, output: &TextureView, encoder: &mut CommandEncoder) {
+    /// Gamma of the output, used from the next call to `update_buffers` on.
+    pub fn set_gamma(&mut self, gamma: f32) {
+        self.gamma = gamma;
+    }
+
+    /// Radius beyond which sequences are considered escaping, used from the next call to
+    /// `update_buffers` on.
+    pub fn set_escape_radius(&mut self, radius: f32) {
+        self.escape_radius = radius;
+    }
+
+    /// Color the output is cleared with, before the fractal is drawn.
+    pub fn set_background(&mut self, color: Color) {
+        self.background = color;
+    }
+
+    /// Color the output is cleared with, before the fractal is drawn.
+    pub fn background(&self) -> Color {
+        self.background
+    }
+
+    /// Replaces the colors of the palette. `colors` must not be empty and hold at most
+    /// [`crate::MAX_PALETTE_COLORS`] elements.
+    pub fn set_palette(&self, queue: &Queue, colors: &[[f32; 3]]) {
+        let mut palette = PaletteArgs::new(colors);
+        palette.set_inverted(self.palette.get().invert());
+        self.write_palette(queue, palette);
+    }
+
+    /// Inverts every color of the output, if `invert` is `true`.
+    pub fn set_color_inversion(&mut self, queue: &Queue, invert: bool) {
+        let mut palette = self.palette.get();
+        palette.set_inverted(invert);
+        self.write_palette(queue, palette);
+    }
+
+    /// Remembers `palette` and uploads it to the GPU.
+    fn write_palette(&self, queue: &Queue, palette: PaletteArgs) {
+        self.palette.set(palette);
+        queue.write_buffer(&self.palette_buffer, 0, bytemuck::bytes_of(&palette));
+    }
+
+    /// Draws the fractal into `output`. `viewport` restricts drawing to the rectangle given by `x`,
+    /// `y`, `width` and `height` in pixels. The remainder of `output` is cleared with the
+    /// background color.
+    ///
+    /// If multisampling is enabled, the fractal is drawn into `multisampled` instead, which must
+    /// match `output` in size and hold [`Self::sample_count`] samples per pixel. It is resolved
+    /// into `output` at the end of the pass.
+    ///
+    /// If `scissor` is specified, only pixels within the rectangle `x`, `y`, `width` and `height`
+    /// are touched and the remainder of `output` is kept. This allows drawing several fractals
+    /// next to each other, one pass each. Since the entire multisampled target is resolved, its
+    /// contents are kept between passes, too.
+    pub fn draw_to(
+        &self,
+        output: &TextureView,
+        multisampled: Option<&TextureView>,
+        viewport: Option<[f32; 4]>,
+        scissor: Option<[u32; 4]>,
+        encoder: &mut CommandEncoder,
+    ) {
+        let (view, resolve_target) = match multisampled {
+            Some(multisampled) => (multisampled, Some(output)),
+            None => (output, None),
+        };
+        let load = match scissor {
+            Some(_) => wgpu::LoadOp::Load,
+            None => wgpu::LoadOp::Clear(self.background),
+        };
         let rpd = RenderPassDescriptor {
             label: Some("Main Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view,
+                resolve_target,
+                ops: Operations {
+                    load,
+                    // Only the resolved samples are of interest, unless later passes resolve the
+                    // multisampled target again.
+                    store: resolve_target.is_none() || scissor.is_some(),
+                },
+            })],
+            depth_stencil_attachment: None,
+        };
+
+        let mut render_pass = encoder.begin_render_pass(&rpd);
+        if let Some([x, y, width, height]) = viewport {
+            render_pass.set_viewport(x, y, width, height, 0., 1.);
+        }
+        if let Some([x, y, width, height]) = scissor {
+            render_pass.set_scissor_rect(x, y, width, height);
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        self.draw_plane(&mut render_pass);
+    }
+
+    /// Writes the escape values of the fractal into `output`, which must be of
+    /// [`HISTOGRAM_FORMAT`]. Buffers must have been updated with
+    /// [`Self::update_histogram_buffers`].
+    pub fn draw_histogram_to(&self, output: &TextureView, encoder: &mut CommandEncoder) {
+        let rpd = RenderPassDescriptor {
+            label: Some("Histogram Render Pass"),
             color_attachments: &[Some(RenderPassColorAttachment {
                 view: output,
                 resolve_target: None,
                 ops: Operations {
-                    load: wgpu::LoadOp::Clear(Color {
-                        r: 0.3,
-                        g: 0.2,
-                        b: 0.7,
-                        a: 1.0,
-                    }),
+                    load: wgpu::LoadOp::Clear(Color::TRANSPARENT),
                     store: true,
                 },
             })],
             depth_stencil_attachment: None,
         };
-
         let mut render_pass = encoder.begin_render_pass(&rpd);
-        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_pipeline(&self.histogram_pipeline);
+        self.draw_plane(&mut render_pass);
+    }
+
+    /// Inverse view matrix written by the last call to `update_buffers`, e.g. to draw lines in the
+    /// complex plane on top of the fractal.
+    pub fn inv_view_bind_group(&self) -> &BindGroup {
+        &self.inv_view_bind_group
+    }
+
+    /// Layout of [`Self::inv_view_bind_group`].
+    pub fn inv_view_layout(&self) -> &BindGroupLayout {
+        &self.inv_view_layout
+    }
+
+    /// Binds the shader arguments and draws the plane spanning the entire target.
+    fn draw_plane<'a>(&'a self, render_pass: &mut RenderPass<'a>) {
         render_pass.set_bind_group(0, &self.inv_view_bind_group, &[]);
-        render_pass.set_bind_group(1, &self.iter_bind_group, &[]);
+        render_pass.set_bind_group(1, &self.fragment_bind_group, &[]);
+        render_pass.set_bind_group(2, &self.fractal_bind_group, &[]);
+        render_pass.set_bind_group(3, &self.palette_bind_group, &[]);
         render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
         render_pass.draw(0..(VERTICES.len() as u32), 0..1);
     }
 }
 
-/// Rectangle vertex strip spanning the entire surface
+/// sRGB formats encode colors with the gamma of a standard monitor in hardware. For all other
+/// formats the shader must do it.
+fn default_gamma(format: TextureFormat) -> f32 {
+    if format.describe().srgb {
+        1.0
+    } else {
+        2.2
+    }
+}
+
+fn create_render_pipeline(
+    device: &Device,
+    layout: &PipelineLayout,
+    shader: &ShaderModule,
+    target_format: TextureFormat,
+    sample_count: u32,
+    cull_mode: Option<Face>,
+) -> RenderPipeline {
+    device.create_render_pipeline(&RenderPipelineDescriptor {
+        label: Some("Canvas Render Pipeline"),
+        layout: Some(layout),
+        vertex: VertexState {
+            module: shader,
+            entry_point: "vs_main",
+            buffers: &[Vertex::DESC],
+        },
+        fragment: Some(FragmentState {
+            module: shader,
+            entry_point: "fs_main",
+            targets: &[Some(ColorTargetState {
+                format: target_format,
+                blend: Some(BlendState::REPLACE),
+                write_mask: ColorWrites::ALL,
+            })],
+        }),
+        primitive: PrimitiveState {
+            topology: PrimitiveTopology::TriangleList,
+            strip_index_format: None,
+            front_face: FRONT_FACE,
+            cull_mode,
+            // Requires Features::DEPTH_CLIP_CONTROL
+            unclipped_depth: false,
+            // Setting this to anything other than Fill requires Features::NON_FILL_POLYGON_MODE
+            polygon_mode: wgpu::PolygonMode::Fill,
+            // Requires Features::CONSERVATIVE_RASTERIZATION
+            conservative: false,
+        },
+        depth_stencil: None,
+        multiview: None,
+        multisample: MultisampleState {
+            count: sample_count,
+            mask: !0,
+            alpha_to_coverage_enabled: false,
+        },
+    })
+}
+
+/// Winding order of the front face of `VERTICES`.
+const FRONT_FACE: FrontFace = FrontFace::Ccw;
+
+/// Single triangle covering the entire surface. Its corners beyond the surface are clipped. Unlike
+/// two triangles forming a rectangle, it has no diagonal edge along which pixels are shaded twice.
+/// Offsets in the complex plane are interpolated linearly, so they are the same as for a rectangle.
 const VERTICES: &[Vertex] = &[
-    Vertex {
-        position: [-1.0, 1.0],
-    },
     Vertex {
         position: [-1.0, -1.0],
     },
     Vertex {
-        position: [1.0, 1.0],
+        position: [3.0, -1.0],
     },
     Vertex {
-        position: [1.0, -1.0],
+        position: [-1.0, 3.0],
     },
 ];
+
+#[cfg(test)]
+mod tests {
+    use wgpu::FrontFace;
+
+    use super::{FRONT_FACE, VERTICES};
+
+    #[test]
+    fn triangle_faces_the_viewer() {
+        // Twice the signed area of the triangle is positive, if its corners are ordered counter
+        // clockwise.
+        let [a, b, c] = [0, 1, 2].map(|index| VERTICES[index].position);
+        let doubled_area = (b[0] - a[0]) * (c[1] - a[1]) - (c[0] - a[0]) * (b[1] - a[1]);
+
+        assert_eq!(FrontFace::Ccw, FRONT_FACE);
+        assert!(doubled_area > 0.);
+    }
+}
@@ -0,0 +1,152 @@
+use std::{num::NonZeroU32, sync::mpsc};
+
+use wgpu::{
+    Buffer, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoder, Device, Extent3d,
+    ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, Maintain, MapMode, Origin3d, Queue,
+    Texture, TextureAspect, TextureDescriptor, TextureDimension, TextureFormat, TextureUsages,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+/// Creates a texture we can render into and copy the result from, so it can be read back by the
+/// CPU.
+pub fn offscreen_texture(
+    device: &Device,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> Texture {
+    device.create_texture(&TextureDescriptor {
+        label: Some("Offscreen Render Target"),
+        size: Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format,
+        usage: TextureUsages::RENDER_ATTACHMENT | TextureUsages::COPY_SRC,
+        view_formats: &[],
+    })
+}
+
+/// Copies the contents of `texture` to the CPU and returns them as tightly packed RGBA8 bytes,
+/// row by row starting at the top left.
+///
+/// `encoder` should contain the commands rendering to `texture`. It is submitted together with the
+/// copy. This function blocks until the GPU has finished the work.
+pub fn read_rgba(
+    device: &Device,
+    queue: &Queue,
+    mut encoder: CommandEncoder,
+    texture: &Texture,
+    width: u32,
+    height: u32,
+    format: TextureFormat,
+) -> Result<Vec<u8>, BufferAsyncError> {
+    const BYTES_PER_PIXEL: u32 = 4;
+    let unpadded_bytes_per_row = width * BYTES_PER_PIXEL;
+    // wgpu requires each row in the destination buffer to start at a multitude of 256 bytes.
+    let padded_bytes_per_row = unpadded_bytes_per_row.div_ceil(COPY_BYTES_PER_ROW_ALIGNMENT)
+        * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size: u64::from(padded_bytes_per_row * height),
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_texture_to_buffer(
+        ImageCopyTexture {
+            texture,
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        ImageCopyBuffer {
+            buffer: &buffer,
+            layout: ImageDataLayout {
+                offset: 0,
+                bytes_per_row: NonZeroU32::new(padded_bytes_per_row),
+                rows_per_image: None,
+            },
+        },
+        Extent3d {
+            width,
+            height,
+            depth_or_array_layers: 1,
+        },
+    );
+    queue.submit(Some(encoder.finish()));
+
+    let slice = buffer.slice(..);
+    map_read(device, &buffer)?;
+
+    let mut pixels = Vec::with_capacity((unpadded_bytes_per_row * height) as usize);
+    {
+        let padded = slice.get_mapped_range();
+        for row in padded.chunks_exact(padded_bytes_per_row as usize) {
+            pixels.extend_from_slice(&row[..unpadded_bytes_per_row as usize]);
+        }
+    }
+    buffer.unmap();
+
+    // Surfaces often prefer BGRA. Swap red and blue channels, so callers always get RGBA.
+    if matches!(
+        format,
+        TextureFormat::Bgra8Unorm | TextureFormat::Bgra8UnormSrgb
+    ) {
+        for pixel in pixels.chunks_exact_mut(BYTES_PER_PIXEL as usize) {
+            pixel.swap(0, 2);
+        }
+    }
+    Ok(pixels)
+}
+
+/// Copies `len` values of type `f32` from `source`, starting `offset` bytes into it, to the CPU.
+///
+/// `encoder` should contain the commands writing to `source`. It is submitted together with the
+/// copy. This function blocks until the GPU has finished the work.
+pub fn read_f32(
+    device: &Device,
+    queue: &Queue,
+    mut encoder: CommandEncoder,
+    source: &Buffer,
+    offset: u64,
+    len: usize,
+) -> Result<Vec<f32>, BufferAsyncError> {
+    const BYTES_PER_VALUE: usize = std::mem::size_of::<f32>();
+    let size = (len * BYTES_PER_VALUE) as u64;
+    let buffer = device.create_buffer(&BufferDescriptor {
+        label: Some("Readback Buffer"),
+        size,
+        usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+        mapped_at_creation: false,
+    });
+    encoder.copy_buffer_to_buffer(source, offset, &buffer, 0, size);
+    queue.submit(Some(encoder.finish()));
+
+    map_read(device, &buffer)?;
+    let values = buffer
+        .slice(..)
+        .get_mapped_range()
+        .chunks_exact(BYTES_PER_VALUE)
+        .map(|bytes| f32::from_ne_bytes(bytes.try_into().unwrap()))
+        .collect();
+    buffer.unmap();
+    Ok(values)
+}
+
+/// Maps `buffer` for reading and blocks until its contents are available to the CPU.
+fn map_read(device: &Device, buffer: &Buffer) -> Result<(), BufferAsyncError> {
+    let (sender, receiver) = mpsc::channel();
+    buffer.slice(..).map_async(MapMode::Read, move |result| {
+        // Receiver is still waiting below, so sending can not fail.
+        sender.send(result).unwrap()
+    });
+    device.poll(Maintain::Wait);
+    receiver
+        .recv()
+        .expect("Device must invoke map callback after waiting for it.")
+}
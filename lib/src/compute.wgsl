@@ -0,0 +1,25 @@
+/// Compute entry point of the canvas shader. This source is appended to the canvas shader, whose
+/// bindings and functions it shares, so both entry points produce the same picture.
+
+/// Colors of the pixels, which are blitted to the output afterwards. Shares its group with the
+/// palette, since devices may not support more than four bind groups.
+@group(3) @binding(2)
+var OUTPUT: texture_storage_2d<rgba16float, write>;
+
+@compute @workgroup_size(8, 8)
+fn cs_main(@builtin(global_invocation_id) id: vec3<u32>) {
+    let size = vec2<f32>(textureDimensions(OUTPUT));
+    let pixel = vec2<f32>(id.xy);
+    // The output is covered by whole workgroups, which may reach beyond its edges.
+    if (pixel.x >= size.x || pixel.y >= size.y) {
+        return;
+    }
+    // Same offset the vertex shader interpolates for the center of this pixel. Rows are counted
+    // from the top, while clip space points up.
+    let ndc = vec2<f32>((pixel.x + 0.5) / size.x * 2.0 - 1.0, 1.0 - (pixel.y + 0.5) / size.y * 2.0);
+    let offset = (VERTEX_ARGS.inv_view * vec4<f32>(ndc, 0.0, 0.0)).xy;
+    // Derivatives are not available in compute shaders, but offsets change linearly.
+    let pixel_dx = (VERTEX_ARGS.inv_view * vec4<f32>(2.0 / size.x, 0.0, 0.0, 0.0)).xy;
+    let pixel_dy = (VERTEX_ARGS.inv_view * vec4<f32>(0.0, -2.0 / size.y, 0.0, 0.0)).xy;
+    textureStore(OUTPUT, vec2<i32>(id.xy), pixel_color(offset, pixel_dx, pixel_dy));
+}
@@ -0,0 +1,337 @@
+use wgpu::{
+    BindGroup, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Buffer, BufferBindingType,
+    BufferDescriptor, BufferUsages, CommandEncoder, ComputePassDescriptor,
+    ComputePipelineDescriptor, Device, ErrorFilter, PipelineLayoutDescriptor, ShaderModule,
+    ShaderModuleDescriptor, ShaderSource, ShaderStages, StorageTextureAccess, TextureFormat,
+    TextureViewDimension,
+};
+
+use crate::{
+    blit_pipeline::{BlitPipeline, Intermediate},
+    CanvasError,
+};
+
+/// Compute entry point, appended to the source of the canvas shader.
+const COMPUTE_SHADER_SOURCE: &str = include_str!("compute.wgsl");
+
+/// Entry point computing the number of iterations per pixel, appended to the source of the canvas
+/// shader.
+const ITERATIONS_SHADER_SOURCE: &str = include_str!("iterations.wgsl");
+
+/// Bytes preceding the values in the buffer of an [`IterationsTarget`]. They hold its width and
+/// height.
+pub const ITERATIONS_HEADER_SIZE: u64 = 8;
+
+/// Format of the storage texture the compute shader writes into. Keeps the full precision of the
+/// colors until they are blitted to the output.
+pub const COMPUTE_FORMAT: TextureFormat = TextureFormat::Rgba16Float;
+
+/// Edge length of the square workgroups. Must match `@workgroup_size` in the compute shader.
+const WORKGROUP_SIZE: u32 = 8;
+
+/// Alternative to drawing the fractal with the fragment shader. The compute shader writes the color
+/// of each pixel into a storage texture, which is blitted to the output afterwards. Shares its
+/// uniform buffers with the canvas render pipeline, so both draw the same picture.
+///
+/// A second entry point writes the number of iterations of each pixel into a storage buffer
+/// instead, see [`Self::dispatch_iterations`].
+pub struct ComputePipeline {
+    pipeline: wgpu::ComputePipeline,
+    /// Computes the smooth iteration count of each pixel, rather than its color.
+    iterations_pipeline: wgpu::ComputePipeline,
+    /// Bind groups of the canvas shader preceding the output group, visible to the compute stage.
+    uniforms: Vec<BindGroup>,
+    /// Layout of the last bind group of the canvas shader, extended by the storage texture. Devices
+    /// may not support more than four bind groups, so the output can not get a group of its own.
+    output_layout: BindGroupLayout,
+    /// Like `output_layout`, but extended by the storage buffer the iterations are written to.
+    iterations_layout: BindGroupLayout,
+}
+
+impl ComputePipeline {
+    /// * `device` is used to create the pipeline and compile the shader.
+    /// * `canvas_source` is the WGSL source of the canvas shader the compute entry point is
+    ///   appended to.
+    /// * `uniforms` are the layouts of the bind groups of the canvas shader, in order, together
+    ///   with the buffers bound to them. The last one is bound per target, see [`Self::target`].
+    ///
+    /// Fails with [`CanvasError::ComputePipeline`] if the device does not support compute shaders,
+    /// or the canvas shader does not provide the functions the compute entry points rely on.
+    pub async fn new(
+        device: &Device,
+        canvas_source: &str,
+        uniforms: &[(&BindGroupLayoutDescriptor<'_>, &[&Buffer])],
+    ) -> Result<Self, CanvasError> {
+        let ((output_layout, _), uniforms) = uniforms
+            .split_last()
+            .expect("Canvas shader must bind at least one group");
+        device.push_error_scope(ErrorFilter::Validation);
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Canvas Compute Shader"),
+            source: ShaderSource::Wgsl(format!("{canvas_source}\n{COMPUTE_SHADER_SOURCE}").into()),
+        });
+        let iterations_shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Canvas Iterations Shader"),
+            source: ShaderSource::Wgsl(
+                format!("{canvas_source}\n{ITERATIONS_SHADER_SOURCE}").into(),
+            ),
+        });
+        let (uniform_layouts, uniforms): (Vec<_>, Vec<_>) = uniforms
+            .iter()
+            .map(|(layout, buffers)| {
+                let layout = compute_layout(device, layout, &[]);
+                let bind_group = bind_buffers(device, &layout, buffers, &[]);
+                (layout, bind_group)
+            })
+            .unzip();
+        let iterations_layout = compute_layout(
+            device,
+            output_layout,
+            &[BindingType::Buffer {
+                ty: BufferBindingType::Storage { read_only: false },
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            }],
+        );
+        let output_layout = compute_layout(
+            device,
+            output_layout,
+            &[BindingType::StorageTexture {
+                access: StorageTextureAccess::WriteOnly,
+                format: COMPUTE_FORMAT,
+                view_dimension: TextureViewDimension::D2,
+            }],
+        );
+        let pipeline =
+            create_pipeline(device, &uniform_layouts, &output_layout, &shader, "cs_main");
+        let iterations_pipeline = create_pipeline(
+            device,
+            &uniform_layouts,
+            &iterations_layout,
+            &iterations_shader,
+            "cs_iterations",
+        );
+        if let Some(error) = device.pop_error_scope().await {
+            return Err(CanvasError::ComputePipeline(error.to_string()));
+        }
+        Ok(ComputePipeline {
+            pipeline,
+            iterations_pipeline,
+            uniforms,
+            output_layout,
+            iterations_layout,
+        })
+    }
+
+    /// Creates a storage texture of `width` times `height` pixels the compute shader can write
+    /// into, and `blit` can copy to the output. `buffers` are bound in the last group of the canvas
+    /// shader, alongside the texture.
+    pub fn target(
+        &self,
+        device: &Device,
+        blit: &BlitPipeline,
+        width: u32,
+        height: u32,
+        buffers: &[&Buffer],
+    ) -> ComputeTarget {
+        let intermediate = blit.storage_intermediate(device, width, height, COMPUTE_FORMAT);
+        let bind_group = bind_buffers(
+            device,
+            &self.output_layout,
+            buffers,
+            &[BindingResource::TextureView(&intermediate.view)],
+        );
+        ComputeTarget {
+            intermediate,
+            bind_group,
+        }
+    }
+
+    /// Records computing the color of every pixel of `target`, with the arguments last written to
+    /// the uniform buffers.
+    pub fn dispatch(&self, target: &ComputeTarget, encoder: &mut CommandEncoder) {
+        let Intermediate { width, height, .. } = target.intermediate;
+        self.encode_pass(&self.pipeline, &target.bind_group, width, height, encoder);
+    }
+
+    /// Creates a storage buffer the smooth iteration counts of `width` times `height` pixels are
+    /// written into. `buffers` are bound in the last group of the canvas shader, alongside it.
+    /// Fails if the device can not bind a storage buffer this large.
+    pub fn iterations_target(
+        &self,
+        device: &Device,
+        width: u32,
+        height: u32,
+        buffers: &[&Buffer],
+    ) -> Result<IterationsTarget, CanvasError> {
+        let values = u64::from(width) * u64::from(height);
+        let size = ITERATIONS_HEADER_SIZE + values * std::mem::size_of::<f32>() as u64;
+        let max_size = u64::from(device.limits().max_storage_buffer_binding_size);
+        if size > max_size {
+            return Err(CanvasError::BufferTooLarge { size, max_size });
+        }
+        let buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Iterations Buffer"),
+            size,
+            usage: BufferUsages::STORAGE | BufferUsages::COPY_SRC,
+            mapped_at_creation: true,
+        });
+        // The shader learns the size of the picture from the header.
+        buffer
+            .slice(..ITERATIONS_HEADER_SIZE)
+            .get_mapped_range_mut()
+            .copy_from_slice(bytemuck::cast_slice(&[width, height]));
+        buffer.unmap();
+        let bind_group = bind_buffers(
+            device,
+            &self.iterations_layout,
+            buffers,
+            &[buffer.as_entire_binding()],
+        );
+        Ok(IterationsTarget {
+            buffer,
+            width,
+            height,
+            bind_group,
+        })
+    }
+
+    /// Records computing the smooth iteration count of every pixel of `target`, with the arguments
+    /// last written to the uniform buffers.
+    pub fn dispatch_iterations(&self, target: &IterationsTarget, encoder: &mut CommandEncoder) {
+        self.encode_pass(
+            &self.iterations_pipeline,
+            &target.bind_group,
+            target.width,
+            target.height,
+            encoder,
+        );
+    }
+
+    /// Records running `pipeline` once per pixel of an output with `width` times `height` pixels,
+    /// which is bound together with the last group of the canvas shader by `output`.
+    fn encode_pass(
+        &self,
+        pipeline: &wgpu::ComputePipeline,
+        output: &BindGroup,
+        width: u32,
+        height: u32,
+        encoder: &mut CommandEncoder,
+    ) {
+        let mut compute_pass = encoder.begin_compute_pass(&ComputePassDescriptor {
+            label: Some("Canvas Compute Pass"),
+        });
+        compute_pass.set_pipeline(pipeline);
+        for (index, bind_group) in self.uniforms.iter().enumerate() {
+            compute_pass.set_bind_group(index as u32, bind_group, &[]);
+        }
+        compute_pass.set_bind_group(self.uniforms.len() as u32, output, &[]);
+        compute_pass.dispatch_workgroups(
+            width.div_ceil(WORKGROUP_SIZE),
+            height.div_ceil(WORKGROUP_SIZE),
+            1,
+        );
+    }
+}
+
+/// Storage texture the compute shader writes into, before it is blitted to the output.
+pub struct ComputeTarget {
+    /// Storage texture, which can also be copied to the output by the blit pipeline.
+    pub intermediate: Intermediate,
+    /// Binds the texture as output of the compute shader, together with the buffers of the last
+    /// group of the canvas shader.
+    bind_group: BindGroup,
+}
+
+/// Storage buffer the compute shader writes the smooth iteration counts of the pixels into. The
+/// values follow a header of [`ITERATIONS_HEADER_SIZE`] bytes, row by row starting at the top left.
+pub struct IterationsTarget {
+    /// Holds the header, followed by one `f32` per pixel.
+    pub buffer: Buffer,
+    /// Number of pixels per row.
+    pub width: u32,
+    /// Number of rows.
+    pub height: u32,
+    /// Binds the buffer as output of the compute shader, together with the buffers of the last
+    /// group of the canvas shader.
+    bind_group: BindGroup,
+}
+
+/// Compute pipeline running `entry_point` of `module`, with the bind groups of the canvas shader
+/// described by `uniforms`, followed by `output`.
+fn create_pipeline(
+    device: &Device,
+    uniforms: &[BindGroupLayout],
+    output: &BindGroupLayout,
+    module: &ShaderModule,
+    entry_point: &str,
+) -> wgpu::ComputePipeline {
+    let bind_group_layouts: Vec<_> = uniforms.iter().chain(Some(output)).collect();
+    let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+        label: Some("Compute Pipeline Layout"),
+        bind_group_layouts: &bind_group_layouts,
+        push_constant_ranges: &[],
+    });
+    device.create_compute_pipeline(&ComputePipelineDescriptor {
+        label: Some("Canvas Compute Pipeline"),
+        layout: Some(&layout),
+        module,
+        entry_point,
+    })
+}
+
+/// Layout of the canvas shader `layout`, but visible to the compute stage. `extra` bindings are
+/// appended after its entries.
+fn compute_layout(
+    device: &Device,
+    layout: &BindGroupLayoutDescriptor,
+    extra: &[BindingType],
+) -> BindGroupLayout {
+    let extra = extra
+        .iter()
+        .enumerate()
+        .map(|(index, &ty)| BindGroupLayoutEntry {
+            binding: (layout.entries.len() + index) as u32,
+            visibility: ShaderStages::COMPUTE,
+            ty,
+            count: None,
+        });
+    let entries: Vec<_> = layout
+        .entries
+        .iter()
+        .map(|entry| BindGroupLayoutEntry {
+            visibility: ShaderStages::COMPUTE,
+            ..*entry
+        })
+        .chain(extra)
+        .collect();
+    device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+        label: layout.label,
+        entries: &entries,
+    })
+}
+
+/// Binds `buffers` followed by `extra` resources in order of their binding index.
+fn bind_buffers(
+    device: &Device,
+    layout: &BindGroupLayout,
+    buffers: &[&Buffer],
+    extra: &[BindingResource],
+) -> BindGroup {
+    let entries: Vec<_> = buffers
+        .iter()
+        .map(|buffer| buffer.as_entire_binding())
+        .chain(extra.iter().cloned())
+        .enumerate()
+        .map(|(binding, resource)| BindGroupEntry {
+            binding: binding as u32,
+            resource,
+        })
+        .collect();
+    device.create_bind_group(&BindGroupDescriptor {
+        label: Some("Compute Bind Group"),
+        layout,
+        entries: &entries,
+    })
+}
@@ -0,0 +1,1556 @@
+use std::{collections::HashMap, time::Duration};
+
+// `std::time::Instant` panics in the browser, `instant` falls back to `performance.now()` there.
+use instant::Instant;
+use log::info;
+use winit::{
+    dpi::PhysicalPosition,
+    event::{
+        ElementState, KeyboardInput, MouseButton, MouseScrollDelta, Touch, TouchPhase,
+        VirtualKeyCode,
+    },
+};
+
+use crate::{
+    Camera, ColoringMode, FitMode, FractalKind, InteriorMode, JuliaInset, KeyBindings, Loupe,
+    PRESET_PALETTES,
+};
+
+/// Factor by which auto zoom magnifies the picture each second.
+const AUTO_ZOOM_RATE: f32 = 1.5;
+/// Factor by which scrolling the mouse wheel by one line magnifies the picture.
+const WHEEL_ZOOM_PER_LINE: f32 = 1.2;
+/// Touchpads report scrolling in pixels. This many pixels are treated like one line.
+const PIXELS_PER_LINE: f32 = 50.;
+
+/// Two clicks within this time count as a double click.
+const DOUBLE_CLICK_TIME: Duration = Duration::from_millis(400);
+/// Two clicks at most this many physical pixels apart count as a double click.
+const DOUBLE_CLICK_DISTANCE: f64 = 5.;
+/// Factor by which double clicking magnifies the picture.
+const DOUBLE_CLICK_ZOOM: f32 = 2.;
+
+/// Number of iterations the view is reset to.
+const DEFAULT_ITERATIONS: f32 = 256.;
+
+/// Number of times per second colors cycle through the entire palette, while color cycling is
+/// enabled.
+const COLOR_CYCLE_SPEED: f32 = 0.1;
+
+/// Time in seconds it takes the velocity of the camera to cover roughly two thirds of the way
+/// towards the one given by the keys held down, if inertia is enabled. Determines how long the
+/// camera coasts after releasing a key.
+const INERTIA_TIME_CONSTANT: f32 = 0.15;
+/// Coasting camera stops once neither its velocity nor its zoom velocity exceed this.
+const MIN_COASTING_SPEED: f32 = 0.01;
+
+/// Supersampling factors the user can cycle through.
+const SUPERSAMPLING: [u32; 3] = [1, 2, 4];
+/// Largest supersampling factor the user can step up to. Computes 64 samples per pixel.
+const MAX_SUPERSAMPLING: u32 = 8;
+
+/// Keep track of which buttons are pressed and decide how much the camera should move from one
+/// frame to the next.
+pub struct Controls {
+    // Since then is the picture currently displayed in the canvas outdated? We use this variable to
+    // check how much we adapt the camera positions between frames. If the picture is currently
+    // unchanging we set this to `None`.
+    outdated_since: Option<Instant>,
+    /// Keys triggering each action.
+    bindings: KeyBindings,
+    up: HeldKeys,
+    down: HeldKeys,
+    left: HeldKeys,
+    right: HeldKeys,
+    zoom_in: bool,
+    zoom_out: bool,
+    inc_iter: bool,
+    dec_iter: bool,
+    /// Number of iterations grows with the zoom level, rather than being fixed.
+    auto_iterations: bool,
+    /// Shift is held down, so the camera moves and zooms faster.
+    fast: bool,
+    /// Ctrl is held down, so the number keys store the current view rather than jumping.
+    ctrl: bool,
+    /// Factor by which panning and zooming speed up while Shift is held down.
+    pub fast_factor: f32,
+    /// If `true`, the camera speeds up smoothly after pressing a key and coasts to a stop after
+    /// releasing it, rather than starting and stopping abruptly.
+    pub inertia: bool,
+    /// Velocity of the camera along the real and imaginary axis, as passed to
+    /// [`Camera::change_pos`] per second. Only used if `inertia` is enabled.
+    velocity: [f32; 2],
+    /// Growth of the natural logarithm of the zoom level per second. Only used if `inertia` is
+    /// enabled.
+    zoom_velocity: f32,
+    /// Growth of the natural logarithm of the number of iterations per second, while `M` or `N` is
+    /// held down. E.g. `0.5` multiplies the iterations by `e` every two seconds.
+    pub iteration_speed: f32,
+    /// `M` and `N` keep the natural logarithm of the number of iterations at or above this bound.
+    /// `0` stops at a single iteration.
+    pub min_ln_iterations: f32,
+    /// `M` and `N` keep the natural logarithm of the number of iterations at or below this bound.
+    /// `10` stops at roughly 22000 iterations.
+    pub max_ln_iterations: f32,
+    /// Last known position of the cursor in physical pixels. `None` if it has not entered the
+    /// window yet.
+    cursor: Option<PhysicalPosition<f64>>,
+    /// Left mouse button is held down, so moving the cursor pans the camera.
+    dragging: bool,
+    /// Time and position of the last click with the left mouse button, to detect double clicks.
+    last_click: Option<(Instant, PhysicalPosition<f64>)>,
+    /// Position of a double click, which has not been applied to the camera yet.
+    double_click: Option<PhysicalPosition<f64>>,
+    /// Distance in physical pixels the cursor has been dragged since the last call to
+    /// `update_scene`.
+    drag_delta: [f64; 2],
+    /// Last known positions in physical pixels of the fingers touching the screen, by touch id.
+    touches: HashMap<u64, PhysicalPosition<f64>>,
+    /// Factor by which the distance between two fingers grew since the last call to
+    /// `update_scene`. The picture is magnified by the same factor.
+    pinch_factor: f32,
+    /// Midpoint between the two fingers of the last pinch, the picture is magnified around.
+    pinch_center: Option<PhysicalPosition<f64>>,
+    /// Show a magnifying glass around the cursor.
+    loupe: bool,
+    /// Julia inset key is held down, so the Julia set for the point under the cursor is shown.
+    julia_inset: bool,
+    /// Show zoom level, iterations and coordinates on top of the fractal.
+    hud: bool,
+    /// Show axes and unit gridlines of the complex plane on top of the fractal.
+    grid: bool,
+    /// Lines the mouse wheel has been scrolled since the last call to `update_scene`. Positive
+    /// values zoom in.
+    wheel_lines: f32,
+    /// Point in the coordinate system of the fractal we continuously zoom towards. `None` if auto
+    /// zoom is disabled.
+    auto_zoom: Option<[f64; 2]>,
+    /// Auto zoom key has been pressed since the last call to `update_scene`.
+    toggle_auto_zoom: bool,
+    /// Position of a click while auto zoom is active. It becomes the new target of the zoom in the
+    /// next call to `update_scene`.
+    auto_zoom_click: Option<PhysicalPosition<f64>>,
+    /// Julia key has been pressed since the last call to `update_scene`.
+    toggle_julia: bool,
+    /// Burning Ship key has been pressed since the last call to `update_scene`.
+    toggle_burning_ship: bool,
+    /// Tricorn key has been pressed since the last call to `update_scene`.
+    toggle_tricorn: bool,
+    /// Newton key has been pressed since the last call to `update_scene`.
+    toggle_newton: bool,
+    /// Key cycling through all kinds of fractals has been pressed since the last call to
+    /// `update_scene`.
+    cycle_kind: bool,
+    /// Constant used for the Julia set, then switching to it.
+    pub julia_c: [f32; 2],
+    /// Share key has been pressed, but the scene has not been shared yet.
+    share_requested: bool,
+    /// Still key has been pressed, but the high resolution still has not been rendered yet.
+    still_requested: bool,
+    /// Screenshot key has been pressed, but the screenshot has not been taken yet.
+    screenshot_requested: bool,
+    /// Index into `PRESET_PALETTES` of the palette currently in use.
+    palette: usize,
+    /// Palette key has been pressed, but the new palette has not been applied yet.
+    palette_changed: bool,
+    /// Number of samples per pixel along each axis, between 1 and `MAX_SUPERSAMPLING`.
+    supersampling: u32,
+    /// Supersampling key has been pressed, but the new factor has not been applied yet.
+    supersampling_changed: bool,
+    /// Colors cycle through the palette over time.
+    color_cycling: bool,
+    /// Color cycling key has been pressed, but the new speed has not been applied yet.
+    color_cycling_changed: bool,
+    /// How the color of a pixel is derived from its sequence.
+    coloring: ColoringMode,
+    /// Coloring key has been pressed, but the new coloring mode has not been applied yet.
+    coloring_changed: bool,
+    /// How points within the set are colored.
+    interior: InteriorMode,
+    /// Interior key has been pressed, but the new interior mode has not been applied yet.
+    interior_changed: bool,
+    /// Invert the colors of the picture.
+    inverted: bool,
+    /// Inversion key has been pressed, but the change has not been applied yet.
+    inversion_changed: bool,
+    /// Spread the colors of the palette evenly over the picture.
+    equalization: bool,
+    /// Equalization key has been pressed, but the change has not been applied yet.
+    equalization_changed: bool,
+    /// How the picture is fitted into a canvas which is not square.
+    fit_mode: FitMode,
+    /// Fit mode key has been pressed, but the new fit mode has not been applied yet.
+    fit_mode_changed: bool,
+    /// Colors blend continuously between iteration counts, rather than forming bands.
+    smooth_coloring: bool,
+    /// Smooth coloring key has been pressed, but the change has not been applied yet.
+    smooth_coloring_changed: bool,
+    /// Draw the fractal at reduced resolution while it is in motion.
+    dynamic_resolution: bool,
+    /// Dynamic resolution key has been pressed, but the change has not been applied yet.
+    dynamic_resolution_changed: bool,
+    /// Fullscreen key has been pressed, but the window has not been toggled yet.
+    fullscreen_requested: bool,
+    /// Save location key has been pressed, but the location has not been saved yet.
+    save_location_requested: bool,
+    /// Load location key has been pressed, but the location has not been loaded yet.
+    load_location_requested: bool,
+    /// Reset key has been pressed since the last call to `update_scene`.
+    reset_view: bool,
+    /// Index of the bookmark selected with the number keys, which has not been jumped to yet.
+    bookmark_requested: Option<usize>,
+    /// Index of the bookmark selected with `Ctrl` and a number key, which the current view has not
+    /// been stored in yet.
+    store_bookmark_requested: Option<usize>,
+    /// Reload shader key has been pressed, but the shader has not been reloaded yet.
+    reload_shader_requested: bool,
+    /// Exit key has been pressed, but the application has not been closed yet.
+    exit_requested: bool,
+    /// Interaction is paused to save energy. Only the pause and exit keys take effect, and the
+    /// application should neither update nor render the scene.
+    paused: bool,
+    /// Set by events which change the picture once, rather than continuously. Consumed by the next
+    /// call to `update_scene`.
+    redraw_pending: bool,
+    /// `true` for the frame following an event which changed the picture once.
+    redraw: bool,
+}
+
+impl Controls {
+    /// Controls triggered by the default [`KeyBindings`].
+    pub fn new() -> Self {
+        Self::with_bindings(KeyBindings::default())
+    }
+
+    /// Controls triggered by the keys in `bindings`, e.g. to adapt them to another keyboard layout.
+    pub fn with_bindings(bindings: KeyBindings) -> Self {
+        Controls {
+            outdated_since: None,
+            bindings,
+            up: HeldKeys::default(),
+            down: HeldKeys::default(),
+            left: HeldKeys::default(),
+            right: HeldKeys::default(),
+            zoom_in: false,
+            zoom_out: false,
+            inc_iter: false,
+            dec_iter: false,
+            auto_iterations: false,
+            fast: false,
+            ctrl: false,
+            fast_factor: 4.,
+            inertia: false,
+            velocity: [0., 0.],
+            zoom_velocity: 0.,
+            iteration_speed: 0.5,
+            min_ln_iterations: 0.,
+            max_ln_iterations: 10.,
+            cursor: None,
+            dragging: false,
+            last_click: None,
+            double_click: None,
+            drag_delta: [0., 0.],
+            touches: HashMap::new(),
+            pinch_factor: 1.,
+            pinch_center: None,
+            loupe: false,
+            julia_inset: false,
+            hud: false,
+            grid: false,
+            wheel_lines: 0.,
+            auto_zoom: None,
+            toggle_auto_zoom: false,
+            auto_zoom_click: None,
+            toggle_julia: false,
+            toggle_burning_ship: false,
+            toggle_tricorn: false,
+            toggle_newton: false,
+            cycle_kind: false,
+            julia_c: [-0.8, 0.156],
+            share_requested: false,
+            still_requested: false,
+            screenshot_requested: false,
+            palette: 0,
+            palette_changed: false,
+            supersampling: 1,
+            supersampling_changed: false,
+            color_cycling: false,
+            color_cycling_changed: false,
+            coloring: ColoringMode::EscapeTime,
+            coloring_changed: false,
+            interior: InteriorMode::Black,
+            interior_changed: false,
+            inverted: false,
+            inversion_changed: false,
+            equalization: false,
+            equalization_changed: false,
+            fit_mode: FitMode::Stretch,
+            fit_mode_changed: false,
+            smooth_coloring: true,
+            smooth_coloring_changed: false,
+            dynamic_resolution: false,
+            dynamic_resolution_changed: false,
+            fullscreen_requested: false,
+            save_location_requested: false,
+            load_location_requested: false,
+            reset_view: false,
+            bookmark_requested: None,
+            store_bookmark_requested: None,
+            reload_shader_requested: false,
+            exit_requested: false,
+            paused: false,
+            redraw_pending: false,
+            redraw: false,
+        }
+    }
+
+    pub fn track_button_presses(&mut self, input: KeyboardInput) {
+        let KeyboardInput {
+            scancode: _,
+            state,
+            virtual_keycode,
+            ..
+        } = input;
+        if let Some(keycode) = virtual_keycode {
+            let is_pressed = state == ElementState::Pressed;
+            // Actions are looked up in the bindings, rather than matching key codes, so users can
+            // remap them.
+            let bound = |keys: &[VirtualKeyCode]| keys.contains(&keycode);
+            let pressed = |keys: &[VirtualKeyCode]| is_pressed && keys.contains(&keycode);
+            let bindings = &self.bindings;
+            // Releases still count, so keys held while pausing do not stay down after resuming.
+            if self.paused && is_pressed && !bound(&bindings.pause) && !bound(&bindings.exit) {
+                return;
+            }
+            match keycode {
+                _ if bound(&bindings.left) => self.left.set(keycode, is_pressed),
+                _ if bound(&bindings.up) => self.up.set(keycode, is_pressed),
+                _ if bound(&bindings.right) => self.right.set(keycode, is_pressed),
+                _ if bound(&bindings.down) => self.down.set(keycode, is_pressed),
+                _ if bound(&bindings.zoom_in) => self.zoom_in = is_pressed,
+                _ if bound(&bindings.zoom_out) => self.zoom_out = is_pressed,
+                _ if bound(&bindings.more_iterations) => self.inc_iter = is_pressed,
+                _ if bound(&bindings.fewer_iterations) => self.dec_iter = is_pressed,
+                _ if bound(&bindings.fast) => self.fast = is_pressed,
+                _ if bound(&bindings.store_bookmark) => self.ctrl = is_pressed,
+                _ if bound(&bindings.julia_inset) => {
+                    // Only redraw if the inset appears or disappears, not for repeated presses.
+                    self.redraw_pending |= self.julia_inset != is_pressed;
+                    self.julia_inset = is_pressed;
+                }
+                _ if pressed(&bindings.auto_iterations) => {
+                    self.auto_iterations = !self.auto_iterations;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.auto_zoom) => {
+                    self.toggle_auto_zoom = !self.toggle_auto_zoom;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.julia) => {
+                    self.toggle_julia = !self.toggle_julia;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.burning_ship) => {
+                    self.toggle_burning_ship = !self.toggle_burning_ship;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.tricorn) => {
+                    self.toggle_tricorn = !self.toggle_tricorn;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.newton) => {
+                    self.toggle_newton = !self.toggle_newton;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.cycle_fractal) => {
+                    self.cycle_kind = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.screenshot) => self.screenshot_requested = true,
+                _ if pressed(&bindings.share) => self.share_requested = true,
+                _ if pressed(&bindings.still) => self.still_requested = true,
+                _ if pressed(&bindings.cycle_palette) => {
+                    self.palette = (self.palette + 1) % PRESET_PALETTES.len();
+                    self.palette_changed = true;
+                    self.redraw_pending = true;
+                }
+                // Select fire, ice, grayscale or the classic palette directly, rather than cycling
+                // through them.
+                _ if pressed(&bindings.fire_palette) => self.select_palette(3),
+                _ if pressed(&bindings.ice_palette) => self.select_palette(4),
+                _ if pressed(&bindings.grayscale_palette) => self.select_palette(1),
+                _ if pressed(&bindings.classic_palette) => self.select_palette(0),
+                _ if pressed(&bindings.invert_colors) => {
+                    self.inverted = !self.inverted;
+                    self.inversion_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.smooth_coloring) => {
+                    self.smooth_coloring = !self.smooth_coloring;
+                    self.smooth_coloring_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.dynamic_resolution) => {
+                    self.dynamic_resolution = !self.dynamic_resolution;
+                    self.dynamic_resolution_changed = true;
+                }
+                _ if pressed(&bindings.color_cycling) => {
+                    self.color_cycling = !self.color_cycling;
+                    self.color_cycling_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.cycle_supersampling) => {
+                    // Factors stepped to with the brackets continue with the next larger preset.
+                    self.supersampling = SUPERSAMPLING
+                        .into_iter()
+                        .find(|&factor| factor > self.supersampling)
+                        .unwrap_or(SUPERSAMPLING[0]);
+                    self.supersampling_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.more_supersampling) => self.step_supersampling(1),
+                _ if pressed(&bindings.less_supersampling) => self.step_supersampling(-1),
+                _ if pressed(&bindings.coloring) => {
+                    self.coloring = match self.coloring {
+                        ColoringMode::EscapeTime => ColoringMode::DistanceEstimation,
+                        ColoringMode::DistanceEstimation => ColoringMode::OrbitTrap,
+                        ColoringMode::OrbitTrap => ColoringMode::EscapeTime,
+                    };
+                    self.coloring_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.interior) => {
+                    self.interior = match self.interior {
+                        InteriorMode::Black => InteriorMode::FinalMagnitude,
+                        InteriorMode::FinalMagnitude => InteriorMode::OrbitAverage,
+                        InteriorMode::OrbitAverage => InteriorMode::Black,
+                    };
+                    self.interior_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.equalization) => {
+                    self.equalization = !self.equalization;
+                    self.equalization_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.reload_shader) => {
+                    self.reload_shader_requested = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.fit_mode) => {
+                    self.fit_mode = match self.fit_mode {
+                        FitMode::Stretch => FitMode::Contain,
+                        FitMode::Contain => FitMode::Stretch,
+                    };
+                    self.fit_mode_changed = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.pause) => {
+                    self.paused = !self.paused;
+                    if self.paused {
+                        // The key which paused is bound to resuming, too.
+                        info!("Paused. Press {keycode:?} to resume.");
+                        // Resuming measures time from the moment we resume, so the camera does
+                        // not jump.
+                        self.outdated_since = None;
+                    } else {
+                        // Discard pointer input accumulated while paused, rather than applying it
+                        // all at once.
+                        self.wheel_lines = 0.;
+                        self.drag_delta = [0., 0.];
+                        self.pinch_factor = 1.;
+                        self.double_click = None;
+                        self.auto_zoom_click = None;
+                        info!("Resumed.");
+                    }
+                }
+                _ if pressed(&bindings.exit) => self.exit_requested = true,
+                _ if pressed(&bindings.fullscreen) => self.fullscreen_requested = true,
+                _ if pressed(&bindings.save_location) => self.save_location_requested = true,
+                _ if pressed(&bindings.load_location) => {
+                    self.load_location_requested = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.reset_view) => {
+                    self.reset_view = true;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.bookmarks) => {
+                    // The first bookmark key selects the first bookmark, and so on.
+                    let index = bindings
+                        .bookmarks
+                        .iter()
+                        .position(|&key| key == keycode)
+                        .unwrap();
+                    if self.ctrl {
+                        self.store_bookmark_requested = Some(index);
+                    } else {
+                        self.bookmark_requested = Some(index);
+                        // Zooming on towards the old target, or coasting on, would lead us away
+                        // from the bookmark.
+                        self.auto_zoom = None;
+                        self.stop_coasting();
+                        self.redraw_pending = true;
+                    }
+                }
+                _ if pressed(&bindings.hud) => {
+                    self.hud = !self.hud;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.grid) => {
+                    self.grid = !self.grid;
+                    self.redraw_pending = true;
+                }
+                _ if pressed(&bindings.loupe) => {
+                    self.loupe = !self.loupe;
+                    self.redraw_pending = true;
+                }
+                _ => (),
+            }
+            if self.outdated_since.is_none() && self.picture_changes() {
+                self.outdated_since = Some(Instant::now())
+            }
+        };
+    }
+
+    /// Raises the supersampling factor by `step`, or lowers it if `step` is negative, within the
+    /// supported range.
+    fn step_supersampling(&mut self, step: i32) {
+        let factor = self
+            .supersampling
+            .saturating_add_signed(step)
+            .clamp(1, MAX_SUPERSAMPLING);
+        if factor != self.supersampling {
+            self.supersampling = factor;
+            self.supersampling_changed = true;
+            self.redraw_pending = true;
+        }
+        info!("Supersampling: {factor}x{factor} samples per pixel");
+    }
+
+    /// Forgets about all keys, mouse buttons and fingers held down, as if they had been released.
+    /// Call this if the window loses focus, since it does not receive the release events anymore.
+    /// Otherwise the camera would keep moving until the key is pressed again.
+    pub fn release_all(&mut self) {
+        for direction in [
+            &mut self.up,
+            &mut self.down,
+            &mut self.left,
+            &mut self.right,
+        ] {
+            *direction = HeldKeys::default();
+        }
+        self.zoom_in = false;
+        self.zoom_out = false;
+        self.inc_iter = false;
+        self.dec_iter = false;
+        self.fast = false;
+        self.ctrl = false;
+        self.dragging = false;
+        self.touches.clear();
+        self.stop_coasting();
+        if self.julia_inset {
+            self.julia_inset = false;
+            self.redraw_pending = true;
+        }
+    }
+
+    /// Remember the cursor position, e.g. to center the loupe around it or pick the Julia set of
+    /// the inset.
+    pub fn track_cursor(&mut self, position: PhysicalPosition<f64>) {
+        if let (true, Some(last)) = (self.dragging, self.cursor) {
+            self.drag_delta[0] += position.x - last.x;
+            self.drag_delta[1] += position.y - last.y;
+        }
+        self.cursor = Some(position);
+        if self.loupe || self.julia_inset {
+            self.redraw_pending = true;
+        }
+    }
+
+    /// The palette to switch to, if the user selected a different one since the last call.
+    pub fn take_palette_change(&mut self) -> Option<&'static [[f32; 3]]> {
+        std::mem::take(&mut self.palette_changed).then_some(PRESET_PALETTES[self.palette])
+    }
+
+    /// Index into [`PRESET_PALETTES`] of the palette selected by the user.
+    pub fn palette_index(&self) -> usize {
+        self.palette
+    }
+
+    /// Selects the palette at `index` in [`PRESET_PALETTES`], as if the user picked it, e.g. to
+    /// restore a shared scene. Indices past the end wrap around.
+    pub fn select_palette(&mut self, index: usize) {
+        self.palette = index % PRESET_PALETTES.len();
+        self.palette_changed = true;
+        self.redraw_pending = true;
+    }
+
+    /// The coloring mode selected by the user.
+    pub fn coloring(&self) -> ColoringMode {
+        self.coloring
+    }
+
+    /// Selects `coloring`, as if the user picked it, e.g. to restore a shared scene.
+    pub fn select_coloring(&mut self, coloring: ColoringMode) {
+        self.coloring = coloring;
+        self.coloring_changed = true;
+        self.redraw_pending = true;
+    }
+
+    /// The color cycle speed to switch to, if the user toggled color cycling since the last call.
+    pub fn take_color_cycle_change(&mut self) -> Option<f32> {
+        std::mem::take(&mut self.color_cycling_changed).then_some(if self.color_cycling {
+            COLOR_CYCLE_SPEED
+        } else {
+            0.
+        })
+    }
+
+    /// The supersampling factor to switch to, if the user selected a different one since the last
+    /// call.
+    pub fn take_supersampling_change(&mut self) -> Option<u32> {
+        std::mem::take(&mut self.supersampling_changed).then_some(self.supersampling)
+    }
+
+    /// The coloring mode to switch to, if the user selected a different one since the last call.
+    pub fn take_coloring_change(&mut self) -> Option<ColoringMode> {
+        std::mem::take(&mut self.coloring_changed).then_some(self.coloring)
+    }
+
+    /// The interior mode to switch to, if the user selected a different one since the last call.
+    pub fn take_interior_change(&mut self) -> Option<InteriorMode> {
+        std::mem::take(&mut self.interior_changed).then_some(self.interior)
+    }
+
+    /// Whether to invert the colors of the picture, if the user toggled it since the last call.
+    pub fn take_inversion_change(&mut self) -> Option<bool> {
+        std::mem::take(&mut self.inversion_changed).then_some(self.inverted)
+    }
+
+    /// Whether to enable histogram equalization, if the user toggled it since the last call.
+    pub fn take_equalization_change(&mut self) -> Option<bool> {
+        std::mem::take(&mut self.equalization_changed).then_some(self.equalization)
+    }
+
+    /// The fit mode to switch to, if the user toggled it since the last call.
+    pub fn take_fit_mode_change(&mut self) -> Option<FitMode> {
+        std::mem::take(&mut self.fit_mode_changed).then_some(self.fit_mode)
+    }
+
+    /// Whether colors blend continuously between iteration counts, if the user switched between
+    /// smooth and banded coloring since the last call.
+    pub fn take_smooth_coloring_change(&mut self) -> Option<bool> {
+        std::mem::take(&mut self.smooth_coloring_changed).then_some(self.smooth_coloring)
+    }
+
+    /// Whether to draw the fractal at reduced resolution while it is in motion, if the user toggled
+    /// it since the last call.
+    pub fn take_dynamic_resolution_change(&mut self) -> Option<bool> {
+        std::mem::take(&mut self.dynamic_resolution_changed).then_some(self.dynamic_resolution)
+    }
+
+    /// `true` if the user requested to toggle fullscreen since the last call.
+    pub fn take_fullscreen_request(&mut self) -> bool {
+        std::mem::take(&mut self.fullscreen_requested)
+    }
+
+    /// `true` if the user requested to save the current location since the last call.
+    pub fn take_save_location_request(&mut self) -> bool {
+        std::mem::take(&mut self.save_location_requested)
+    }
+
+    /// `true` if the user requested to load the saved location since the last call.
+    pub fn take_load_location_request(&mut self) -> bool {
+        std::mem::take(&mut self.load_location_requested)
+    }
+
+    /// Index of the bookmark to jump to, if the user pressed one of the number keys `1` to `9`
+    /// since the last call. `1` selects index `0`. Bookmark keys are remapped with
+    /// [`KeyBindings::bookmarks`], whose position determines the index.
+    pub fn take_bookmark_request(&mut self) -> Option<usize> {
+        self.bookmark_requested.take()
+    }
+
+    /// Index of the bookmark to store the current view in, if the user pressed one of the number
+    /// keys `1` to `9` while holding `Ctrl` since the last call. `1` selects index `0`.
+    pub fn take_store_bookmark_request(&mut self) -> Option<usize> {
+        self.store_bookmark_requested.take()
+    }
+
+    /// `true` while the user paused the interaction. Applications should neither update nor
+    /// render the scene until it resumes.
+    pub fn paused(&self) -> bool {
+        self.paused
+    }
+
+    /// `true` if the number of iterations should grow with the zoom level. The iterations adjusted
+    /// by `update_scene` then serve as the base for the initial zoom level.
+    pub fn auto_iterations(&self) -> bool {
+        self.auto_iterations
+    }
+
+    /// `true` if the user requested to reload the fractal shader since the last call.
+    pub fn take_reload_shader_request(&mut self) -> bool {
+        std::mem::take(&mut self.reload_shader_requested)
+    }
+
+    /// `true` if the user requested to exit the application since the last call.
+    pub fn take_exit_request(&mut self) -> bool {
+        std::mem::take(&mut self.exit_requested)
+    }
+
+    /// Start or stop panning the camera by dragging with the left mouse button.
+    pub fn track_mouse_button(&mut self, state: ElementState, button: MouseButton) {
+        if button == MouseButton::Left {
+            self.dragging = state == ElementState::Pressed;
+            if let (true, Some(cursor)) = (self.dragging, self.cursor) {
+                self.track_click(cursor);
+                if self.auto_zoom.is_some() {
+                    self.auto_zoom_click = Some(cursor);
+                }
+            }
+            if self.dragging && self.outdated_since.is_none() {
+                self.outdated_since = Some(Instant::now())
+            }
+        }
+    }
+
+    /// Detect double clicks, by comparing a click at `position` with the previous one.
+    fn track_click(&mut self, position: PhysicalPosition<f64>) {
+        let now = Instant::now();
+        let is_double_click = self.last_click.is_some_and(|(time, last)| {
+            now - time <= DOUBLE_CLICK_TIME
+                && (position.x - last.x).hypot(position.y - last.y) <= DOUBLE_CLICK_DISTANCE
+        });
+        if is_double_click {
+            self.double_click = Some(position);
+            self.redraw_pending = true;
+            // A third click starts over, rather than counting as another double click.
+            self.last_click = None;
+        } else {
+            self.last_click = Some((now, position));
+        }
+    }
+
+    /// Dragging a single finger pans the camera, pinching two fingers zooms around the point between
+    /// them. Further fingers are ignored.
+    pub fn track_touch(&mut self, touch: Touch) {
+        let Touch {
+            id,
+            location,
+            phase,
+            ..
+        } = touch;
+        match phase {
+            TouchPhase::Started => {
+                self.touches.insert(id, location);
+            }
+            TouchPhase::Moved => {
+                let Some(last) = self.touches.insert(id, location) else {
+                    return;
+                };
+                self.redraw_pending = true;
+                let mut others = self.touches.iter().filter(|&(&other, _)| other != id);
+                match (self.touches.len(), others.next()) {
+                    (1, _) => {
+                        self.drag_delta[0] += location.x - last.x;
+                        self.drag_delta[1] += location.y - last.y;
+                    }
+                    (2, Some((_, &other))) => {
+                        let distance =
+                            |a: PhysicalPosition<f64>| (a.x - other.x).hypot(a.y - other.y);
+                        if distance(last) > 0. {
+                            self.pinch_factor *= (distance(location) / distance(last)) as f32;
+                        }
+                        // The midpoint moves half as far as the finger, and the content follows
+                        // it.
+                        self.drag_delta[0] += (location.x - last.x) / 2.;
+                        self.drag_delta[1] += (location.y - last.y) / 2.;
+                        self.pinch_center = Some(PhysicalPosition::new(
+                            (location.x + other.x) / 2.,
+                            (location.y + other.y) / 2.,
+                        ));
+                    }
+                    _ => (),
+                }
+            }
+            TouchPhase::Ended | TouchPhase::Cancelled => {
+                self.touches.remove(&id);
+            }
+        }
+        if self.outdated_since.is_none() && self.picture_changes() {
+            self.outdated_since = Some(Instant::now())
+        }
+    }
+
+    /// Accumulate mouse wheel scrolling, so the next call to `update_scene` zooms towards the
+    /// cursor.
+    pub fn track_mouse_wheel(&mut self, delta: MouseScrollDelta) {
+        self.wheel_lines += match delta {
+            MouseScrollDelta::LineDelta(_x, y) => y,
+            MouseScrollDelta::PixelDelta(position) => position.y as f32 / PIXELS_PER_LINE,
+        };
+        self.redraw_pending = true;
+        if self.outdated_since.is_none() {
+            self.outdated_since = Some(Instant::now())
+        }
+    }
+
+    /// `true` if the user requested to share the current scene since the last call.
+    pub fn take_share_request(&mut self) -> bool {
+        std::mem::take(&mut self.share_requested)
+    }
+
+    /// `true` if the user requested a high resolution still of the current view since the last
+    /// call.
+    pub fn take_still_request(&mut self) -> bool {
+        std::mem::take(&mut self.still_requested)
+    }
+
+    /// `true` if the user requested a screenshot since the last call.
+    pub fn take_screenshot_request(&mut self) -> bool {
+        std::mem::take(&mut self.screenshot_requested)
+    }
+
+    /// The magnifying glass to display, if enabled and the cursor position is known.
+    pub fn loupe(&self) -> Option<Loupe> {
+        let cursor = self.cursor.filter(|_| self.loupe)?;
+        Some(Loupe::new([cursor.x as f32, cursor.y as f32]))
+    }
+
+    /// The Julia set inset to display, while its key is held and the cursor position is known.
+    pub fn julia_inset(&self) -> Option<JuliaInset> {
+        let cursor = self.cursor.filter(|_| self.julia_inset)?;
+        Some(JuliaInset::new([cursor.x as f32, cursor.y as f32]))
+    }
+
+    /// `true` while `Shift` is held down, e.g. to jump to a bookmark instantly rather than
+    /// animating the transition.
+    pub fn fast(&self) -> bool {
+        self.fast
+    }
+
+    /// `true` if zoom level, iterations and coordinates should be displayed on top of the fractal.
+    pub fn hud(&self) -> bool {
+        self.hud
+    }
+
+    /// `true` if axes and unit gridlines of the complex plane should be drawn on top of the
+    /// fractal.
+    pub fn grid(&self) -> bool {
+        self.grid
+    }
+
+    /// Applies the user input to the scene. `canvas_size` is the size of the canvas in pixels, used
+    /// to locate the cursor within the picture.
+    pub fn update_scene(
+        &mut self,
+        camera: &mut Camera,
+        iterations: &mut f32,
+        kind: &mut FractalKind,
+        canvas_size: (u32, u32),
+    ) {
+        if self.paused {
+            return;
+        }
+        self.redraw = std::mem::take(&mut self.redraw_pending);
+        let wheel_lines = std::mem::take(&mut self.wheel_lines);
+        if wheel_lines != 0. {
+            let mut factor = WHEEL_ZOOM_PER_LINE.powf(wheel_lines);
+            if self.bindings.invert_zoom {
+                factor = 1.0 / factor;
+            }
+            let (width, height) = canvas_size;
+            // Without a known cursor position we zoom towards the center of the view.
+            let cursor = self
+                .cursor
+                .map_or([0., 0.], |cursor| to_ndc(cursor, canvas_size));
+            camera.zoom_about(factor, cursor, width, height);
+        }
+        let pinch_factor = std::mem::replace(&mut self.pinch_factor, 1.);
+        if let (true, Some(center)) = (pinch_factor != 1., self.pinch_center) {
+            let (width, height) = canvas_size;
+            camera.zoom_about(pinch_factor, to_ndc(center, canvas_size), width, height);
+        }
+        if let Some(position) = self.double_click.take() {
+            let (width, height) = canvas_size;
+            let ndc = to_ndc(position, canvas_size);
+            *camera = camera.magnified_at(ndc, width, height, DOUBLE_CLICK_ZOOM);
+        }
+        let [drag_x, drag_y] = std::mem::take(&mut self.drag_delta);
+        if drag_x != 0. || drag_y != 0. {
+            let (width, height) = canvas_size;
+            // The shorter side of the canvas spans two units at zoom level one. The content
+            // follows the cursor, so the camera moves in the opposite direction. Pixel rows grow
+            // downwards, while the imaginary axis points upwards.
+            let units_per_pixel = 2.0 / width.min(height) as f32;
+            camera.change_pos(
+                -drag_x as f32 * units_per_pixel,
+                drag_y as f32 * units_per_pixel,
+            );
+        }
+        if std::mem::take(&mut self.toggle_julia) {
+            *kind = match kind {
+                FractalKind::Julia { .. } => FractalKind::Mandelbrot,
+                _ => FractalKind::Julia { c: self.julia_c },
+            };
+        }
+        if std::mem::take(&mut self.toggle_burning_ship) {
+            *kind = match kind {
+                FractalKind::BurningShip => FractalKind::Mandelbrot,
+                _ => FractalKind::BurningShip,
+            };
+            // The ship lies elsewhere than the other fractals, so we show all of it.
+            camera.show_overview(kind);
+        }
+        if std::mem::take(&mut self.toggle_tricorn) {
+            *kind = match kind {
+                FractalKind::Tricorn => FractalKind::Mandelbrot,
+                _ => FractalKind::Tricorn,
+            };
+            camera.show_overview(kind);
+        }
+        if std::mem::take(&mut self.toggle_newton) {
+            *kind = match kind {
+                FractalKind::Newton => FractalKind::Mandelbrot,
+                _ => FractalKind::Newton,
+            };
+            camera.show_overview(kind);
+        }
+        if std::mem::take(&mut self.cycle_kind) {
+            *kind = match kind {
+                FractalKind::Mandelbrot => FractalKind::Julia { c: self.julia_c },
+                FractalKind::Julia { .. } => FractalKind::BurningShip,
+                FractalKind::BurningShip => FractalKind::Tricorn,
+                FractalKind::Tricorn => FractalKind::Newton,
+                FractalKind::Newton => FractalKind::Mandelbrot,
+            };
+            camera.show_overview(kind);
+            info!("Switched to {}", kind.name());
+        }
+        if std::mem::take(&mut self.reset_view) {
+            camera.reset();
+            *iterations = DEFAULT_ITERATIONS;
+            self.stop_coasting();
+            // Zooming on towards the old target would lead us astray again.
+            self.auto_zoom = None;
+        }
+        if std::mem::take(&mut self.toggle_auto_zoom) {
+            // Zoom towards the point under the cursor, or the center of the view if we do not know
+            // where the cursor is.
+            self.auto_zoom = match self.auto_zoom {
+                Some(_) => None,
+                None => Some(self.cursor.map_or(camera.position(), |cursor| {
+                    let (width, height) = canvas_size;
+                    camera.screen_to_complex(to_ndc(cursor, canvas_size), width, height)
+                })),
+            };
+        }
+        if let (Some(position), Some(_)) = (self.auto_zoom_click.take(), self.auto_zoom) {
+            let (width, height) = canvas_size;
+            let ndc = to_ndc(position, canvas_size);
+            self.auto_zoom = Some(camera.screen_to_complex(ndc, width, height));
+        }
+        let now = Instant::now();
+        if let Some(outdated_since) = self.outdated_since {
+            let delta_time = now - outdated_since;
+            self.update_camera(delta_time, camera);
+            if let Some(target) = self.auto_zoom {
+                camera.zoom_around(AUTO_ZOOM_RATE.powf(delta_time.as_secs_f32()), target);
+                if camera.zoom_level() >= camera.max_zoom() {
+                    info!("Maximum zoom reached. Auto zoom stopped.");
+                    self.auto_zoom = None;
+                }
+            }
+            self.update_iterations(delta_time, iterations);
+        }
+        if self.picture_changes() {
+            self.outdated_since = Some(now);
+        } else {
+            self.outdated_since = None;
+        }
+    }
+
+    fn update_iterations(&self, delta_time: Duration, iterations: &mut f32) {
+        // Change iterations in log space since we perceive the difference between 1 and 100
+        // iterations way stronger than the difference between 101 and 200.
+        let delta_iter = self.iteration_speed * delta_time.as_secs_f32();
+        let mut ln_iter = iterations.ln();
+        if self.inc_iter {
+            ln_iter += delta_iter;
+            ln_iter = ln_iter.min(self.max_ln_iterations);
+        }
+        if self.dec_iter {
+            ln_iter -= delta_iter;
+            ln_iter = ln_iter.max(self.min_ln_iterations);
+        }
+        *iterations = ln_iter.exp()
+    }
+
+    fn update_camera(&mut self, delta_time: Duration, camera: &mut Camera) {
+        // Speed is applied to the time passed since the last frame only, so releasing Shift returns
+        // to normal speed immediately.
+        let speed = if self.fast { self.fast_factor } else { 1.0 };
+        let delta_pos = speed * delta_time.as_secs_f32();
+        let delta_zoom = 1.0 + 0.4 * speed * delta_time.as_secs_f32();
+        // Camera
+        let mut delta_x = 0.;
+        let mut delta_y = 0.;
+        let mut zoom = 1.0;
+        if self.left.is_pressed() {
+            delta_x -= delta_pos;
+        }
+        if self.right.is_pressed() {
+            delta_x += delta_pos;
+        }
+        if self.up.is_pressed() {
+            delta_y += delta_pos;
+        }
+        if self.down.is_pressed() {
+            delta_y -= delta_pos;
+        }
+        if self.zoom_in {
+            zoom *= delta_zoom;
+        }
+        if self.zoom_out {
+            zoom /= delta_zoom;
+        }
+        if self.bindings.invert_pan_y {
+            delta_y = -delta_y;
+        }
+        if self.bindings.invert_zoom {
+            zoom = 1.0 / zoom;
+        }
+        let seconds = delta_time.as_secs_f32();
+        if self.inertia && seconds > 0. {
+            // The keys determine the velocity the camera approaches, rather than its movement.
+            // Exponential smoothing makes the approach independent of the frame rate.
+            let target = [delta_x / seconds, delta_y / seconds, zoom.ln() / seconds];
+            let blend = 1. - (-seconds / INERTIA_TIME_CONSTANT).exp();
+            let [velocity_x, velocity_y] = &mut self.velocity;
+            for (velocity, target) in [velocity_x, velocity_y, &mut self.zoom_velocity]
+                .into_iter()
+                .zip(target)
+            {
+                *velocity += (target - *velocity) * blend;
+            }
+            let released = target == [0., 0., 0.];
+            let slow = self
+                .velocity
+                .iter()
+                .chain([&self.zoom_velocity])
+                .all(|velocity| velocity.abs() < MIN_COASTING_SPEED);
+            if released && slow {
+                self.stop_coasting();
+            }
+            delta_x = self.velocity[0] * seconds;
+            delta_y = self.velocity[1] * seconds;
+            zoom = (self.zoom_velocity * seconds).exp();
+        }
+        camera.change_pos(delta_x, delta_y);
+        camera.zoom(zoom);
+    }
+
+    /// `true` while the camera keeps moving after all keys have been released, see
+    /// [`Self::inertia`].
+    fn is_coasting(&self) -> bool {
+        self.velocity != [0., 0.] || self.zoom_velocity != 0.
+    }
+
+    /// Brings a coasting camera to an immediate halt.
+    fn stop_coasting(&mut self) {
+        self.velocity = [0., 0.];
+        self.zoom_velocity = 0.;
+    }
+
+    /// `true` if the picture changes from one frame to the next, so the application should keep
+    /// rendering. Always `false` while paused.
+    pub fn picture_changes(&self) -> bool {
+        if self.paused {
+            return false;
+        }
+        self.up.is_pressed()
+            || self.down.is_pressed()
+            || self.left.is_pressed()
+            || self.right.is_pressed()
+            || self.zoom_in
+            || self.zoom_out
+            || self.inc_iter
+            || self.dec_iter
+            || self.dragging
+            || !self.touches.is_empty()
+            || self.auto_zoom.is_some()
+            || self.is_coasting()
+            // The picture is animated
+            || self.color_cycling
+            || self.redraw
+    }
+}
+
+/// State of a direction, which can be controlled with any of the keys bound to it, e.g. either an
+/// arrow key or one of WASD.
+#[derive(Clone, Default)]
+struct HeldKeys(Vec<VirtualKeyCode>);
+
+impl HeldKeys {
+    /// Remembers whether `key` is held down.
+    fn set(&mut self, key: VirtualKeyCode, is_pressed: bool) {
+        self.0.retain(|&held| held != key);
+        if is_pressed {
+            self.0.push(key);
+        }
+    }
+
+    /// `true` while any key is held down. Releasing one of them keeps the movement going, as long
+    /// as another is still held.
+    fn is_pressed(&self) -> bool {
+        !self.0.is_empty()
+    }
+}
+
+/// Transforms a position in physical pixels on a canvas of `(width, height)` pixels into normalized
+/// device coordinates.
+fn to_ndc(position: PhysicalPosition<f64>, (width, height): (u32, u32)) -> [f32; 2] {
+    [
+        2.0 * position.x as f32 / width as f32 - 1.0,
+        1.0 - 2.0 * position.y as f32 / height as f32,
+    ]
+}
+
+impl Default for Controls {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use winit::{
+        dpi::PhysicalPosition,
+        event::{
+            DeviceId, ElementState, KeyboardInput, ModifiersState, MouseButton, MouseScrollDelta,
+            Touch, TouchPhase, VirtualKeyCode,
+        },
+    };
+
+    use super::{Controls, DEFAULT_ITERATIONS, MAX_SUPERSAMPLING};
+    use crate::{Camera, FractalKind, KeyBindings, FIRE_PALETTE};
+
+    fn press(key: VirtualKeyCode) -> KeyboardInput {
+        key_input(key, ElementState::Pressed)
+    }
+
+    fn release(key: VirtualKeyCode) -> KeyboardInput {
+        key_input(key, ElementState::Released)
+    }
+
+    #[allow(deprecated)]
+    fn key_input(key: VirtualKeyCode, state: ElementState) -> KeyboardInput {
+        KeyboardInput {
+            scancode: 0,
+            state,
+            virtual_keycode: Some(key),
+            modifiers: ModifiersState::empty(),
+        }
+    }
+
+    fn touch(id: u64, phase: TouchPhase, x: f64, y: f64) -> Touch {
+        Touch {
+            // Only used to tell devices apart, which the controls do not do.
+            device_id: unsafe { DeviceId::dummy() },
+            phase,
+            location: PhysicalPosition::new(x, y),
+            force: None,
+            id,
+        }
+    }
+
+    #[test]
+    fn reset_key_restores_initial_view_for_one_frame() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        camera.zoom(1e4);
+        camera.change_pos(0.3, 0.1);
+        let mut iterations = 2000.;
+        let mut kind = FractalKind::Mandelbrot;
+
+        controls.track_button_presses(press(VirtualKeyCode::R));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        assert_eq!(Camera::new().inv_view(), camera.inv_view());
+        assert_eq!(DEFAULT_ITERATIONS, iterations);
+        assert!(controls.picture_changes());
+
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        assert!(!controls.picture_changes());
+    }
+
+    #[test]
+    fn remapped_keys_replace_the_default_ones() {
+        let mut controls = Controls::with_bindings(KeyBindings {
+            zoom_in: vec![VirtualKeyCode::Equals],
+            ..KeyBindings::default()
+        });
+        let mut camera = Camera::new();
+        let second = Duration::from_secs(1);
+
+        controls.track_button_presses(press(VirtualKeyCode::Period));
+        controls.update_camera(second, &mut camera);
+        assert_eq!(Camera::new(), camera);
+
+        controls.track_button_presses(press(VirtualKeyCode::Equals));
+        controls.update_camera(second, &mut camera);
+        assert_ne!(Camera::new(), camera);
+    }
+
+    #[test]
+    fn inverted_zoom_swaps_zooming_in_and_out() {
+        let mut controls = Controls::with_bindings(KeyBindings {
+            invert_zoom: true,
+            ..KeyBindings::default()
+        });
+        let mut camera = Camera::new();
+
+        controls.track_button_presses(press(VirtualKeyCode::Period));
+        controls.update_camera(Duration::from_secs(1), &mut camera);
+
+        assert!(camera.zoom_level() < 1.);
+    }
+
+    #[test]
+    fn direction_keeps_moving_while_another_of_its_keys_is_held() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let second = Duration::from_secs(1);
+        controls.track_button_presses(press(VirtualKeyCode::Right));
+        controls.track_button_presses(press(VirtualKeyCode::D));
+
+        controls.track_button_presses(release(VirtualKeyCode::Right));
+        controls.update_camera(second, &mut camera);
+        assert_eq!(0.5, camera.position()[0]);
+
+        controls.track_button_presses(release(VirtualKeyCode::D));
+        controls.update_camera(second, &mut camera);
+        assert_eq!(0.5, camera.position()[0]);
+    }
+
+    #[test]
+    fn shift_speeds_up_panning_only_while_held() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let second = Duration::from_secs(1);
+        controls.track_button_presses(press(VirtualKeyCode::Right));
+
+        controls.track_button_presses(press(VirtualKeyCode::LShift));
+        controls.update_camera(second, &mut camera);
+        assert_eq!(-0.5 + controls.fast_factor as f64, camera.position()[0]);
+
+        controls.track_button_presses(release(VirtualKeyCode::LShift));
+        controls.update_camera(second, &mut camera);
+        assert_eq!(0.5 + controls.fast_factor as f64, camera.position()[0]);
+    }
+
+    #[test]
+    fn iterations_change_at_configured_speed_within_bounds() {
+        let mut controls = Controls::new();
+        controls.iteration_speed = 2.;
+        controls.max_ln_iterations = 5.;
+        let mut iterations = 1.;
+        controls.track_button_presses(press(VirtualKeyCode::M));
+
+        controls.update_iterations(Duration::from_secs(1), &mut iterations);
+        assert!((iterations.ln() - 2.).abs() < 1e-6);
+
+        controls.update_iterations(Duration::from_secs(10), &mut iterations);
+        assert!((iterations.ln() - 5.).abs() < 1e-6);
+    }
+
+    #[test]
+    fn camera_with_inertia_coasts_to_a_stop_after_releasing_key() {
+        let mut controls = Controls::new();
+        controls.inertia = true;
+        let mut camera = Camera::new();
+        let frame = Duration::from_millis(16);
+        controls.track_button_presses(press(VirtualKeyCode::Right));
+        for _ in 0..30 {
+            controls.update_camera(frame, &mut camera);
+        }
+
+        controls.track_button_presses(release(VirtualKeyCode::Right));
+        let released_at = camera.position();
+        controls.update_camera(frame, &mut camera);
+
+        assert!(camera.position()[0] > released_at[0]);
+        assert!(controls.picture_changes());
+        for _ in 0..100 {
+            controls.update_camera(frame, &mut camera);
+        }
+        assert!(!controls.picture_changes());
+    }
+
+    #[test]
+    fn double_click_centers_on_cursor_and_zooms_in() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+        let size = (400, 400);
+        // Quarter of the way from the left, halfway from the top
+        controls.track_cursor(PhysicalPosition::new(100., 200.));
+        let target = camera.screen_to_complex([-0.5, 0.], 400, 400);
+
+        for _ in 0..2 {
+            controls.track_mouse_button(ElementState::Pressed, MouseButton::Left);
+            controls.track_mouse_button(ElementState::Released, MouseButton::Left);
+        }
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, size);
+
+        assert_eq!(target, camera.position());
+        assert_eq!(2., camera.zoom_level());
+    }
+
+    #[test]
+    fn auto_zoom_targets_the_cursor_and_follows_clicks() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+        let size = (400, 400);
+
+        // Toggling auto zoom targets the point under the cursor
+        controls.track_cursor(PhysicalPosition::new(100., 200.));
+        controls.track_button_presses(press(VirtualKeyCode::Z));
+        let target = camera.screen_to_complex([-0.5, 0.], 400, 400);
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, size);
+        assert_eq!(Some(target), controls.auto_zoom);
+
+        // A click while zooming retargets
+        controls.track_cursor(PhysicalPosition::new(300., 100.));
+        controls.track_mouse_button(ElementState::Pressed, MouseButton::Left);
+        controls.track_mouse_button(ElementState::Released, MouseButton::Left);
+        let target = camera.screen_to_complex([0.5, 0.5], 400, 400);
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, size);
+        assert_eq!(Some(target), controls.auto_zoom);
+    }
+
+    #[test]
+    fn releasing_one_of_two_keys_for_a_direction_keeps_moving() {
+        let mut controls = Controls::new();
+
+        controls.track_button_presses(press(VirtualKeyCode::Up));
+        controls.track_button_presses(press(VirtualKeyCode::W));
+        controls.track_button_presses(release(VirtualKeyCode::Up));
+        assert!(controls.picture_changes());
+
+        controls.track_button_presses(release(VirtualKeyCode::W));
+        assert!(!controls.picture_changes());
+    }
+
+    #[test]
+    fn releasing_all_keys_stops_the_camera() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        controls.track_button_presses(press(VirtualKeyCode::Left));
+        controls.track_button_presses(press(VirtualKeyCode::Period));
+        controls.track_mouse_button(ElementState::Pressed, MouseButton::Left);
+
+        // E.g. the window lost focus, so we never see the keys being released.
+        controls.release_all();
+        controls.update_camera(Duration::from_secs(1), &mut camera);
+
+        assert!(!controls.picture_changes());
+        assert_eq!(Camera::new().inv_view(), camera.inv_view());
+    }
+
+    #[test]
+    fn brackets_step_supersampling_within_bounds() {
+        let mut controls = Controls::new();
+
+        controls.track_button_presses(press(VirtualKeyCode::LBracket));
+        assert_eq!(None, controls.take_supersampling_change());
+
+        for _ in 0..10 {
+            controls.track_button_presses(press(VirtualKeyCode::RBracket));
+        }
+        assert_eq!(
+            Some(MAX_SUPERSAMPLING),
+            controls.take_supersampling_change()
+        );
+
+        controls.track_button_presses(press(VirtualKeyCode::LBracket));
+        assert_eq!(
+            Some(MAX_SUPERSAMPLING - 1),
+            controls.take_supersampling_change()
+        );
+    }
+
+    #[test]
+    fn number_keys_select_bookmarks() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+
+        controls.track_button_presses(press(VirtualKeyCode::Key3));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        assert!(controls.picture_changes());
+        assert_eq!(Some(2), controls.take_bookmark_request());
+        assert_eq!(None, controls.take_bookmark_request());
+    }
+
+    #[test]
+    fn keys_other_than_pause_and_exit_are_ignored_while_paused() {
+        let mut controls = Controls::new();
+
+        controls.track_button_presses(press(VirtualKeyCode::Space));
+        controls.track_button_presses(release(VirtualKeyCode::Space));
+        controls.track_button_presses(press(VirtualKeyCode::Key3));
+        assert!(controls.paused());
+        assert_eq!(None, controls.take_bookmark_request());
+
+        controls.track_button_presses(press(VirtualKeyCode::Escape));
+        assert!(controls.take_exit_request());
+        controls.track_button_presses(press(VirtualKeyCode::Space));
+        assert!(!controls.paused());
+    }
+
+    #[test]
+    fn number_keys_store_bookmarks_while_ctrl_is_held() {
+        let mut controls = Controls::new();
+
+        controls.track_button_presses(press(VirtualKeyCode::LControl));
+        controls.track_button_presses(press(VirtualKeyCode::Key4));
+        controls.track_button_presses(release(VirtualKeyCode::LControl));
+        controls.track_button_presses(press(VirtualKeyCode::Key5));
+
+        assert_eq!(Some(3), controls.take_store_bookmark_request());
+        assert_eq!(None, controls.take_store_bookmark_request());
+        assert_eq!(Some(4), controls.take_bookmark_request());
+    }
+
+    #[test]
+    fn storing_bookmarks_does_not_show_the_julia_inset() {
+        let mut controls = Controls::new();
+        controls.track_cursor(PhysicalPosition::new(100., 200.));
+
+        controls.track_button_presses(press(VirtualKeyCode::LControl));
+        controls.track_button_presses(press(VirtualKeyCode::Key4));
+
+        assert_eq!(None, controls.julia_inset());
+        controls.track_button_presses(press(VirtualKeyCode::LAlt));
+        assert!(controls.julia_inset().is_some());
+    }
+
+    #[test]
+    fn function_keys_select_palette_for_one_redraw() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+
+        controls.track_button_presses(press(VirtualKeyCode::F1));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        assert!(controls.picture_changes());
+        assert_eq!(Some(FIRE_PALETTE), controls.take_palette_change());
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+        assert!(!controls.picture_changes());
+    }
+
+    #[test]
+    fn pausing_freezes_the_picture() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+        controls.track_button_presses(press(VirtualKeyCode::Space));
+
+        controls.track_button_presses(press(VirtualKeyCode::Right));
+        controls.track_mouse_wheel(MouseScrollDelta::LineDelta(0., 3.));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        assert!(!controls.picture_changes());
+        assert_eq!(Camera::new().position(), camera.position());
+        assert_eq!(1., camera.zoom_level());
+
+        controls.track_button_presses(press(VirtualKeyCode::Space));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        // Neither the key pressed nor the wheel scrolled while paused take effect after resuming
+        assert!(!controls.right.is_pressed());
+        assert_eq!(Camera::new().position(), camera.position());
+        assert_eq!(1., camera.zoom_level());
+    }
+
+    #[test]
+    fn dragging_one_finger_pans() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+
+        controls.track_touch(touch(7, TouchPhase::Started, 200., 200.));
+        controls.track_touch(touch(7, TouchPhase::Moved, 300., 200.));
+        assert!(controls.picture_changes());
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        // A quarter of the canvas, which spans two units
+        assert_eq!([-1., 0.], camera.position());
+        assert_eq!(1., camera.zoom_level());
+
+        controls.track_touch(touch(7, TouchPhase::Ended, 300., 200.));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+        assert!(!controls.picture_changes());
+    }
+
+    #[test]
+    fn pinching_two_fingers_zooms_around_their_midpoint() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+
+        // Spread the fingers to twice their distance, symmetrically around the center
+        controls.track_touch(touch(1, TouchPhase::Started, 150., 200.));
+        controls.track_touch(touch(2, TouchPhase::Started, 250., 200.));
+        controls.track_touch(touch(1, TouchPhase::Moved, 100., 200.));
+        controls.track_touch(touch(2, TouchPhase::Moved, 300., 200.));
+        controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+
+        assert!((camera.zoom_level() - 2.).abs() < 1e-6);
+        assert_eq!(Camera::new().position(), camera.position());
+    }
+
+    #[test]
+    fn cycle_key_visits_every_kind_of_fractal() {
+        let mut controls = Controls::new();
+        let mut camera = Camera::new();
+        let mut iterations = 256.;
+        let mut kind = FractalKind::Mandelbrot;
+        let mut visited = Vec::new();
+
+        for _ in 0..5 {
+            controls.track_button_presses(press(VirtualKeyCode::Y));
+            controls.update_scene(&mut camera, &mut iterations, &mut kind, (400, 400));
+            visited.push(kind);
+        }
+
+        assert_eq!(
+            vec![
+                FractalKind::Julia {
+                    c: controls.julia_c
+                },
+                FractalKind::BurningShip,
+                FractalKind::Tricorn,
+                FractalKind::Newton,
+                FractalKind::Mandelbrot,
+            ],
+            visited
+        );
+    }
+}
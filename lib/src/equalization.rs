@@ -0,0 +1,93 @@
+use bytemuck::{Pod, Zeroable};
+
+/// Number of bins the escape values are sorted into. The histogram pass encodes escape values in a
+/// single byte, so each possible value gets its own bin.
+pub const HISTOGRAM_BINS: usize = 256;
+
+/// Longer side in pixels of the downscaled picture the histogram is gathered from. Large enough to
+/// capture the distribution of escape values, small enough to read it back every frame.
+pub const HISTOGRAM_SIZE: u32 = 128;
+
+/// Cumulative distribution of escape values, as seen by the fragment shader. Values are packed into
+/// vectors of four, since 16 Bytes is the required stride of arrays in uniforms.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct EqualizationArgs {
+    cdf: [[f32; 4]; HISTOGRAM_BINS / 4],
+}
+
+impl EqualizationArgs {
+    /// Distribution of evenly spread escape values. Maps every escape value to itself.
+    pub fn identity() -> Self {
+        let mut cdf = [0.; HISTOGRAM_BINS];
+        for (bin, value) in cdf.iter_mut().enumerate() {
+            *value = bin as f32 / (HISTOGRAM_BINS - 1) as f32;
+        }
+        Self::new(&cdf)
+    }
+
+    /// Cumulative distribution of the escape values written by the histogram pass. `pixels` are
+    /// tightly packed RGBA8 bytes, holding the escape value in the red channel. Points which never
+    /// escape are marked with an alpha of zero and do not count. Falls back to
+    /// [`Self::identity`] if no point escapes.
+    pub fn from_escape_values(pixels: &[u8]) -> Self {
+        let mut histogram = [0u32; HISTOGRAM_BINS];
+        for pixel in pixels.chunks_exact(4) {
+            if pixel[3] != 0 {
+                histogram[usize::from(pixel[0])] += 1;
+            }
+        }
+        let total: u32 = histogram.iter().sum();
+        if total == 0 {
+            return Self::identity();
+        }
+        let mut cdf = [0.; HISTOGRAM_BINS];
+        let mut cumulative = 0;
+        for (value, count) in cdf.iter_mut().zip(histogram) {
+            cumulative += count;
+            *value = cumulative as f32 / total as f32;
+        }
+        Self::new(&cdf)
+    }
+
+    fn new(cdf: &[f32; HISTOGRAM_BINS]) -> Self {
+        let mut packed = [[0.; 4]; HISTOGRAM_BINS / 4];
+        for (dest, src) in packed.iter_mut().zip(cdf.chunks_exact(4)) {
+            dest.copy_from_slice(src);
+        }
+        EqualizationArgs { cdf: packed }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::EqualizationArgs;
+
+    #[test]
+    fn frequent_escape_values_cover_most_of_the_palette() {
+        // Three quarters of the escaping points share a low escape value, the rest share a high
+        // one. One point never escapes.
+        let mut pixels = Vec::new();
+        pixels.extend([10, 0, 0, 255].repeat(3));
+        pixels.extend([200, 0, 0, 255]);
+        pixels.extend([0, 0, 0, 0]);
+
+        let args = EqualizationArgs::from_escape_values(&pixels);
+        let cdf: &[f32] = bytemuck::cast_slice(&args.cdf);
+
+        assert_eq!(0., cdf[9]);
+        assert_eq!(0.75, cdf[10]);
+        assert_eq!(0.75, cdf[199]);
+        assert_eq!(1., cdf[200]);
+        assert_eq!(1., cdf[255]);
+    }
+
+    #[test]
+    fn fall_back_to_identity_if_no_point_escapes() {
+        let args = EqualizationArgs::from_escape_values(&[0, 0, 0, 0]);
+        let cdf: &[f32] = bytemuck::cast_slice(&args.cdf);
+
+        assert_eq!(0., cdf[0]);
+        assert_eq!(1., cdf[255]);
+    }
+}
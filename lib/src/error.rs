@@ -0,0 +1,116 @@
+use std::fmt::{self, Display, Formatter};
+
+use wgpu::{Backends, BufferAsyncError, CreateSurfaceError, RequestDeviceError, TextureFormat};
+
+use crate::MAX_PALETTE_COLORS;
+
+/// Errors which may occur setting up the canvas for rendering, or reading back its results.
+#[derive(Debug)]
+pub enum CanvasError {
+    /// No surface could be created for the window.
+    CreateSurface(CreateSurfaceError),
+    /// None of the graphics adapters available for these backends is able to render the canvas,
+    /// not even a software adapter.
+    NoAdapter(Backends),
+    /// The adapter did not grant us a device.
+    RequestDevice(RequestDeviceError),
+    /// The WGSL source of the canvas shader could not be compiled. Contains the diagnostic emitted
+    /// by the shader compiler.
+    ShaderCompile(String),
+    /// The compute pipeline could not be created, e.g. because the device does not support compute
+    /// shaders. Contains the diagnostic emitted by wgpu.
+    ComputePipeline(String),
+    /// The adapter does not support rendering to and sampling from textures of this format.
+    UnsupportedFormat(TextureFormat),
+    /// The adapter does not support multisampling with this number of samples per pixel.
+    UnsupportedSampleCount(u32),
+    /// A palette must consist of at least one and at most [`crate::MAX_PALETTE_COLORS`] colors.
+    /// Contains the number of colors passed.
+    PaletteSize(usize),
+    /// Iterations are only computed by the compute pipeline, which is not in use. See
+    /// [`crate::Canvas::set_pipeline_mode`].
+    NoComputePipeline,
+    /// The iterations of every pixel of the canvas do not fit into a single storage buffer of the
+    /// device. Contains the size required and the largest size supported, both in bytes.
+    BufferTooLarge { size: u64, max_size: u64 },
+    /// Results computed by the GPU could not be read back.
+    ReadBack(BufferAsyncError),
+}
+
+impl Display for CanvasError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            CanvasError::CreateSurface(_) => write!(f, "Failed to create surface for the window."),
+            CanvasError::NoAdapter(backends) => write!(
+                f,
+                "No compatible graphics adapter available, not even a software one. Tried backends: \
+                {backends:?}."
+            ),
+            CanvasError::RequestDevice(_) => {
+                write!(f, "Graphics adapter did not provide a device for drawing.")
+            }
+            CanvasError::ShaderCompile(diagnostic) => {
+                write!(f, "Failed to compile canvas shader:\n{diagnostic}")
+            }
+            CanvasError::ComputePipeline(diagnostic) => {
+                write!(f, "Failed to create compute pipeline:\n{diagnostic}")
+            }
+            CanvasError::UnsupportedFormat(format) => {
+                write!(
+                    f,
+                    "Texture format {format:?} is not supported as render target."
+                )
+            }
+            CanvasError::UnsupportedSampleCount(count) => {
+                write!(
+                    f,
+                    "Multisampling with {count} samples per pixel is not supported."
+                )
+            }
+            CanvasError::PaletteSize(len) => {
+                write!(
+                    f,
+                    "Palette must have between 1 and {MAX_PALETTE_COLORS} colors, but has {len}."
+                )
+            }
+            CanvasError::NoComputePipeline => {
+                write!(f, "Iterations can only be read while drawing with the compute pipeline.")
+            }
+            CanvasError::BufferTooLarge { size, max_size } => write!(
+                f,
+                "Buffer of {size} bytes exceeds the largest storage buffer of the device, which \
+                holds {max_size} bytes."
+            ),
+            CanvasError::ReadBack(_) => write!(f, "Failed to read back results from the GPU."),
+        }
+    }
+}
+
+impl std::error::Error for CanvasError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            CanvasError::CreateSurface(error) => Some(error),
+            CanvasError::RequestDevice(error) => Some(error),
+            CanvasError::ReadBack(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<CreateSurfaceError> for CanvasError {
+    fn from(error: CreateSurfaceError) -> Self {
+        CanvasError::CreateSurface(error)
+    }
+}
+
+impl From<RequestDeviceError> for CanvasError {
+    fn from(error: RequestDeviceError) -> Self {
+        CanvasError::RequestDevice(error)
+    }
+}
+
+impl From<BufferAsyncError> for CanvasError {
+    fn from(error: BufferAsyncError) -> Self {
+        CanvasError::ReadBack(error)
+    }
+}
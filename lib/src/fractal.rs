@@ -0,0 +1,126 @@
+/// The kind of fractal rendered on the canvas.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum FractalKind {
+    /// Iterates `z = z^2 + c` starting with `z = 0`, using the position of the pixel as `c`.
+    #[default]
+    Mandelbrot,
+    /// Iterates `z = z^2 + c` starting with the position of the pixel as `z`, holding `c` fixed.
+    Julia { c: [f32; 2] },
+    /// Iterates `z = (|Re z| + i|Im z|)^2 + c` starting with `z = 0`, using the position of the
+    /// pixel as `c`.
+    BurningShip,
+    /// Also known as Mandelbar. Iterates `z = conj(z)^2 + c` starting with `z = 0`, using the
+    /// position of the pixel as `c`.
+    Tricorn,
+    /// Basins of the Newton-Raphson iteration `z = z - (z^3 - 1) / (3 z^2)`, starting with the
+    /// position of the pixel as `z`. Pixels are colored by the root of `z^3 - 1` they converge to.
+    /// Power and coloring mode do not apply.
+    Newton,
+}
+
+impl FractalKind {
+    /// Integer identifying the kind of fractal in the shader.
+    pub(crate) fn mode(&self) -> i32 {
+        match self {
+            FractalKind::Mandelbrot => 0,
+            FractalKind::Julia { .. } => 1,
+            FractalKind::BurningShip => 2,
+            FractalKind::Tricorn => 3,
+            FractalKind::Newton => 4,
+        }
+    }
+
+    /// Human readable name, e.g. to tell the user which fractal is displayed.
+    pub fn name(&self) -> &'static str {
+        match self {
+            FractalKind::Mandelbrot => "Mandelbrot set",
+            FractalKind::Julia { .. } => "Julia set",
+            FractalKind::BurningShip => "Burning Ship",
+            FractalKind::Tricorn => "Tricorn",
+            FractalKind::Newton => "Newton fractal",
+        }
+    }
+
+    /// Constant `c` of the Julia set. Zero for fractals which do not hold it fixed.
+    pub(crate) fn c(&self) -> [f32; 2] {
+        match self {
+            FractalKind::Julia { c } => *c,
+            _ => [0., 0.],
+        }
+    }
+}
+
+/// Decides how the color of a pixel is derived from its sequence.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ColoringMode {
+    /// Colors pixels using the palette, by how fast their sequence escapes.
+    #[default]
+    EscapeTime,
+    /// Brightens pixels by how close they are to the boundary of the set, estimated from the
+    /// derivative of the sequence. Yields crisp boundaries and thin filaments, e.g. for stills.
+    DistanceEstimation,
+    /// Colors pixels using the palette, by how close their sequence comes to a trap formed by the
+    /// real and imaginary axes. Yields swirling patterns, also within the set.
+    OrbitTrap,
+}
+
+impl ColoringMode {
+    /// Integer identifying the coloring mode in the shader.
+    pub(crate) fn mode(&self) -> i32 {
+        match self {
+            ColoringMode::EscapeTime => 0,
+            ColoringMode::DistanceEstimation => 1,
+            ColoringMode::OrbitTrap => 2,
+        }
+    }
+}
+
+/// Decides how the escape value of a point is mapped to a position in the palette, when coloring by
+/// escape time.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMapping {
+    /// Positions in the palette are proportional to the escape value.
+    #[default]
+    Linear,
+    /// Positions in the palette are proportional to the logarithm of the escape value. Spends more
+    /// of the palette on points which escape quickly, which reveals more structure far from the
+    /// set.
+    Log,
+}
+
+impl ColorMapping {
+    /// Integer identifying the color mapping in the shader.
+    pub(crate) fn mode(&self) -> i32 {
+        match self {
+            ColorMapping::Linear => 0,
+            ColorMapping::Log => 1,
+        }
+    }
+}
+
+/// Decides how points within the set, i.e. whose sequence never escapes, are colored.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum InteriorMode {
+    /// Points within the set are black. Precisely they get the first color of the palette, if
+    /// coloring by escape time.
+    #[default]
+    Black,
+    /// Colors points using the palette, by the magnitude of the last element of their sequence.
+    FinalMagnitude,
+    /// Colors points using the palette, by the average magnitude of the elements of their
+    /// sequence. Smoother than [`Self::FinalMagnitude`].
+    OrbitAverage,
+}
+
+impl InteriorMode {
+    /// Integer identifying the interior mode in the shader.
+    pub(crate) fn mode(&self) -> i32 {
+        match self {
+            InteriorMode::Black => 0,
+            InteriorMode::FinalMagnitude => 1,
+            InteriorMode::OrbitAverage => 2,
+        }
+    }
+}
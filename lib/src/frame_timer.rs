@@ -0,0 +1,167 @@
+use std::{
+    cell::Cell,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+// `std::time::Instant` panics in the browser, `instant` falls back to `performance.now()` there.
+use instant::Instant;
+use wgpu::{
+    Buffer, BufferDescriptor, BufferUsages, CommandBuffer, CommandEncoderDescriptor, Device,
+    Features, MapMode, QuerySet, QuerySetDescriptor, QueryType, Queue,
+};
+
+/// Measures how long frames take. Always measures the wall-clock time between submitting
+/// consecutive frames. Additionally measures the time the GPU spends on each frame, if the device
+/// supports timestamp queries.
+pub struct FrameTimer {
+    /// Point in time the last frame has been submitted.
+    last_submit: Cell<Option<Instant>>,
+    /// Wall-clock time between the last two submitted frames.
+    frame_time: Cell<Option<Duration>>,
+    /// `None` if the device does not support timestamp queries.
+    gpu: Option<GpuTimer>,
+}
+
+impl FrameTimer {
+    pub fn new(device: &Device, queue: &Queue) -> Self {
+        let gpu = device
+            .features()
+            .contains(Features::TIMESTAMP_QUERY)
+            .then(|| GpuTimer::new(device, queue));
+        FrameTimer {
+            last_submit: Cell::new(None),
+            frame_time: Cell::new(None),
+            gpu,
+        }
+    }
+
+    /// Commands to submit before the frame. Records the GPU timestamp at the start of the frame,
+    /// unless the previous measurement is still being read back.
+    pub fn begin(&self, device: &Device) -> Option<CommandBuffer> {
+        // Invoke callbacks of finished measurements
+        device.poll(wgpu::Maintain::Poll);
+        self.gpu
+            .as_ref()
+            .filter(|gpu| !gpu.in_flight.load(Ordering::Acquire))
+            .map(|gpu| gpu.encode_begin(device))
+    }
+
+    /// Commands to submit after the frame. Records the GPU timestamp at the end of the frame.
+    /// `measured` must be `true` if the commands returned by [`Self::begin`] have been submitted.
+    pub fn end(&self, device: &Device, measured: bool) -> Option<CommandBuffer> {
+        let now = Instant::now();
+        if let Some(last_submit) = self.last_submit.replace(Some(now)) {
+            self.frame_time.set(Some(now - last_submit));
+        }
+        self.gpu
+            .as_ref()
+            .filter(|_| measured)
+            .map(|gpu| gpu.encode_end(device))
+    }
+
+    /// Starts reading back the GPU timestamps. Must be called after the commands returned by
+    /// [`Self::end`] have been submitted.
+    pub fn read_back(&self) {
+        if let Some(gpu) = &self.gpu {
+            gpu.read_back();
+        }
+    }
+
+    /// Wall-clock time between the last two calls to [`Self::end`]. `None` until `end` has been
+    /// called twice.
+    pub fn frame_time(&self) -> Option<Duration> {
+        self.frame_time.get()
+    }
+
+    /// GPU time of the most recent frame whose timestamps have been read back. Always `None` if
+    /// the device does not support timestamp queries.
+    pub fn gpu_time(&self) -> Option<Duration> {
+        self.gpu
+            .as_ref()
+            .and_then(|gpu| *gpu.gpu_time.lock().unwrap())
+    }
+}
+
+/// Measures GPU time using timestamp queries.
+struct GpuTimer {
+    /// Holds the timestamps at the beginning and the end of the frame.
+    query_set: QuerySet,
+    /// Queries are resolved into this buffer, so the CPU can read them.
+    readback_buffer: Arc<Buffer>,
+    /// Nanoseconds per timestamp tick.
+    period: f32,
+    /// `true` while `readback_buffer` is in use by a measurement.
+    in_flight: Arc<AtomicBool>,
+    /// Result of the last finished measurement.
+    gpu_time: Arc<Mutex<Option<Duration>>>,
+}
+
+impl GpuTimer {
+    /// Size of the two timestamps in bytes.
+    const SIZE: u64 = 2 * std::mem::size_of::<u64>() as u64;
+
+    fn new(device: &Device, queue: &Queue) -> Self {
+        let query_set = device.create_query_set(&QuerySetDescriptor {
+            label: Some("Frame Timestamps"),
+            ty: QueryType::Timestamp,
+            count: 2,
+        });
+        let readback_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Frame Timestamps Readback Buffer"),
+            size: Self::SIZE,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        GpuTimer {
+            query_set,
+            readback_buffer: Arc::new(readback_buffer),
+            period: queue.get_timestamp_period(),
+            in_flight: Arc::new(AtomicBool::new(false)),
+            gpu_time: Arc::new(Mutex::new(None)),
+        }
+    }
+
+    fn encode_begin(&self, device: &Device) -> CommandBuffer {
+        self.in_flight.store(true, Ordering::Release);
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Frame Timer Begin Encoder"),
+        });
+        encoder.write_timestamp(&self.query_set, 0);
+        encoder.finish()
+    }
+
+    fn encode_end(&self, device: &Device) -> CommandBuffer {
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("Frame Timer End Encoder"),
+        });
+        encoder.write_timestamp(&self.query_set, 1);
+        encoder.resolve_query_set(&self.query_set, 0..2, &self.readback_buffer, 0);
+        encoder.finish()
+    }
+
+    fn read_back(&self) {
+        let buffer = self.readback_buffer.clone();
+        let in_flight = self.in_flight.clone();
+        let gpu_time = self.gpu_time.clone();
+        let period = self.period;
+        self.readback_buffer
+            .slice(..)
+            .map_async(MapMode::Read, move |result| {
+                if result.is_ok() {
+                    let timestamps: [u64; 2] = {
+                        let bytes = buffer.slice(..).get_mapped_range();
+                        bytemuck::pod_read_unaligned(&bytes)
+                    };
+                    buffer.unmap();
+                    let ticks = timestamps[1].saturating_sub(timestamps[0]);
+                    let nanos = (ticks as f64 * f64::from(period)) as u64;
+                    *gpu_time.lock().unwrap() = Some(Duration::from_nanos(nanos));
+                }
+                in_flight.store(false, Ordering::Release);
+            });
+    }
+}
@@ -0,0 +1,36 @@
+/// Draws the axes and unit gridlines of the complex plane on top of the fractal.
+
+/// Same inverse view matrix the canvas shader uses. Only its scale is used, since the vertices are
+/// already given relative to the camera position.
+struct VertexArgs {
+    inv_view: mat4x4<f32>,
+}
+
+@group(0) @binding(0)
+var<uniform> VERTEX_ARGS: VertexArgs;
+
+struct VertexInput {
+    /// Offset of the end point of a line from the camera position in the complex plane
+    @location(0) offset: vec2<f32>,
+    @location(1) color: vec4<f32>,
+};
+
+struct VertexOutput {
+    @builtin(position) clip_position: vec4<f32>,
+    @location(0) color: vec4<f32>,
+};
+
+@vertex
+fn vs_main(line: VertexInput) -> VertexOutput {
+    // Inverts the scale the canvas shader applies to get from clip space into the complex plane.
+    let scale = vec2<f32>(VERTEX_ARGS.inv_view[0].x, VERTEX_ARGS.inv_view[1].y);
+    var out: VertexOutput;
+    out.clip_position = vec4<f32>(line.offset / scale, 0.0, 1.0);
+    out.color = line.color;
+    return out;
+}
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    return in.color;
+}
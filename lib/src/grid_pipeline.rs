@@ -0,0 +1,234 @@
+use std::mem::size_of;
+
+use bytemuck::{Pod, Zeroable};
+use wgpu::{
+    BindGroup, BindGroupLayout, BlendState, Buffer, BufferAddress, BufferDescriptor, BufferUsages,
+    ColorTargetState, ColorWrites, CommandEncoder, Device, FragmentState, LoadOp, MultisampleState,
+    Operations, PipelineLayoutDescriptor, PrimitiveState, PrimitiveTopology, Queue,
+    RenderPassColorAttachment, RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor,
+    ShaderModuleDescriptor, ShaderSource, TextureFormat, TextureView, VertexAttribute,
+    VertexBufferLayout, VertexFormat, VertexState, VertexStepMode,
+};
+
+use crate::Camera;
+
+/// Source used to compile the grid shader at startup
+const GRID_SHADER_SOURCE: &str = include_str!("grid.wgsl");
+
+/// Gridlines are only drawn if at most this many of them are visible along an axis. Any more would
+/// hide the fractal behind them. The axes are drawn regardless.
+const MAX_LINES_PER_AXIS: usize = 64;
+
+/// Color of the real and imaginary axes.
+const AXIS_COLOR: [f32; 4] = [1., 1., 1., 0.75];
+
+/// Color of the gridlines at integer coordinates.
+const GRID_COLOR: [f32; 4] = [1., 1., 1., 0.25];
+
+/// End point of a line, as used in the vertex buffer of the grid shader.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+struct LineVertex {
+    /// Offset from the camera position in the complex plane.
+    offset: [f32; 2],
+    color: [f32; 4],
+}
+
+impl LineVertex {
+    const DESC: VertexBufferLayout<'static> = VertexBufferLayout {
+        array_stride: size_of::<Self>() as u64,
+        step_mode: VertexStepMode::Vertex,
+        attributes: &[
+            VertexAttribute {
+                format: VertexFormat::Float32x2,
+                offset: 0,
+                shader_location: 0,
+            },
+            VertexAttribute {
+                format: VertexFormat::Float32x4,
+                offset: size_of::<[f32; 2]>() as BufferAddress,
+                shader_location: 1,
+            },
+        ],
+    };
+}
+
+/// Draws the real and imaginary axes, as well as gridlines at integer coordinates, on top of the
+/// fractal. Shares the inverse view matrix with the canvas render pipeline, so the lines move and
+/// scale with the camera.
+pub struct GridPipeline {
+    render_pipeline: RenderPipeline,
+    /// Holds the end points of the lines, updated each frame the grid is drawn.
+    vertex_buffer: Buffer,
+}
+
+impl GridPipeline {
+    /// * `device` is used to create the render pipeline and load the shader.
+    /// * `inv_view_layout` is the layout of the inverse view matrix of the canvas render pipeline.
+    /// * `target_format` is the format of the output the grid is drawn onto.
+    pub fn new(
+        device: &Device,
+        inv_view_layout: &BindGroupLayout,
+        target_format: TextureFormat,
+    ) -> Self {
+        let shader = device.create_shader_module(ShaderModuleDescriptor {
+            label: Some("Grid Shader"),
+            source: ShaderSource::Wgsl(GRID_SHADER_SOURCE.into()),
+        });
+        let layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("Grid Pipeline Layout"),
+            bind_group_layouts: &[inv_view_layout],
+            push_constant_ranges: &[],
+        });
+        let render_pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("Grid Render Pipeline"),
+            layout: Some(&layout),
+            vertex: VertexState {
+                module: &shader,
+                entry_point: "vs_main",
+                buffers: &[LineVertex::DESC],
+            },
+            fragment: Some(FragmentState {
+                module: &shader,
+                entry_point: "fs_main",
+                targets: &[Some(ColorTargetState {
+                    format: target_format,
+                    blend: Some(BlendState::ALPHA_BLENDING),
+                    write_mask: ColorWrites::ALL,
+                })],
+            }),
+            primitive: PrimitiveState {
+                topology: PrimitiveTopology::LineList,
+                ..PrimitiveState::default()
+            },
+            depth_stencil: None,
+            multiview: None,
+            multisample: MultisampleState::default(),
+        });
+        // Two lines per integer coordinate along both axes, two vertices each.
+        let vertex_buffer = device.create_buffer(&BufferDescriptor {
+            label: Some("Grid Vertices"),
+            size: (4 * (MAX_LINES_PER_AXIS + 1) * size_of::<LineVertex>()) as BufferAddress,
+            usage: BufferUsages::VERTEX | BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        GridPipeline {
+            render_pipeline,
+            vertex_buffer,
+        }
+    }
+
+    /// Writes the lines visible through `camera` into the vertex buffer. `(width, height)` is the
+    /// size of the area the fractal is drawn into in pixels. Returns the number of vertices to
+    /// draw.
+    pub fn update_lines(&self, queue: &Queue, camera: &Camera, (width, height): (u32, u32)) -> u32 {
+        let vertices = visible_lines(camera, width, height);
+        queue.write_buffer(&self.vertex_buffer, 0, bytemuck::cast_slice(&vertices));
+        vertices.len() as u32
+    }
+
+    /// Records drawing the first `vertices` of the lines written by [`Self::update_lines`] on top
+    /// of `output`. `viewport` restricts drawing to the rectangle the fractal has been drawn into.
+    /// `inv_view` is the bind group of the inverse view matrix the fractal has been drawn with.
+    pub fn draw_to(
+        &self,
+        output: &TextureView,
+        viewport: Option<[f32; 4]>,
+        inv_view: &BindGroup,
+        vertices: u32,
+        encoder: &mut CommandEncoder,
+    ) {
+        let rpd = RenderPassDescriptor {
+            label: Some("Grid Render Pass"),
+            color_attachments: &[Some(RenderPassColorAttachment {
+                view: output,
+                resolve_target: None,
+                ops: Operations {
+                    load: LoadOp::Load,
+                    store: true,
+                },
+            })],
+            depth_stencil_attachment: None,
+        };
+        let mut render_pass = encoder.begin_render_pass(&rpd);
+        if let Some([x, y, width, height]) = viewport {
+            render_pass.set_viewport(x, y, width, height, 0., 1.);
+        }
+        render_pass.set_pipeline(&self.render_pipeline);
+        render_pass.set_bind_group(0, inv_view, &[]);
+        render_pass.set_vertex_buffer(0, self.vertex_buffer.slice(..));
+        render_pass.draw(0..vertices, 0..1);
+    }
+}
+
+/// End points of the axes and gridlines visible through `camera` on a canvas of `width` times
+/// `height` pixels. Offsets from the camera position are computed in double precision, so the
+/// lines stay in place when zoomed in deeply.
+fn visible_lines(camera: &Camera, width: u32, height: u32) -> Vec<LineVertex> {
+    let ([left, bottom], [right, top]) = camera.visible_bounds(width, height);
+    let [x, y] = camera.position();
+    let mut vertices = Vec::new();
+    let mut vertex = |offset: [f64; 2], color| {
+        vertices.push(LineVertex {
+            offset: offset.map(|value| value as f32),
+            color,
+        })
+    };
+    for re in integers_within(left, right) {
+        let color = if re == 0. { AXIS_COLOR } else { GRID_COLOR };
+        vertex([re - x, bottom - y], color);
+        vertex([re - x, top - y], color);
+    }
+    for im in integers_within(bottom, top) {
+        let color = if im == 0. { AXIS_COLOR } else { GRID_COLOR };
+        vertex([left - x, im - y], color);
+        vertex([right - x, im - y], color);
+    }
+    vertices
+}
+
+/// Integers between `low` and `high`. Only zero, if there are more than [`MAX_LINES_PER_AXIS`] of
+/// them.
+fn integers_within(low: f64, high: f64) -> Vec<f64> {
+    let first = low.ceil();
+    let last = high.floor();
+    if last - first >= MAX_LINES_PER_AXIS as f64 {
+        return if first <= 0. && 0. <= last {
+            vec![0.]
+        } else {
+            Vec::new()
+        };
+    }
+    let mut integers = Vec::new();
+    let mut value = first;
+    while value <= last {
+        integers.push(value);
+        value += 1.;
+    }
+    integers
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Camera;
+
+    use super::{integers_within, visible_lines, AXIS_COLOR};
+
+    #[test]
+    fn initial_view_shows_axes_and_unit_lines() {
+        // Initial view spans -1.5..0.5 along the real and -1..1 along the imaginary axis.
+        let lines = visible_lines(&Camera::new(), 100, 100);
+
+        // Real parts -1 and 0, imaginary parts -1 to 1
+        assert_eq!(2 * (2 + 3), lines.len());
+        let axes = lines.iter().filter(|vertex| vertex.color == AXIS_COLOR);
+        assert_eq!(4, axes.count());
+    }
+
+    #[test]
+    fn dense_gridlines_are_left_out() {
+        assert_eq!(vec![-1., 0., 1.], integers_within(-1.5, 1.));
+        assert_eq!(vec![0.], integers_within(-1e3, 1e3));
+        assert!(integers_within(1e3, 1e4).is_empty());
+    }
+}
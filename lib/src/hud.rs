@@ -0,0 +1,236 @@
+use std::num::NonZeroU32;
+
+use wgpu::{
+    Device, Extent3d, ImageCopyTexture, ImageDataLayout, Origin3d, Queue, TextureAspect,
+    TextureFormat,
+};
+
+use crate::blit_pipeline::{BlitPipeline, Intermediate};
+
+/// Format of the texture the text of the HUD is rasterized into.
+const HUD_FORMAT: TextureFormat = TextureFormat::Rgba8Unorm;
+/// Each pixel of a glyph is drawn as a square of this many pixels edge length.
+const SCALE: usize = 2;
+/// Width of a glyph in font pixels, including the space to the next one.
+const CELL_WIDTH: usize = 6;
+/// Height of a line in font pixels, including the space to the next one.
+const CELL_HEIGHT: usize = 9;
+/// Space between the text and the edge of its background in pixels.
+const PADDING: usize = 4;
+/// Distance of the HUD from the top left corner of the canvas in pixels.
+const MARGIN: u32 = 8;
+/// The background darkens the fractal behind the text, so it stays readable on bright colors.
+const BACKGROUND: [u8; 4] = [0, 0, 0, 160];
+const FOREGROUND: [u8; 4] = [255, 255, 255, 255];
+
+/// Lines of text displayed on top of the fractal, e.g. the zoom level and the position of the
+/// camera.
+pub struct Hud {
+    /// Text currently rasterized into `target`. Kept so we only rasterize again if it changes.
+    lines: Vec<String>,
+    /// Holds the rasterized text, bound as source for the blit pipeline.
+    target: Intermediate,
+}
+
+impl Hud {
+    /// Rasterizes `lines` and uploads them to the GPU.
+    pub fn new(
+        device: &Device,
+        queue: &Queue,
+        blit_pipeline: &BlitPipeline,
+        lines: &[String],
+    ) -> Self {
+        let (width, height, pixels) = rasterize(lines);
+        let target = blit_pipeline.intermediate(device, width, height, HUD_FORMAT);
+        upload(queue, &target, &pixels);
+        Hud {
+            lines: lines.to_vec(),
+            target,
+        }
+    }
+
+    /// Rasterizes `lines` again if they differ from the ones displayed. Reuses the texture if the
+    /// size of the text did not change.
+    pub fn update(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        blit_pipeline: &BlitPipeline,
+        lines: &[String],
+    ) {
+        if self.lines == lines {
+            return;
+        }
+        let (width, height, pixels) = rasterize(lines);
+        if (width, height) != (self.target.width, self.target.height) {
+            self.target = blit_pipeline.intermediate(device, width, height, HUD_FORMAT);
+        }
+        upload(queue, &self.target, &pixels);
+        self.lines = lines.to_vec();
+    }
+
+    /// Rasterized text, to be drawn on top of the fractal.
+    pub fn target(&self) -> &Intermediate {
+        &self.target
+    }
+
+    /// Rectangle `[x, y, width, height]` in pixels the HUD is drawn into, in the top left corner of
+    /// a canvas with the given size. `None` if it does not fit into the canvas.
+    pub fn viewport(&self, width: u32, height: u32) -> Option<[u32; 4]> {
+        let fits = MARGIN + self.target.width <= width && MARGIN + self.target.height <= height;
+        fits.then_some([MARGIN, MARGIN, self.target.width, self.target.height])
+    }
+}
+
+/// Writes tightly packed RGBA8 `pixels` into the texture of `target`.
+fn upload(queue: &Queue, target: &Intermediate, pixels: &[u8]) {
+    queue.write_texture(
+        ImageCopyTexture {
+            texture: target.texture(),
+            mip_level: 0,
+            origin: Origin3d::ZERO,
+            aspect: TextureAspect::All,
+        },
+        pixels,
+        ImageDataLayout {
+            offset: 0,
+            bytes_per_row: NonZeroU32::new(target.width * 4),
+            rows_per_image: None,
+        },
+        Extent3d {
+            width: target.width,
+            height: target.height,
+            depth_or_array_layers: 1,
+        },
+    );
+}
+
+/// Draws `lines` with the built-in bitmap font onto a translucent background. Returns width,
+/// height and tightly packed RGBA8 pixels, row by row starting at the top left.
+fn rasterize(lines: &[String]) -> (u32, u32, Vec<u8>) {
+    let columns = lines
+        .iter()
+        .map(|line| line.chars().count())
+        .max()
+        .unwrap_or(0);
+    let width = 2 * PADDING + columns * CELL_WIDTH * SCALE;
+    let height = 2 * PADDING + lines.len() * CELL_HEIGHT * SCALE;
+    let mut pixels = BACKGROUND.repeat(width * height);
+    for (row, line) in lines.iter().enumerate() {
+        for (column, character) in line.chars().enumerate() {
+            let rows = glyph(character);
+            for (glyph_y, bits) in rows.iter().enumerate() {
+                for glyph_x in 0..5 {
+                    if bits & (0b10000 >> glyph_x) == 0 {
+                        continue;
+                    }
+                    let left = PADDING + (column * CELL_WIDTH + glyph_x) * SCALE;
+                    let top = PADDING + (row * CELL_HEIGHT + glyph_y) * SCALE;
+                    for y in top..top + SCALE {
+                        for x in left..left + SCALE {
+                            let offset = (y * width + x) * 4;
+                            pixels[offset..offset + 4].copy_from_slice(&FOREGROUND);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    (width as u32, height as u32, pixels)
+}
+
+/// Rows of the 5x7 glyph for `character`, top to bottom. The leftmost pixel is the most
+/// significant of the five bits. Lower case letters are displayed in upper case and unknown
+/// characters as `?`.
+fn glyph(character: char) -> [u8; 7] {
+    let character = character.to_ascii_uppercase();
+    GLYPHS
+        .iter()
+        .find(|(c, _)| *c == character)
+        .or_else(|| GLYPHS.iter().find(|(c, _)| *c == '?'))
+        .map(|(_, rows)| *rows)
+        .expect("Font must contain '?'")
+}
+
+/// Built-in bitmap font. Covers digits, upper case letters and the punctuation needed to display
+/// numbers and coordinates.
+#[rustfmt::skip]
+const GLYPHS: &[(char, [u8; 7])] = &[
+    (' ', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000]),
+    ('0', [0b01110, 0b10001, 0b10011, 0b10101, 0b11001, 0b10001, 0b01110]),
+    ('1', [0b00100, 0b01100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('2', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b01000, 0b11111]),
+    ('3', [0b11111, 0b00010, 0b00100, 0b00010, 0b00001, 0b10001, 0b01110]),
+    ('4', [0b00010, 0b00110, 0b01010, 0b10010, 0b11111, 0b00010, 0b00010]),
+    ('5', [0b11111, 0b10000, 0b11110, 0b00001, 0b00001, 0b10001, 0b01110]),
+    ('6', [0b00110, 0b01000, 0b10000, 0b11110, 0b10001, 0b10001, 0b01110]),
+    ('7', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b01000, 0b01000]),
+    ('8', [0b01110, 0b10001, 0b10001, 0b01110, 0b10001, 0b10001, 0b01110]),
+    ('9', [0b01110, 0b10001, 0b10001, 0b01111, 0b00001, 0b00010, 0b01100]),
+    ('A', [0b01110, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('B', [0b11110, 0b10001, 0b10001, 0b11110, 0b10001, 0b10001, 0b11110]),
+    ('C', [0b01110, 0b10001, 0b10000, 0b10000, 0b10000, 0b10001, 0b01110]),
+    ('D', [0b11100, 0b10010, 0b10001, 0b10001, 0b10001, 0b10010, 0b11100]),
+    ('E', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b11111]),
+    ('F', [0b11111, 0b10000, 0b10000, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('G', [0b01110, 0b10001, 0b10000, 0b10111, 0b10001, 0b10001, 0b01111]),
+    ('H', [0b10001, 0b10001, 0b10001, 0b11111, 0b10001, 0b10001, 0b10001]),
+    ('I', [0b01110, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b01110]),
+    ('J', [0b00111, 0b00010, 0b00010, 0b00010, 0b00010, 0b10010, 0b01100]),
+    ('K', [0b10001, 0b10010, 0b10100, 0b11000, 0b10100, 0b10010, 0b10001]),
+    ('L', [0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b10000, 0b11111]),
+    ('M', [0b10001, 0b11011, 0b10101, 0b10101, 0b10001, 0b10001, 0b10001]),
+    ('N', [0b10001, 0b10001, 0b11001, 0b10101, 0b10011, 0b10001, 0b10001]),
+    ('O', [0b01110, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('P', [0b11110, 0b10001, 0b10001, 0b11110, 0b10000, 0b10000, 0b10000]),
+    ('Q', [0b01110, 0b10001, 0b10001, 0b10001, 0b10101, 0b10010, 0b01101]),
+    ('R', [0b11110, 0b10001, 0b10001, 0b11110, 0b10100, 0b10010, 0b10001]),
+    ('S', [0b01111, 0b10000, 0b10000, 0b01110, 0b00001, 0b00001, 0b11110]),
+    ('T', [0b11111, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100, 0b00100]),
+    ('U', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01110]),
+    ('V', [0b10001, 0b10001, 0b10001, 0b10001, 0b10001, 0b01010, 0b00100]),
+    ('W', [0b10001, 0b10001, 0b10001, 0b10101, 0b10101, 0b10101, 0b01010]),
+    ('X', [0b10001, 0b10001, 0b01010, 0b00100, 0b01010, 0b10001, 0b10001]),
+    ('Y', [0b10001, 0b10001, 0b10001, 0b01010, 0b00100, 0b00100, 0b00100]),
+    ('Z', [0b11111, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b11111]),
+    ('.', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b01100]),
+    (',', [0b00000, 0b00000, 0b00000, 0b00000, 0b01100, 0b00100, 0b01000]),
+    (':', [0b00000, 0b01100, 0b01100, 0b00000, 0b01100, 0b01100, 0b00000]),
+    ('-', [0b00000, 0b00000, 0b00000, 0b11111, 0b00000, 0b00000, 0b00000]),
+    ('+', [0b00000, 0b00100, 0b00100, 0b11111, 0b00100, 0b00100, 0b00000]),
+    ('(', [0b00010, 0b00100, 0b01000, 0b01000, 0b01000, 0b00100, 0b00010]),
+    (')', [0b01000, 0b00100, 0b00010, 0b00010, 0b00010, 0b00100, 0b01000]),
+    ('/', [0b00000, 0b00001, 0b00010, 0b00100, 0b01000, 0b10000, 0b00000]),
+    ('=', [0b00000, 0b00000, 0b11111, 0b00000, 0b11111, 0b00000, 0b00000]),
+    ('%', [0b11000, 0b11001, 0b00010, 0b00100, 0b01000, 0b10011, 0b00011]),
+    ('*', [0b00000, 0b00100, 0b10101, 0b01110, 0b10101, 0b00100, 0b00000]),
+    ('_', [0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b00000, 0b11111]),
+    ('?', [0b01110, 0b10001, 0b00001, 0b00010, 0b00100, 0b00000, 0b00100]),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::{glyph, rasterize, BACKGROUND, FOREGROUND, PADDING, SCALE};
+
+    #[test]
+    fn rasterized_text_is_framed_by_background() {
+        let (width, height, pixels) = rasterize(&["-1".to_owned(), "".to_owned()]);
+
+        assert_eq!((2 * PADDING + 2 * 6 * SCALE) as u32, width);
+        assert_eq!((2 * PADDING + 2 * 9 * SCALE) as u32, height);
+        assert_eq!((width * height * 4) as usize, pixels.len());
+        let pixel = |x: usize, y: usize| {
+            let offset = (y * width as usize + x) * 4;
+            &pixels[offset..offset + 4]
+        };
+        assert_eq!(&BACKGROUND, pixel(0, 0));
+        // Left end of the bar of the minus sign, in its fourth row
+        assert_eq!(&FOREGROUND, pixel(PADDING, PADDING + 3 * SCALE));
+    }
+
+    #[test]
+    fn lower_case_and_unknown_characters_have_glyphs() {
+        assert_eq!(glyph('Z'), glyph('z'));
+        assert_eq!(glyph('?'), glyph('§'));
+    }
+}
@@ -0,0 +1,29 @@
+/// Entry point computing the raw number of iterations per pixel, rather than colors. This source is
+/// appended to the canvas shader, whose bindings and functions it shares.
+
+/// Smooth iteration counts of the pixels, row by row starting at the top left.
+struct Iterations {
+    /// Width of the picture in pixels. Written by the canvas before the values are computed.
+    width: u32,
+    /// Height of the picture in pixels.
+    height: u32,
+    values: array<f32>,
+}
+
+/// Shares its group with the palette, since devices may not support more than four bind groups.
+@group(3) @binding(2)
+var<storage, read_write> ITERATIONS: Iterations;
+
+@compute @workgroup_size(8, 8)
+fn cs_iterations(@builtin(global_invocation_id) id: vec3<u32>) {
+    // The picture is covered by whole workgroups, which may reach beyond its edges.
+    if (id.x >= ITERATIONS.width || id.y >= ITERATIONS.height) {
+        return;
+    }
+    let size = vec2<f32>(f32(ITERATIONS.width), f32(ITERATIONS.height));
+    let pixel = vec2<f32>(id.xy);
+    // Center of the pixel, like in the compute entry point drawing colors.
+    let ndc = vec2<f32>((pixel.x + 0.5) / size.x * 2.0 - 1.0, 1.0 - (pixel.y + 0.5) / size.y * 2.0);
+    let offset = (VERTEX_ARGS.inv_view * vec4<f32>(ndc, 0.0, 0.0)).xy;
+    ITERATIONS.values[id.y * ITERATIONS.width + id.x] = smooth_iterations(offset);
+}
@@ -0,0 +1,52 @@
+/// Picture-in-picture preview of the Julia set belonging to the point of the Mandelbrot set under
+/// the cursor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct JuliaInset {
+    /// Position of the cursor in physical pixels, relative to the top left of the canvas. The
+    /// point under it is the constant `c` of the Julia set.
+    pub cursor: [f32; 2],
+    /// Edge length of the square inset in physical pixels.
+    pub size: u32,
+}
+
+impl JuliaInset {
+    /// Default sized inset for the point under the cursor.
+    pub fn new(cursor: [f32; 2]) -> Self {
+        JuliaInset { cursor, size: 200 }
+    }
+
+    /// The cursor position in normalized device coordinates of a canvas with the given size.
+    pub fn cursor_ndc(&self, width: u32, height: u32) -> [f32; 2] {
+        [
+            2.0 * self.cursor[0] / width as f32 - 1.0,
+            1.0 - 2.0 * self.cursor[1] / height as f32,
+        ]
+    }
+
+    /// Square viewport `[x, y, size]` in pixels, the inset is drawn into. It sits in the bottom
+    /// corner on the other side of the canvas than the cursor, so it does not hide the point
+    /// picked. Shrunk if necessary to fit inside the canvas.
+    pub fn viewport(&self, width: u32, height: u32) -> [u32; 3] {
+        let size = self.size.min(width).min(height);
+        let x = if self.cursor[0] > width as f32 / 2. {
+            0
+        } else {
+            width - size
+        };
+        [x, height - size, size]
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::JuliaInset;
+
+    #[test]
+    fn inset_avoids_the_cursor() {
+        let left = JuliaInset::new([100., 300.]);
+        let right = JuliaInset::new([700., 300.]);
+
+        assert_eq!([600, 200, 200], left.viewport(800, 400));
+        assert_eq!([0, 200, 200], right.viewport(800, 400));
+    }
+}
@@ -0,0 +1,183 @@
+use winit::event::VirtualKeyCode;
+
+/// Keys triggering each action of [`crate::Controls`]. Any number of keys may be bound to an
+/// action, none disables it. The default binds the keys described in the greeting of the
+/// application, e.g. remap `zoom_in` and `zoom_out` for keyboard layouts where period and comma are
+/// hard to reach. The direction of panning and zooming is configured here as well, so all of the
+/// input is set up in one place.
+///
+/// ```
+/// use fractal_wgpu_lib::{Controls, KeyBindings};
+/// use winit::event::VirtualKeyCode;
+///
+/// let bindings = KeyBindings {
+///     zoom_in: vec![VirtualKeyCode::Equals],
+///     zoom_out: vec![VirtualKeyCode::Minus],
+///     ..KeyBindings::default()
+/// };
+/// let controls = Controls::with_bindings(bindings);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct KeyBindings {
+    /// Move the visible part up, while held down.
+    pub up: Vec<VirtualKeyCode>,
+    /// Move the visible part down, while held down.
+    pub down: Vec<VirtualKeyCode>,
+    /// Move the visible part left, while held down.
+    pub left: Vec<VirtualKeyCode>,
+    /// Move the visible part right, while held down.
+    pub right: Vec<VirtualKeyCode>,
+    /// Zoom in, while held down.
+    pub zoom_in: Vec<VirtualKeyCode>,
+    /// Zoom out, while held down.
+    pub zoom_out: Vec<VirtualKeyCode>,
+    /// Increase the number of iterations, while held down.
+    pub more_iterations: Vec<VirtualKeyCode>,
+    /// Decrease the number of iterations, while held down.
+    pub fewer_iterations: Vec<VirtualKeyCode>,
+    /// Move and zoom faster, while held down.
+    pub fast: Vec<VirtualKeyCode>,
+    /// Preview the Julia set of the point under the cursor, while held down.
+    pub julia_inset: Vec<VirtualKeyCode>,
+    /// While held down, bookmark keys store the current view, rather than jumping to it.
+    pub store_bookmark: Vec<VirtualKeyCode>,
+    /// Toggle adapting the number of iterations to the zoom level.
+    pub auto_iterations: Vec<VirtualKeyCode>,
+    /// Toggle zooming continuously towards the point under the cursor. Clicking while zooming picks
+    /// a new target.
+    pub auto_zoom: Vec<VirtualKeyCode>,
+    /// Switch between the Mandelbrot and a Julia set.
+    pub julia: Vec<VirtualKeyCode>,
+    /// Switch between the Mandelbrot set and the Burning Ship.
+    pub burning_ship: Vec<VirtualKeyCode>,
+    /// Switch between the Mandelbrot set and the Tricorn.
+    pub tricorn: Vec<VirtualKeyCode>,
+    /// Switch between the Mandelbrot set and the Newton fractal.
+    pub newton: Vec<VirtualKeyCode>,
+    /// Cycle through all kinds of fractals.
+    pub cycle_fractal: Vec<VirtualKeyCode>,
+    /// Cycle through the preset color palettes.
+    pub cycle_palette: Vec<VirtualKeyCode>,
+    /// Select the fire palette.
+    pub fire_palette: Vec<VirtualKeyCode>,
+    /// Select the ice palette.
+    pub ice_palette: Vec<VirtualKeyCode>,
+    /// Select the grayscale palette.
+    pub grayscale_palette: Vec<VirtualKeyCode>,
+    /// Select the classic palette.
+    pub classic_palette: Vec<VirtualKeyCode>,
+    /// Toggle inverting the colors.
+    pub invert_colors: Vec<VirtualKeyCode>,
+    /// Switch between smooth colors and bands of color.
+    pub smooth_coloring: Vec<VirtualKeyCode>,
+    /// Let the colors flow through the palette.
+    pub color_cycling: Vec<VirtualKeyCode>,
+    /// Cycle through coloring by escape time, distance estimation and orbit traps.
+    pub coloring: Vec<VirtualKeyCode>,
+    /// Cycle through the ways to color the inside of the set.
+    pub interior: Vec<VirtualKeyCode>,
+    /// Toggle spreading the colors of the palette evenly over the picture.
+    pub equalization: Vec<VirtualKeyCode>,
+    /// Cycle through the preset anti-aliasing levels.
+    pub cycle_supersampling: Vec<VirtualKeyCode>,
+    /// Raise the anti-aliasing level by one step.
+    pub more_supersampling: Vec<VirtualKeyCode>,
+    /// Lower the anti-aliasing level by one step.
+    pub less_supersampling: Vec<VirtualKeyCode>,
+    /// Toggle drawing at reduced resolution while the picture moves.
+    pub dynamic_resolution: Vec<VirtualKeyCode>,
+    /// Switch between filling the window and showing a square picture.
+    pub fit_mode: Vec<VirtualKeyCode>,
+    /// Show zoom level, iterations and coordinates.
+    pub hud: Vec<VirtualKeyCode>,
+    /// Show the axes and unit gridlines of the complex plane.
+    pub grid: Vec<VirtualKeyCode>,
+    /// Show a magnifying glass around the cursor.
+    pub loupe: Vec<VirtualKeyCode>,
+    /// Return to the initial view.
+    pub reset_view: Vec<VirtualKeyCode>,
+    /// Jump to a bookmark, or store the current view in its place. The first key selects the first
+    /// bookmark, the second key the second one, and so on.
+    pub bookmarks: Vec<VirtualKeyCode>,
+    /// Save a screenshot.
+    pub screenshot: Vec<VirtualKeyCode>,
+    /// Save a large still of the current view.
+    pub still: Vec<VirtualKeyCode>,
+    /// Share a token restoring the current scene.
+    pub share: Vec<VirtualKeyCode>,
+    /// Save the current location.
+    pub save_location: Vec<VirtualKeyCode>,
+    /// Load the saved location.
+    pub load_location: Vec<VirtualKeyCode>,
+    /// Reload the fractal shader.
+    pub reload_shader: Vec<VirtualKeyCode>,
+    /// Toggle fullscreen.
+    pub fullscreen: Vec<VirtualKeyCode>,
+    /// Pause and resume rendering.
+    pub pause: Vec<VirtualKeyCode>,
+    /// Quit the application.
+    pub exit: Vec<VirtualKeyCode>,
+    /// If `true` pressing up moves the content up rather than the view.
+    pub invert_pan_y: bool,
+    /// If `true` the zoom in and out controls, including the mouse wheel, swap their roles.
+    pub invert_zoom: bool,
+}
+
+impl Default for KeyBindings {
+    fn default() -> Self {
+        use VirtualKeyCode::*;
+        KeyBindings {
+            up: vec![Up, W],
+            down: vec![Down, S],
+            left: vec![Left, A],
+            right: vec![Right, D],
+            zoom_in: vec![Period],
+            zoom_out: vec![Comma],
+            more_iterations: vec![M],
+            fewer_iterations: vec![N],
+            fast: vec![LShift, RShift],
+            julia_inset: vec![LAlt, RAlt],
+            store_bookmark: vec![LControl, RControl],
+            auto_iterations: vec![I],
+            auto_zoom: vec![Z],
+            julia: vec![J],
+            burning_ship: vec![B],
+            tricorn: vec![T],
+            newton: vec![G],
+            cycle_fractal: vec![Y],
+            cycle_palette: vec![C],
+            fire_palette: vec![F1],
+            ice_palette: vec![F2],
+            grayscale_palette: vec![F3],
+            classic_palette: vec![F4],
+            // `I` is taken by automatic iterations, so inversion lives next to `F5`.
+            invert_colors: vec![F6],
+            smooth_coloring: vec![F10],
+            color_cycling: vec![H],
+            coloring: vec![E],
+            interior: vec![V],
+            equalization: vec![U],
+            cycle_supersampling: vec![Q],
+            more_supersampling: vec![RBracket],
+            less_supersampling: vec![LBracket],
+            dynamic_resolution: vec![F7],
+            fit_mode: vec![F],
+            hud: vec![Tab],
+            grid: vec![X],
+            loupe: vec![L],
+            reset_view: vec![R, Home],
+            bookmarks: vec![Key1, Key2, Key3, Key4, Key5, Key6, Key7, Key8, Key9],
+            screenshot: vec![P],
+            still: vec![F9],
+            share: vec![F8],
+            save_location: vec![K],
+            load_location: vec![O],
+            reload_shader: vec![F5],
+            fullscreen: vec![F11],
+            pause: vec![Space, Pause],
+            exit: vec![Escape],
+            invert_pan_y: false,
+            invert_zoom: false,
+        }
+    }
+}
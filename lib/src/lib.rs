@@ -1,6 +1,56 @@
+mod blit_pipeline;
 mod camera;
 mod canvas;
+mod canvas_builder;
 mod canvas_render_pipeline;
+mod capture;
+mod compute_pipeline;
+mod controls;
+mod equalization;
+mod error;
+mod fractal;
+mod frame_timer;
+mod grid_pipeline;
+mod hud;
+mod julia_inset;
+mod key_bindings;
+mod loupe;
+mod palette;
+mod render_state;
+#[cfg(feature = "serde")]
+mod scene_token;
 mod shader;
+mod surface_target;
 
-pub use self::{camera::Camera, canvas::Canvas};
+#[cfg(feature = "testing")]
+pub mod testing;
+
+pub use self::{
+    camera::{Camera, InvalidCamera, DEFAULT_MAX_ZOOM},
+    canvas::{Canvas, FitMode, PipelineMode},
+    canvas_builder::CanvasBuilder,
+    controls::Controls,
+    error::CanvasError,
+    fractal::{ColorMapping, ColoringMode, FractalKind, InteriorMode},
+    julia_inset::JuliaInset,
+    key_bindings::KeyBindings,
+    loupe::Loupe,
+    palette::{
+        DEFAULT_PALETTE, FIRE_PALETTE, GRAYSCALE_PALETTE, ICE_PALETTE, MAX_PALETTE_COLORS,
+        PRESET_PALETTES, RAINBOW_PALETTE,
+    },
+    render_state::RenderState,
+    shader::DEFAULT_ESCAPE_RADIUS,
+    surface_target::{SurfaceTarget, WindowHandle},
+};
+
+#[cfg(feature = "serde")]
+pub use self::scene_token::{SceneToken, SceneTokenError};
+
+// Re-exported, so callers can configure and inspect the canvas without depending on wgpu
+// themselves.
+pub use wgpu::{AdapterInfo, Backends, Color, Limits, PowerPreference, PresentMode};
+
+// Re-exported, so windows of any toolkit can be linked to a canvas using the same version of the
+// handles as wgpu.
+pub use raw_window_handle;
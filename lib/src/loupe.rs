@@ -0,0 +1,39 @@
+/// A magnifying glass rendered on top of the canvas around the cursor.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Loupe {
+    /// Position of the cursor in physical pixels, relative to the top left of the canvas.
+    pub cursor: [f32; 2],
+    /// Radius of the loupe in physical pixels.
+    pub radius: u32,
+    /// How much more the fractal is zoomed in within the loupe, compared to the main view.
+    pub magnification: f32,
+}
+
+impl Loupe {
+    /// Default sized loupe at the cursor position.
+    pub fn new(cursor: [f32; 2]) -> Self {
+        Loupe {
+            cursor,
+            radius: 100,
+            magnification: 8.0,
+        }
+    }
+
+    /// The cursor position in normalized device coordinates of a canvas with the given size.
+    pub fn cursor_ndc(&self, width: u32, height: u32) -> [f32; 2] {
+        [
+            2.0 * self.cursor[0] / width as f32 - 1.0,
+            1.0 - 2.0 * self.cursor[1] / height as f32,
+        ]
+    }
+
+    /// Square viewport `[x, y, size]` in pixels, the loupe is drawn into. It is centered on the
+    /// cursor, but shifted and shrunk if necessary to stay inside the canvas.
+    pub fn viewport(&self, width: u32, height: u32) -> [u32; 3] {
+        let size = (2 * self.radius).min(width).min(height);
+        let half = size as f32 / 2.;
+        let x = (self.cursor[0] - half).clamp(0., (width - size) as f32) as u32;
+        let y = (self.cursor[1] - half).clamp(0., (height - size) as f32) as u32;
+        [x, y, size]
+    }
+}
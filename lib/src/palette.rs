@@ -0,0 +1,59 @@
+//! Color palettes mapping the escape value of a pixel to its color. A palette is a list of RGB
+//! control points. The first color is used for points which never escape, the last one for points
+//! which escape the slowest. Colors in between are spaced evenly and blended linearly.
+
+/// Maximum number of control points in a palette.
+pub const MAX_PALETTE_COLORS: usize = 8;
+
+/// Palette used unless specified otherwise. Blends from black over green and red to blue.
+pub const DEFAULT_PALETTE: &[[f32; 3]] = &[
+    [0., 0., 0.],
+    [0., 0.5, 0.],
+    [0., 1., 0.],
+    [1., 0., 0.],
+    [0., 0., 1.],
+];
+
+/// Blends from black to white.
+pub const GRAYSCALE_PALETTE: &[[f32; 3]] = &[[0., 0., 0.], [1., 1., 1.]];
+
+/// Glows from black over dark red, red and orange to yellow and white.
+pub const FIRE_PALETTE: &[[f32; 3]] = &[
+    [0., 0., 0.],
+    [0.5, 0., 0.],
+    [1., 0., 0.],
+    [1., 0.5, 0.],
+    [1., 1., 0.],
+    [1., 1., 1.],
+];
+
+/// Freezes from black over dark blue and cyan to white.
+pub const ICE_PALETTE: &[[f32; 3]] = &[
+    [0., 0., 0.],
+    [0., 0., 0.4],
+    [0., 0.4, 0.8],
+    [0.4, 0.9, 1.],
+    [1., 1., 1.],
+];
+
+/// Starts with black, and then runs through the colors of the rainbow.
+pub const RAINBOW_PALETTE: &[[f32; 3]] = &[
+    [0., 0., 0.],
+    [1., 0., 0.],
+    [1., 0.5, 0.],
+    [1., 1., 0.],
+    [0., 1., 0.],
+    [0., 0., 1.],
+    [0.3, 0., 0.5],
+    [0.6, 0., 0.8],
+];
+
+/// Palettes offered to the user, e.g. to cycle through. A [`crate::RenderState`] refers to palettes
+/// by their index in this list.
+pub const PRESET_PALETTES: [&[[f32; 3]]; 5] = [
+    DEFAULT_PALETTE,
+    GRAYSCALE_PALETTE,
+    RAINBOW_PALETTE,
+    FIRE_PALETTE,
+    ICE_PALETTE,
+];
@@ -0,0 +1,47 @@
+use crate::{Camera, FractalKind};
+
+/// Everything which changes from frame to frame and determines the picture drawn by
+/// [`crate::Canvas::render`], so it can be stored or shared as one. Settings which change rarely,
+/// like the coloring mode, are configured on the canvas instead.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RenderState {
+    /// Camera position and zoom level. Determines which part of the fractal we see.
+    pub camera: Camera,
+    /// Number of iterations used to determine whether a point converges or not.
+    pub iterations: i32,
+    /// Kind of fractal displayed, e.g. Mandelbrot or Julia set.
+    pub kind: FractalKind,
+    /// Index into [`crate::PRESET_PALETTES`] of the palette to draw with. Indices past the end
+    /// wrap around. `None` keeps the palette of the canvas, e.g. one set with
+    /// [`crate::Canvas::set_palette`].
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub palette: Option<usize>,
+}
+
+impl RenderState {
+    /// State drawn with the palette of the canvas. See [`Self::with_palette`] to pick one.
+    pub fn new(camera: Camera, iterations: i32, kind: FractalKind) -> Self {
+        RenderState {
+            camera,
+            iterations,
+            kind,
+            palette: None,
+        }
+    }
+
+    /// Draws the state with the palette at `index` in [`crate::PRESET_PALETTES`].
+    pub fn with_palette(self, index: usize) -> Self {
+        RenderState {
+            palette: Some(index),
+            ..self
+        }
+    }
+}
+
+impl Default for RenderState {
+    /// Overview of the Mandelbrot set with 256 iterations.
+    fn default() -> Self {
+        Self::new(Camera::new(), 256, FractalKind::Mandelbrot)
+    }
+}
@@ -0,0 +1,187 @@
+use std::{
+    fmt::{self, Display, Formatter},
+    str::FromStr,
+};
+
+use crate::{ColoringMode, RenderState, PRESET_PALETTES};
+
+/// Alphabet of URL safe base64, so tokens can be part of links and command lines without quoting.
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Everything needed to restore a scene exactly, e.g. to share it with others. Converts into a
+/// compact token of URL safe base64 using [`ToString::to_string`], and back using
+/// [`str::parse`].
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct SceneToken {
+    /// Camera, iterations, kind of fractal and palette.
+    pub state: RenderState,
+    /// How the color of a pixel is derived from its sequence.
+    pub coloring: ColoringMode,
+}
+
+impl Display for SceneToken {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let json = serde_json::to_string(self).expect("Scene must be serializable to JSON");
+        f.write_str(&encode_base64(json.as_bytes()))
+    }
+}
+
+impl FromStr for SceneToken {
+    type Err = SceneTokenError;
+
+    /// Restores a scene from a token. Fails if the token is malformed, or describes a scene we can
+    /// not render, e.g. with a palette which does not exist.
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        let json = decode_base64(token.trim()).ok_or(SceneTokenError::Encoding)?;
+        let scene: SceneToken = serde_json::from_slice(&json).map_err(SceneTokenError::Scene)?;
+        if let Some(palette) = scene
+            .state
+            .palette
+            .filter(|&index| index >= PRESET_PALETTES.len())
+        {
+            return Err(SceneTokenError::Palette(palette));
+        }
+        if scene.state.iterations <= 0 {
+            return Err(SceneTokenError::Iterations(scene.state.iterations));
+        }
+        Ok(scene)
+    }
+}
+
+/// Reasons a [`SceneToken`] may be rejected.
+#[derive(Debug)]
+pub enum SceneTokenError {
+    /// The token is not valid URL safe base64.
+    Encoding,
+    /// The decoded token does not describe a scene, e.g. because fields are missing or the camera
+    /// position is not finite.
+    Scene(serde_json::Error),
+    /// There is no preset palette with this index.
+    Palette(usize),
+    /// The number of iterations must be positive.
+    Iterations(i32),
+}
+
+impl Display for SceneTokenError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SceneTokenError::Encoding => write!(f, "Scene token is not valid base64."),
+            SceneTokenError::Scene(_) => write!(f, "Scene token does not describe a scene."),
+            SceneTokenError::Palette(index) => write!(
+                f,
+                "Scene token refers to palette {index}, but there are only {}.",
+                PRESET_PALETTES.len()
+            ),
+            SceneTokenError::Iterations(iterations) => write!(
+                f,
+                "Scene token has {iterations} iterations, but must have at least one."
+            ),
+        }
+    }
+}
+
+impl std::error::Error for SceneTokenError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SceneTokenError::Scene(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// Encodes `bytes` as URL safe base64 without padding.
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut encoded = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        // Missing bytes of the last chunk are zero, and the characters they would make up are left
+        // out.
+        let mut group = [0; 3];
+        group[..chunk.len()].copy_from_slice(chunk);
+        let bits = u32::from_be_bytes([0, group[0], group[1], group[2]]);
+        for index in 0..=chunk.len() {
+            let sextet = (bits >> (18 - 6 * index)) & 0b11_1111;
+            encoded.push(char::from(BASE64_ALPHABET[sextet as usize]));
+        }
+    }
+    encoded
+}
+
+/// Decodes URL safe base64 without padding. `None` if `text` contains characters outside of the
+/// alphabet, or its length could not have been produced by [`encode_base64`].
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let mut decoded = Vec::with_capacity(text.len() / 4 * 3);
+    for chunk in text.as_bytes().chunks(4) {
+        // A single character holds only six bits, too few for a byte.
+        if chunk.len() == 1 {
+            return None;
+        }
+        let mut bits = 0u32;
+        for (index, &character) in chunk.iter().enumerate() {
+            let sextet = BASE64_ALPHABET
+                .iter()
+                .position(|&letter| letter == character)?;
+            bits |= (sextet as u32) << (18 - 6 * index);
+        }
+        decoded.extend_from_slice(&bits.to_be_bytes()[1..chunk.len()]);
+    }
+    Some(decoded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{decode_base64, encode_base64, SceneToken, SceneTokenError};
+    use crate::{Camera, ColoringMode, FractalKind, RenderState};
+
+    #[test]
+    fn base64_round_trip_for_every_length_of_the_last_chunk() {
+        for input in [&b""[..], b"f", b"fo", b"foo", b"foob", b"\xff\xfe\x00"] {
+            let encoded = encode_base64(input);
+            assert_eq!(Some(input.to_vec()), decode_base64(&encoded), "{encoded}");
+        }
+        assert_eq!("Zm9vYg", encode_base64(b"foob"));
+    }
+
+    #[test]
+    fn parse_scene_from_its_token() {
+        let mut camera = Camera::new();
+        camera.set_position([-0.743, 0.131]);
+        camera.set_zoom(1e5);
+        let scene = SceneToken {
+            state: RenderState::new(camera, 1200, FractalKind::Julia { c: [-0.8, 0.156] })
+                .with_palette(3),
+            coloring: ColoringMode::OrbitTrap,
+        };
+
+        let parsed: SceneToken = scene.to_string().parse().unwrap();
+
+        assert_eq!(scene, parsed);
+    }
+
+    #[test]
+    fn reject_malformed_tokens() {
+        let mut scene = SceneToken {
+            state: RenderState::default().with_palette(0),
+            coloring: ColoringMode::EscapeTime,
+        };
+        assert!(matches!(
+            "not a token!".parse::<SceneToken>(),
+            Err(SceneTokenError::Encoding)
+        ));
+        assert!(matches!(
+            encode_base64(b"{}").parse::<SceneToken>(),
+            Err(SceneTokenError::Scene(_))
+        ));
+        scene.state.palette = Some(42);
+        assert!(matches!(
+            scene.to_string().parse::<SceneToken>(),
+            Err(SceneTokenError::Palette(42))
+        ));
+        scene.state.palette = Some(0);
+        scene.state.iterations = 0;
+        assert!(matches!(
+            scene.to_string().parse::<SceneToken>(),
+            Err(SceneTokenError::Iterations(0))
+        ));
+    }
+}
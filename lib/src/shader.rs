@@ -8,12 +8,17 @@ use wgpu::{
     ShaderStages, VertexAttribute, VertexBufferLayout, VertexFormat, VertexStepMode,
 };
 
+use crate::{
+    equalization::EqualizationArgs, ColorMapping, ColoringMode, FractalKind, InteriorMode,
+    MAX_PALETTE_COLORS,
+};
+
 /// Source used to compile the shader code at startup
 pub const CANVAS_SHADER_SOURCE: &str = include_str!("shader.wgsl");
 
 /// Inverse View matrix is bound as a Uniform variable available in the vertex shader stage. The
 /// inverse view matrix is used to control which part of the canvas the user can see.
-const INV_VIEW_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
+pub const INV_VIEW_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
     label: Some("Inverse View Bind Group Layout"),
     entries: &[BindGroupLayoutEntry {
         // Must match shader index
@@ -30,10 +35,11 @@ const INV_VIEW_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
     }],
 };
 
-/// Number of iterations is bound as a Uniform variable available in the fragment shader stage. It
-/// is the number of iterations we calculate of the complex sequence before we consider it
-/// convergent.
-pub const ITERATIONS_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
+/// Number of iterations and camera position are bound as a Uniform variable available in the
+/// fragment shader stage. The number of iterations is how many elements of the complex sequence we
+/// calculate before we consider it convergent. The position is added to the (small) offsets
+/// interpolated by the vertex shader in emulated double precision.
+pub const FRAGMENT_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
     label: Some("ITERATIONS BIND GROUP LAYOUT"),
     entries: &[BindGroupLayoutEntry {
         // Must match shader index
@@ -50,6 +56,245 @@ pub const ITERATIONS_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescript
     }],
 };
 
+/// Kind of fractal is bound as Uniform variable available in the fragment shader stage. It decides
+/// how the sequence is initialized, and holds the constant for Julia sets.
+pub const FRACTAL_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
+    label: Some("Fractal Bind Group Layout"),
+    entries: &[BindGroupLayoutEntry {
+        // Must match shader index
+        binding: 0,
+        // We only need this in the fragment shader
+        visibility: ShaderStages::FRAGMENT,
+        ty: BindingType::Buffer {
+            // All fragments see the same fractal
+            ty: BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: None,
+        },
+        count: None,
+    }],
+};
+
+/// Color palette is bound as Uniform variable available in the fragment shader stage. It maps the
+/// escape value of a pixel to its color. Alongside it we bind the cumulative distribution of escape
+/// values, which spreads them evenly over the palette if histogram equalization is enabled.
+pub const PALETTE_LAYOUT: BindGroupLayoutDescriptor = BindGroupLayoutDescriptor {
+    label: Some("Palette Bind Group Layout"),
+    entries: &[
+        BindGroupLayoutEntry {
+            // Must match shader index
+            binding: 0,
+            // We only need this in the fragment shader
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Buffer {
+                // All fragments see the same palette
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+        BindGroupLayoutEntry {
+            // Must match shader index
+            binding: 1,
+            // We only need this in the fragment shader
+            visibility: ShaderStages::FRAGMENT,
+            ty: BindingType::Buffer {
+                // All fragments see the same distribution
+                ty: BufferBindingType::Uniform,
+                has_dynamic_offset: false,
+                min_binding_size: None,
+            },
+            count: None,
+        },
+    ],
+};
+
+/// Control points of the color palette, as seen by the fragment shader. Colors are padded to 16
+/// Bytes each, since this is the required stride of arrays in uniforms.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct PaletteArgs {
+    len: i32,
+    /// `1` if the colors are inverted, `0` otherwise.
+    invert: i32,
+    _padding: [i32; 2],
+    colors: [[f32; 4]; MAX_PALETTE_COLORS],
+}
+
+impl PaletteArgs {
+    /// `colors` must not be empty and hold at most [`MAX_PALETTE_COLORS`] elements.
+    pub fn new(colors: &[[f32; 3]]) -> Self {
+        debug_assert!(!colors.is_empty() && colors.len() <= MAX_PALETTE_COLORS);
+        let mut padded = [[0.; 4]; MAX_PALETTE_COLORS];
+        for (dest, &[r, g, b]) in padded.iter_mut().zip(colors) {
+            *dest = [r, g, b, 1.];
+        }
+        PaletteArgs {
+            len: colors.len() as i32,
+            invert: 0,
+            _padding: [0; 2],
+            colors: padded,
+        }
+    }
+
+    /// Inverts every color of the output, if `invert` is `true`.
+    pub fn set_inverted(&mut self, invert: bool) {
+        self.invert = invert.into();
+    }
+
+    /// `true` if every color of the output is inverted.
+    pub fn invert(&self) -> bool {
+        self.invert != 0
+    }
+}
+
+/// Arguments of the fragment shader. 64 Bytes in size for compatibility with webGL.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct FragmentArgs {
+    iterations: i32,
+    supersampling: i32,
+    coloring: i32,
+    gamma: f32,
+    /// Position of the camera, rounded to single precision.
+    center_hi: [f32; 2],
+    /// Rounding error of `center_hi`.
+    center_lo: [f32; 2],
+    /// Shifts the palette lookup of escaping points, to cycle colors over time.
+    color_offset: f32,
+    /// See [`Equalization`].
+    equalization: i32,
+    interior: i32,
+    escape_radius: f32,
+    color_mapping: i32,
+    smooth_coloring: i32,
+    padding: [i32; 2],
+}
+
+/// Smallest escape radius which guarantees that sequences exceeding it diverge.
+pub const DEFAULT_ESCAPE_RADIUS: f32 = 2.;
+
+/// Decides how the fragment shader derives the color of a point from its sequence.
+#[derive(Clone, Copy, Debug)]
+pub struct ColorSettings {
+    /// Whether pixels are colored by escape time, distance estimation or orbit traps.
+    pub coloring: ColoringMode,
+    /// How points which never escape are colored.
+    pub interior: InteriorMode,
+    /// Colors are raised to the power of `1 / gamma`.
+    pub gamma: f32,
+    /// Shifts the palette. `0` leaves it in place and `1` shifts it by its entire length.
+    pub color_offset: f32,
+    /// Whether escape values are spread evenly across the palette using histogram equalization.
+    pub equalization: Equalization,
+    /// Sequences are considered escaping once their magnitude exceeds this radius.
+    pub escape_radius: f32,
+    /// How escape values are mapped to positions in the palette.
+    pub mapping: ColorMapping,
+    /// Escape values are continuous, rather than whole numbers of iterations, which would show
+    /// bands of color.
+    pub smooth: bool,
+}
+
+impl Default for ColorSettings {
+    fn default() -> Self {
+        ColorSettings {
+            coloring: ColoringMode::EscapeTime,
+            interior: InteriorMode::Black,
+            gamma: 1.,
+            color_offset: 0.,
+            equalization: Equalization::Off,
+            escape_radius: DEFAULT_ESCAPE_RADIUS,
+            mapping: ColorMapping::Linear,
+            smooth: true,
+        }
+    }
+}
+
+/// Whether and how the fragment shader applies histogram equalization.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Equalization {
+    /// Escape values are mapped to the palette linearly.
+    Off,
+    /// Escape values are mapped through their cumulative distribution before looking up the
+    /// palette.
+    On,
+    /// Histogram pass. Instead of a color, the shader writes the raw escape value into the red
+    /// channel, and whether the point escaped at all into the alpha channel.
+    Histogram,
+}
+
+impl Equalization {
+    /// Integer identifying the equalization in the shader.
+    fn mode(self) -> i32 {
+        match self {
+            Equalization::Off => 0,
+            Equalization::On => 1,
+            Equalization::Histogram => 2,
+        }
+    }
+}
+
+impl FragmentArgs {
+    /// `supersampling` is the number of samples per pixel along each axis. `center` is the
+    /// position of the camera.
+    pub fn new(
+        iterations: i32,
+        supersampling: u32,
+        center: [f64; 2],
+        colors: &ColorSettings,
+    ) -> Self {
+        let [x_hi, x_lo] = split_f64(center[0]);
+        let [y_hi, y_lo] = split_f64(center[1]);
+        FragmentArgs {
+            iterations,
+            supersampling: supersampling as i32,
+            coloring: colors.coloring.mode(),
+            gamma: colors.gamma,
+            center_hi: [x_hi, y_hi],
+            center_lo: [x_lo, y_lo],
+            color_offset: colors.color_offset,
+            equalization: colors.equalization.mode(),
+            interior: colors.interior.mode(),
+            escape_radius: colors.escape_radius,
+            color_mapping: colors.mapping.mode(),
+            smooth_coloring: colors.smooth.into(),
+            padding: [0; 2],
+        }
+    }
+}
+
+/// Splits `value` into a pair of single precision floats, whose sum approximates `value` with
+/// roughly twice the precision of a single float. This is the representation used for the double
+/// single arithmetic in the fragment shader.
+fn split_f64(value: f64) -> [f32; 2] {
+    let hi = value as f32;
+    let lo = (value - f64::from(hi)) as f32;
+    [hi, lo]
+}
+
+/// Arguments describing the kind of fractal, as seen by the fragment shader. 16 Bytes in size for
+/// compatibility with webGL.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, Pod, Zeroable)]
+pub struct FractalArgs {
+    mode: i32,
+    power: f32,
+    c: [f32; 2],
+}
+
+impl FractalArgs {
+    /// `power` is the exponent `z` is raised to in each iteration.
+    pub fn new(kind: &FractalKind, power: f32) -> Self {
+        FractalArgs {
+            mode: kind.mode(),
+            power,
+            c: kind.c(),
+        }
+    }
+}
+
 /// Vertex as used in the vertex buffer of our canvas shader.
 #[repr(C)]
 #[derive(Copy, Clone, Debug, Pod, Zeroable)]
@@ -70,7 +315,7 @@ impl Vertex {
 }
 
 /// Inverse view matrix padded to a multitude of 16bytes for compatibility with webGL.
-pub fn inv_view_to_bytes(inv_view: &[[f32;2]; 3]) -> [u8; 64] {
+pub fn inv_view_to_bytes(inv_view: &[[f32; 2]; 3]) -> [u8; 64] {
     // Only way to reliable get the matrix to the shader for webGL is to put it into a 4x4 matrix.
     // There should be other ways, but empirically this is had been the only one working for me
 
@@ -96,8 +341,6 @@ pub fn inv_view_to_bytes(inv_view: &[[f32;2]; 3]) -> [u8; 64] {
         [inv_view[2][0], inv_view[2][1], 0., 0.],
     ];
 
-
-
     let mut bytes = [0; 64];
     bytes.copy_from_slice(bytemuck::cast_slice(&four_by_four));
     bytes
@@ -126,21 +369,42 @@ pub fn inv_view_uniform(
     (layout, buffer, bind_group)
 }
 
-pub fn iterations_uniform(
+/// Returns the layout, buffer and bind group for the arguments of the fragment shader in one go.
+pub fn fragment_uniform(
     device: &Device,
-    iterations: i32,
+    args: &FragmentArgs,
 ) -> (BindGroupLayout, Buffer, BindGroup) {
-    let mut iterations_padded = [0; 4];
-    iterations_padded[0] = iterations;
+    let layout = device.create_bind_group_layout(&FRAGMENT_LAYOUT);
+    let buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Fragment Arguments Buffer"),
+        contents: bytemuck::bytes_of(args),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("Fragment Arguments Bind Group"),
+        layout: &layout,
+        entries: &[BindGroupEntry {
+            binding: 0,
+            resource: buffer.as_entire_binding(),
+        }],
+    });
+    (layout, buffer, bind_group)
+}
 
-    let layout = device.create_bind_group_layout(&ITERATIONS_LAYOUT);
+/// Returns the layout, buffer and bind group for the arguments describing the kind of fractal in
+/// one go.
+pub fn fractal_uniform(
+    device: &Device,
+    kind: &FractalKind,
+) -> (BindGroupLayout, Buffer, BindGroup) {
+    let layout = device.create_bind_group_layout(&FRACTAL_LAYOUT);
     let buffer = device.create_buffer_init(&BufferInitDescriptor {
-        label: Some("Iterations Buffer"),
-        contents: bytemuck::cast_slice(&iterations_padded),
+        label: Some("Fractal Buffer"),
+        contents: bytemuck::bytes_of(&FractalArgs::new(kind, 2.)),
         usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
     });
     let bind_group = device.create_bind_group(&BindGroupDescriptor {
-        label: Some("Iterations Bind Group"),
+        label: Some("Fractal Bind Group"),
         layout: &layout,
         entries: &[BindGroupEntry {
             binding: 0,
@@ -149,3 +413,52 @@ pub fn iterations_uniform(
     });
     (layout, buffer, bind_group)
 }
+
+/// Returns the layout, the buffers for the color palette and the distribution of escape values,
+/// and their bind group in one go. The distribution starts out as [`EqualizationArgs::identity`].
+pub fn palette_uniform(
+    device: &Device,
+    palette: &PaletteArgs,
+) -> (BindGroupLayout, Buffer, Buffer, BindGroup) {
+    let layout = device.create_bind_group_layout(&PALETTE_LAYOUT);
+    let buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Palette Buffer"),
+        contents: bytemuck::bytes_of(palette),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+    let equalization_buffer = device.create_buffer_init(&BufferInitDescriptor {
+        label: Some("Equalization Buffer"),
+        contents: bytemuck::bytes_of(&EqualizationArgs::identity()),
+        usage: BufferUsages::UNIFORM | BufferUsages::COPY_DST,
+    });
+    let bind_group = device.create_bind_group(&BindGroupDescriptor {
+        label: Some("Palette Bind Group"),
+        layout: &layout,
+        entries: &[
+            BindGroupEntry {
+                binding: 0,
+                resource: buffer.as_entire_binding(),
+            },
+            BindGroupEntry {
+                binding: 1,
+                resource: equalization_buffer.as_entire_binding(),
+            },
+        ],
+    });
+    (layout, buffer, equalization_buffer, bind_group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::split_f64;
+
+    #[test]
+    fn split_preserves_precision_beyond_single_float() {
+        let value = -0.743_643_887_037_151;
+
+        let [hi, lo] = split_f64(value);
+
+        assert_eq!(f64::from(hi), f64::from(value as f32));
+        assert!((f64::from(hi) + f64::from(lo) - value).abs() < 1e-14);
+    }
+}
@@ -1,5 +1,6 @@
 /// Inverse view matrix with padding so its size is a multitude of 16 Bytes. This is required for
-/// running this shader with WebGL
+/// running this shader with WebGL. Only scale is applied in the vertex shader, the translation is
+/// added in the fragment shader with higher precision.
 struct VertexArgs {
     inv_view: mat4x4<f32>,
 }
@@ -10,85 +11,514 @@ var<uniform> VERTEX_ARGS: VertexArgs;
 /// Uniform arguments for fragment shader, padedd to 16Bytes alignment for wegGL compatibility
 struct FragmentArgs {
     iterations: i32,
+    /// Number of samples per pixel along each axis. The escape value is computed on a grid of
+    /// `supersampling * supersampling` points and the colors are averaged.
+    supersampling: i32,
+    /// 0 = escape time, 1 = distance estimation, 2 = orbit trap
+    coloring: i32,
+    /// Colors are raised to the power of `1 / gamma` before they are written to the output.
+    gamma: f32,
+    /// Camera position, rounded to single precision
+    center_hi: vec2<f32>,
+    /// Rounding error of `center_hi`
+    center_lo: vec2<f32>,
+    /// Shifts the palette lookup of escaping points by this fraction of its length, to cycle colors
+    /// over time.
+    color_offset: f32,
+    /// 0 = off, 1 = map escape values through their cumulative distribution, 2 = histogram pass
+    /// writing the raw escape values
+    equalization: i32,
+    /// Coloring of points which never escape. 0 = black, 1 = final magnitude, 2 = orbit average
+    interior: i32,
+    /// Sequences whose magnitude exceeds this radius are considered escaping.
+    escape_radius: f32,
+    /// Mapping of escape values to the palette. 0 = linear, 1 = logarithmic
+    color_mapping: i32,
+    /// 1 = continuous escape values, 0 = whole numbers of iterations, which shows bands of color
+    smooth_coloring: i32,
     padding_0: i32,
     padding_1: i32,
-    padding_2: i32,
 }
 
 @group(1) @binding(0)
 var<uniform> FRAGMENT_ARGS: FragmentArgs;
 
+/// Kind of fractal. Padded to 16 Bytes for webGL compatibility.
+struct FractalArgs {
+    /// 0 = Mandelbrot, 1 = Julia, 2 = Burning Ship, 3 = Tricorn, 4 = Newton
+    mode: i32,
+    /// Exponent `z` is raised to in each iteration
+    power: f32,
+    /// Constant of the Julia set
+    c: vec2<f32>,
+}
+
+@group(2) @binding(0)
+var<uniform> FRACTAL_ARGS: FractalArgs;
+
+/// Control points of the color palette. Colors are padded to 16 Bytes, since this is the required
+/// stride of arrays in uniforms.
+struct Palette {
+    /// Number of colors in use
+    len: i32,
+    /// 1 = output colors are inverted, 0 = they are not
+    invert: i32,
+    padding_0: i32,
+    padding_1: i32,
+    colors: array<vec4<f32>, 8>,
+}
+
+@group(3) @binding(0)
+var<uniform> PALETTE: Palette;
+
+/// Cumulative distribution of the escape values in the current view, sampled at 256 evenly spaced
+/// escape values. Packed into vectors, since 16 Bytes is the required stride of arrays in uniforms.
+struct Equalization {
+    cdf: array<vec4<f32>, 64>,
+}
+
+@group(3) @binding(1)
+var<uniform> EQUALIZATION: Equalization;
+
 struct VertexInput {
     @location(0) position: vec2<f32>,
 };
 
 struct VertexOutput {
     @builtin(position) clip_position: vec4<f32>,
-    @location(0) coords: vec2<f32>,
+    /// Offset from the camera position in the complex plane
+    @location(0) offset: vec2<f32>,
 };
 
+// Double single arithmetic. A number is represented as an unevaluated sum of two f32 `(hi, lo)`,
+// with `lo` holding the rounding error of `hi`. This gives us roughly twice the precision of a
+// single f32, allowing us to zoom much deeper before the picture becomes blocky.
+
+/// Sum of `a` and `b`, with rounding error. Requires `abs(a) >= abs(b)`.
+fn quick_two_sum(a: f32, b: f32) -> vec2<f32> {
+    let s = a + b;
+    let e = b - (s - a);
+    return vec2<f32>(s, e);
+}
+
+/// Sum of `a` and `b`, with rounding error.
+fn two_sum(a: f32, b: f32) -> vec2<f32> {
+    let s = a + b;
+    let v = s - a;
+    let e = (a - (s - v)) + (b - v);
+    return vec2<f32>(s, e);
+}
+
+/// Splits `a` into two halves with at most 12 significant bits each, so their products are exact.
+fn split(a: f32) -> vec2<f32> {
+    let t = 4097.0 * a;
+    let hi = t - (t - a);
+    return vec2<f32>(hi, a - hi);
+}
+
+/// Product of `a` and `b`, with rounding error.
+fn two_prod(a: f32, b: f32) -> vec2<f32> {
+    let p = a * b;
+    let sa = split(a);
+    let sb = split(b);
+    let e = ((sa.x * sb.x - p) + sa.x * sb.y + sa.y * sb.x) + sa.y * sb.y;
+    return vec2<f32>(p, e);
+}
+
+fn ds_add(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    let s = two_sum(a.x, b.x);
+    return quick_two_sum(s.x, s.y + a.y + b.y);
+}
+
+fn ds_sub(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return ds_add(a, -b);
+}
+
+fn ds_abs(a: vec2<f32>) -> vec2<f32> {
+    if (a.x < 0.0) {
+        return -a;
+    }
+    return a;
+}
+
+fn ds_mul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    let p = two_prod(a.x, b.x);
+    return quick_two_sum(p.x, p.y + a.x * b.y + a.y * b.x);
+}
+
 @vertex
 fn vs_main(
     plane: VertexInput,
 ) -> VertexOutput {
     var out: VertexOutput;
     out.clip_position = vec4<f32>(plane.position, 0.0, 1.0);
-    // let inv_view = mat3x2(1.0, 0.0, 0.0, 1.0, -0.5, 0.0);
-    out.coords = (VERTEX_ARGS.inv_view * vec4<f32>(plane.position, 0.0, 1.0)).xy;
+    // Setting w to zero skips the translation. It is applied in the fragment shader.
+    out.offset = (VERTEX_ARGS.inv_view * vec4<f32>(plane.position, 0.0, 0.0)).xy;
     return out;
 }
 
 @fragment
 fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    // Distance between neighbouring pixels in the complex plane. Derivatives must be taken before
+    // any non-uniform control flow.
+    let pixel_dx = dpdx(in.offset);
+    let pixel_dy = dpdy(in.offset);
+    return pixel_color(in.offset, pixel_dx, pixel_dy);
+}
+
+/// Output color of the pixel centered `offset` away from the camera position in the complex plane.
+/// `pixel_dx` and `pixel_dy` are the offsets between the centers of neighbouring pixels along
+/// either axis of the output. Shared by the fragment and the compute entry point.
+fn pixel_color(offset: vec2<f32>, pixel_dx: vec2<f32>, pixel_dy: vec2<f32>) -> vec4<f32> {
+    let samples = FRAGMENT_ARGS.supersampling;
+    let pixel_size = max(length(pixel_dx), length(pixel_dy));
+    // The histogram pass reports raw escape values, which must neither be averaged nor encoded.
+    if (FRAGMENT_ARGS.equalization == 2) {
+        return color_at(offset, pixel_size);
+    }
+    if (samples <= 1) {
+        return output_color(color_at(offset, pixel_size));
+    }
+    var sum = vec4<f32>(0.0, 0.0, 0.0, 0.0);
+    for (var j = 0; j < samples; j++) {
+        for (var k = 0; k < samples; k++) {
+            // Centers of the cells of a regular grid spanning the pixel
+            let sub_x = (f32(k) + 0.5) / f32(samples) - 0.5;
+            let sub_y = (f32(j) + 0.5) / f32(samples) - 0.5;
+            sum += color_at(offset + sub_x * pixel_dx + sub_y * pixel_dy, pixel_size);
+        }
+    }
+    return output_color(sum / f32(samples * samples));
+}
+
+/// Inverts the color channels of `color`, if requested, and encodes them with the gamma of the
+/// output. Alpha is left untouched.
+fn output_color(color: vec4<f32>) -> vec4<f32> {
+    var rgb = color.rgb;
+    if (PALETTE.invert != 0) {
+        rgb = 1.0 - rgb;
+    }
+    return vec4<f32>(pow(rgb, vec3<f32>(1.0 / FRAGMENT_ARGS.gamma)), color.a);
+}
+
+/// Position of a point in the complex plane, in double single precision.
+struct Point {
+    x: vec2<f32>,
+    y: vec2<f32>,
+}
+
+/// The point `offset` away from the camera position in the complex plane.
+fn point_at(offset: vec2<f32>) -> Point {
+    let x = ds_add(
+        vec2<f32>(FRAGMENT_ARGS.center_hi.x, FRAGMENT_ARGS.center_lo.x),
+        vec2<f32>(offset.x, 0.0)
+    );
+    let y = ds_add(
+        vec2<f32>(FRAGMENT_ARGS.center_hi.y, FRAGMENT_ARGS.center_lo.y),
+        vec2<f32>(offset.y, 0.0)
+    );
+    return Point(x, y);
+}
+
+/// State of the sequence of a point, once it escaped or the iterations ran out.
+struct Orbit {
+    /// Iterations left when the sequence escaped. Zero if it never did.
+    remaining: i32,
+    /// Last element of the sequence
+    z: vec2<f32>,
+    /// Derivative of z with respect to the pixel position, only tracked for distance estimation.
+    dz: vec2<f32>,
+    /// Smallest distance between the orbit of z and the trap, formed by the real and imaginary
+    /// axes. Only tracked for orbit traps.
+    trap: f32,
+    /// Sum of the magnitudes of the elements of the sequence, only tracked for coloring the
+    /// interior by the orbit average.
+    orbit_sum: f32,
+}
+
+/// Color of the point `offset` away from the camera position in the complex plane. `pixel_size` is
+/// the distance between neighbouring pixels in the complex plane.
+fn color_at(offset: vec2<f32>, pixel_size: f32) -> vec4<f32> {
+    let point = point_at(offset);
+    if (FRACTAL_ARGS.mode == 4) {
+        return newton_color(vec2<f32>(point.x.x, point.y.x));
+    }
+    let orbit = iterate(point);
+    let i = orbit.remaining;
+    let z = orbit.z;
+    let iter = FRAGMENT_ARGS.iterations;
+    let distance_estimation = FRAGMENT_ARGS.coloring == 1;
+    let orbit_trap = FRAGMENT_ARGS.coloring == 2;
+
+    // Points which never escaped are part of the set. The histogram pass must report them as such.
+    let interior = i == 0;
+    if (interior && FRAGMENT_ARGS.interior != 0 && FRAGMENT_ARGS.equalization != 2 && !orbit_trap) {
+        return interior_color(z, orbit.orbit_sum / f32(iter));
+    }
+
+    if (distance_estimation) {
+        if (interior) {
+            return vec4<f32>(0.0, 0.0, 0.0, 1.0);
+        }
+        // Estimated distance to the set, in pixels. Pixels close to the boundary are bright, so
+        // even filaments thinner than a pixel become visible.
+        let r = length(z);
+        let distance = 0.5 * r * log(r) / length(orbit.dz) / pixel_size;
+        let brightness = 1.0 - sqrt(clamp(distance / 4.0, 0.0, 1.0));
+        return vec4<f32>(brightness, brightness, brightness, 1.0);
+    }
+
+    if (orbit_trap) {
+        // Points whose orbit comes close to the trap are colored with the last colors of the
+        // palette. Unlike escape time, this colors the interior of the set, too.
+        return palette_color(1.0 - sqrt(clamp(orbit.trap, 0.0, 1.0)));
+    }
+
+    // Smooth (continuous) escape value, to avoid visible bands between iteration counts. `i`
+    // counts the remaining iterations, so the usual `n + 1 - log_p(log_R(|z|))` for an escape
+    // radius `R` becomes `i - 1 + log_p(log_R(|z|))`. Points which never escaped keep the value
+    // zero.
+    var value = 0.0;
+    if (i != 0 && FRAGMENT_ARGS.smooth_coloring == 0) {
+        // Classic banded look
+        value = f32(i);
+    } else if (i != 0) {
+        value = clamp(f32(i) - 1.0 + escape_overshoot(z), 0.0, f32(iter));
+    }
+
+    var t = value / f32(iter);
+    if (FRAGMENT_ARGS.color_mapping == 1) {
+        // Both map zero to zero and the maximum number of iterations to one.
+        t = log2(1.0 + value) / log2(1.0 + f32(iter));
+    }
+    if (FRAGMENT_ARGS.equalization == 2) {
+        var escaped = 0.0;
+        if (i != 0) {
+            escaped = 1.0;
+        }
+        return vec4<f32>(t, 0.0, 0.0, escaped);
+    }
+    // Most convergent colors first. Points which never escaped keep the first color.
+    if (i != 0) {
+        if (FRAGMENT_ARGS.equalization == 1) {
+            t = equalize(t);
+        }
+        // Wrapping around only while cycling keeps the last color of the palette for `t = 1`.
+        if (FRAGMENT_ARGS.color_offset != 0.0) {
+            t = fract(t + FRAGMENT_ARGS.color_offset);
+        }
+    }
+    return palette_color(t);
+}
+
+/// Number of iterations after which the sequence of the point `offset` away from the camera
+/// position escapes, with a fractional part changing continuously between neighbouring points.
+/// For the Newton fractal, the number of steps it takes to converge to a root. `-1` for points
+/// which never escape or converge.
+fn smooth_iterations(offset: vec2<f32>) -> f32 {
+    let point = point_at(offset);
+    if (FRACTAL_ARGS.mode == 4) {
+        let root = newton_root(vec2<f32>(point.x.x, point.y.x));
+        if (root.x < 0) {
+            return -1.0;
+        }
+        return f32(root.y + 1);
+    }
+    let orbit = iterate(point);
+    if (orbit.remaining == 0) {
+        return -1.0;
+    }
+    let iter = FRAGMENT_ARGS.iterations;
+    let n = f32(iter - orbit.remaining) + 1.0 - escape_overshoot(orbit.z);
+    return clamp(n, 0.0, f32(iter));
+}
+
+/// `log_p(log_R(|z|))` for the element `z` a sequence escaped the radius `R` with, and the power
+/// `p` of the fractal. The fraction of an iteration by which the sequence overshot the escape
+/// radius.
+fn escape_overshoot(z: vec2<f32>) -> f32 {
+    let log_r = log2(length(z)) / log2(FRAGMENT_ARGS.escape_radius);
+    return log2(log_r) / log2(FRACTAL_ARGS.power);
+}
+
+/// Iterates the sequence of `point`, until it escapes or the iterations run out.
+fn iterate(point: Point) -> Orbit {
     // Find out how quickly the position in the complex plane
     // diverges.
-    let c = in.coords;
-    var z = vec2<f32>(0.0, 0.0);
+    var c_x = point.x;
+    var c_y = point.y;
+    var z_x = vec2<f32>(0.0, 0.0);
+    var z_y = vec2<f32>(0.0, 0.0);
+    if (FRACTAL_ARGS.mode == 1) {
+        // Julia: Start the sequence at the pixel, and hold the constant fixed.
+        z_x = point.x;
+        z_y = point.y;
+        c_x = vec2<f32>(FRACTAL_ARGS.c.x, 0.0);
+        c_y = vec2<f32>(FRACTAL_ARGS.c.y, 0.0);
+    }
+    let distance_estimation = FRAGMENT_ARGS.coloring == 1;
+    let orbit_trap = FRAGMENT_ARGS.coloring == 2;
+    // Smallest distance between the orbit of z and the trap, formed by the real and imaginary axes.
+    var trap = 1.0e20;
+    // Sum of the magnitudes of the elements of the sequence, only tracked for coloring the interior
+    // by the orbit average.
+    var orbit_sum = 0.0;
+    // Derivative of z with respect to the pixel position, only tracked for distance estimation.
+    // For Julia sets z starts at the pixel, for all others it starts at zero.
+    var dz = vec2<f32>(0.0, 0.0);
+    if (FRACTAL_ARGS.mode == 1) {
+        dz = vec2<f32>(1.0, 0.0);
+    }
+    // Distance estimation is more accurate if we let the sequence grow larger before stopping.
+    var bailout = FRAGMENT_ARGS.escape_radius * FRAGMENT_ARGS.escape_radius;
+    if (distance_estimation) {
+        bailout = max(bailout, 1.0e6);
+    }
     var i = 0;
     let iter = FRAGMENT_ARGS.iterations;
     for (i=iter; i != 0; i--){
-        let real = z.x * z.x - z.y * z.y + c.x;
-        let imag = 2.0 * z.x * z.y + c.y;
+        if (FRACTAL_ARGS.mode == 2) {
+            // Burning Ship: Fold z into the first quadrant before squaring it.
+            z_x = ds_abs(z_x);
+            z_y = ds_abs(z_y);
+        }
+        if (FRACTAL_ARGS.mode == 3) {
+            // Tricorn: Conjugate z before squaring it. Its derivative is conjugated alongside.
+            z_y = -z_y;
+            dz.y = -dz.y;
+        }
+        if (distance_estimation) {
+            // dz = p * z^(p-1) * dz (+ 1 if z depends on the pixel through c)
+            let z_f = vec2<f32>(z_x.x, z_y.x);
+            let p = FRACTAL_ARGS.power;
+            let r = length(z_f);
+            var z_p_1 = vec2<f32>(0.0, 0.0);
+            if (r > 0.0) {
+                let theta = atan2(z_f.y, z_f.x);
+                z_p_1 = pow(r, p - 1.0) * vec2<f32>(cos((p - 1.0) * theta), sin((p - 1.0) * theta));
+            }
+            dz = p * vec2<f32>(z_p_1.x * dz.x - z_p_1.y * dz.y, z_p_1.x * dz.y + z_p_1.y * dz.x);
+            if (FRACTAL_ARGS.mode != 1) {
+                dz.x += 1.0;
+            }
+        }
+        var real = vec2<f32>(0.0, 0.0);
+        var imag = vec2<f32>(0.0, 0.0);
+        if (FRACTAL_ARGS.power == 2.0) {
+            real = ds_add(ds_sub(ds_mul(z_x, z_x), ds_mul(z_y, z_y)), c_x);
+            imag = ds_add(ds_mul(vec2<f32>(2.0, 0.0), ds_mul(z_x, z_y)), c_y);
+        } else {
+            // Multibrot: Exponentiation in polar form, in single precision.
+            let p = FRACTAL_ARGS.power;
+            let r = length(vec2<f32>(z_x.x, z_y.x));
+            var z_p = vec2<f32>(0.0, 0.0);
+            if (r > 0.0) {
+                let theta = atan2(z_y.x, z_x.x);
+                z_p = pow(r, p) * vec2<f32>(cos(p * theta), sin(p * theta));
+            }
+            real = ds_add(vec2<f32>(z_p.x, 0.0), c_x);
+            imag = ds_add(vec2<f32>(z_p.y, 0.0), c_y);
+        }
+
+        z_x = real;
+        z_y = imag;
+        if (orbit_trap) {
+            trap = min(trap, min(abs(real.x), abs(imag.x)));
+        }
+        if (FRAGMENT_ARGS.interior == 2) {
+            orbit_sum += length(vec2<f32>(real.x, imag.x));
+        }
 
-        // Sequences with abs(z) > 2 will always diverge
-        if (real * real + imag * imag > 4.0) {
+        // Sequences with abs(z) > 2 will always diverge. Larger escape radii smooth the coloring.
+        // Single precision suffices for the test.
+        if (real.x * real.x + imag.x * imag.x > bailout) {
             break;
         }
+    }
+    return Orbit(i, vec2<f32>(z_x.x, z_y.x), dz, trap, orbit_sum);
+}
+
+/// Color of a point within the set, see `FragmentArgs.interior`. `z` is the last element of its
+/// sequence and `orbit_average` the average magnitude of all elements. Both stay below 2, since the
+/// sequence never escaped.
+fn interior_color(z: vec2<f32>, orbit_average: f32) -> vec4<f32> {
+    var magnitude = length(z);
+    if (FRAGMENT_ARGS.interior == 2) {
+        magnitude = orbit_average;
+    }
+    return palette_color(clamp(magnitude / 2.0, 0.0, 1.0));
+}
+
+/// Maps the escape value `t` through the cumulative distribution of escape values in the current
+/// view, so each color of the palette covers a similar number of pixels.
+fn equalize(t: f32) -> f32 {
+    let position = t * 255.0;
+    let index = min(i32(position), 254);
+    let blend = position - f32(index);
+    return mix(cdf_at(index), cdf_at(index + 1), blend);
+}
+
+/// Cumulative distribution of escape values at the `index`th of the 256 sample points.
+fn cdf_at(index: i32) -> f32 {
+    return EQUALIZATION.cdf[index / 4][index % 4];
+}
 
-        z.x = real;
-        z.y = imag;
+/// Interpolates the palette at `t` between `0` (first color) and `1` (last color). The control
+/// points are spaced evenly.
+fn palette_color(t: f32) -> vec4<f32> {
+    if (PALETTE.len == 1) {
+        return PALETTE.colors[0];
     }
-    let divergence = f32(i) / f32(iter);
+    let position = t * f32(PALETTE.len - 1);
+    let index = min(i32(position), PALETTE.len - 2);
+    let blend = position - f32(index);
+    return mix(PALETTE.colors[index], PALETTE.colors[index + 1], blend);
+}
 
-    // if i == 0 {
-    //     return vec4<f32> (0.,0.,0.,1.);
-    // }
+/// Product of the complex numbers `a` and `b`.
+fn c_mul(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return vec2<f32>(a.x * b.x - a.y * b.y, a.x * b.y + a.y * b.x);
+}
+
+/// Quotient of the complex numbers `a` and `b`.
+fn c_div(a: vec2<f32>, b: vec2<f32>) -> vec2<f32> {
+    return c_mul(a, vec2<f32>(b.x, -b.y)) / dot(b, b);
+}
 
-    // Most convergent colors first
-    let colors = array(
-        vec4<f32>(0.,0.,0.,1.),
-        vec4<f32>(0.,1.,0.,1.),
-        vec4<f32>(1.,0.,0.,1.),
-        vec4<f32>(0.,0.,1.,1.),
+/// Color of the point `z` in the Newton fractal of `z^3 - 1`. Points are colored by the root the
+/// Newton-Raphson iteration starting at them converges to, and darkened by the number of steps it
+/// takes. Single precision suffices, since the basins do not reward deep zooms the way the
+/// Mandelbrot set does.
+fn newton_color(start: vec2<f32>) -> vec4<f32> {
+    let root = newton_root(start);
+    if (root.x < 0) {
+        return palette_color(0.0);
+    }
+    // Skip the first color of the palette, which is reserved for the points which never converge.
+    let color = palette_color(f32(root.x + 1) / 3.0);
+    let shade = pow(0.92, f32(root.y));
+    return vec4<f32>(color.rgb * shade, color.a);
+}
+
+/// Index of the root of `z^3 - 1` the Newton-Raphson iteration starting at `start` converges to,
+/// and the index of the step it arrives there. The root index is `-1` if the iteration does not
+/// converge.
+fn newton_root(start: vec2<f32>) -> vec2<i32> {
+    var roots = array<vec2<f32>, 3>(
+        vec2<f32>(1.0, 0.0),
+        vec2<f32>(-0.5, 0.8660254),
+        vec2<f32>(-0.5, -0.8660254),
     );
-    // First half go into the first blend
-    let end_first_blend = iter / 2; // The last color also gets the remainder
-    let end_second_blend = iter / 4 + end_first_blend; // The last color also gets the remainder
-    var first_color = vec4(0.,0.,0.,0.);
-    var second_color = vec4(0.,0.,0.,0.);
-    var blend = 0.0;
-    if (i < end_first_blend) {
-        first_color = colors[0];
-        second_color = colors[1];
-        blend = f32(i) / f32(end_first_blend);
-    } else if (i < end_second_blend) {
-        first_color = colors[1];
-        second_color = colors[2];
-        blend = f32(i - end_first_blend) / f32(end_second_blend - end_first_blend);
-    } else {
-        first_color = colors[2];
-        second_color = colors[3];
-        blend = f32(i - end_second_blend) / f32(iter - end_second_blend);
-    }
-    return (1. - blend) * first_color + blend * second_color;
-}
\ No newline at end of file
+    var z = start;
+    for (var n = 0; n < FRAGMENT_ARGS.iterations; n++) {
+        let z_2 = c_mul(z, z);
+        z -= c_div(c_mul(z_2, z) - vec2<f32>(1.0, 0.0), 3.0 * z_2);
+        for (var k = 0; k < 3; k++) {
+            let delta = z - roots[k];
+            if (dot(delta, delta) < 1.0e-6) {
+                return vec2<i32>(k, n);
+            }
+        }
+    }
+    return vec2<i32>(-1, FRAGMENT_ARGS.iterations);
+}
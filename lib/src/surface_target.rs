@@ -0,0 +1,89 @@
+use std::sync::Arc;
+
+use raw_window_handle::{
+    HasRawDisplayHandle, HasRawWindowHandle, RawDisplayHandle, RawWindowHandle,
+};
+
+/// Anything providing raw handles to a native window, e.g. a [`winit::window::Window`]. Implement
+/// [`HasRawWindowHandle`] and [`HasRawDisplayHandle`] to link a canvas to windows of other
+/// toolkits or raw handles.
+pub trait WindowHandle: HasRawWindowHandle + HasRawDisplayHandle {}
+
+impl<T> WindowHandle for T where T: HasRawWindowHandle + HasRawDisplayHandle + ?Sized {}
+
+/// The window a [`crate::Canvas`] presents its frames to.
+///
+/// `'w` is the lifetime of a borrowed window. The canvas keeps the target alive and can not
+/// outlive it, so the window is guaranteed to be valid as long as the canvas draws to it. Pass the
+/// window in an [`Arc`] to share ownership with the canvas instead, e.g. if the canvas is moved
+/// into an event loop together with the window. This yields a `SurfaceTarget<'static>`.
+///
+/// Windows of toolkits other than winit work just the same, as long as they implement the traits of
+/// [`raw_window_handle`] in the version re-exported by this crate (0.5, which is the one wgpu
+/// 0.15 understands). Handles of other versions, e.g. `raw-window-handle` 0.6, can be converted
+/// field by field and passed to [`SurfaceTarget::from_raw`].
+pub struct SurfaceTarget<'w> {
+    window: Box<dyn WindowHandle + 'w>,
+}
+
+impl SurfaceTarget<'static> {
+    /// Target a window identified solely by its raw handles, e.g. one created by a toolkit which
+    /// does not implement [`HasRawWindowHandle`] itself.
+    ///
+    /// # Safety
+    ///
+    /// Both handles must be valid and stay valid for as long as the canvas created from this
+    /// target exists.
+    pub unsafe fn from_raw(window: RawWindowHandle, display: RawDisplayHandle) -> Self {
+        SurfaceTarget {
+            window: Box::new(RawHandles { window, display }),
+        }
+    }
+}
+
+impl<'w> SurfaceTarget<'w> {
+    pub(crate) fn window(&self) -> &(dyn WindowHandle + 'w) {
+        &*self.window
+    }
+}
+
+impl<'w, W> From<&'w W> for SurfaceTarget<'w>
+where
+    W: WindowHandle + ?Sized,
+{
+    fn from(window: &'w W) -> Self {
+        SurfaceTarget {
+            window: Box::new(window),
+        }
+    }
+}
+
+impl<'w, W> From<Arc<W>> for SurfaceTarget<'w>
+where
+    W: WindowHandle + ?Sized + 'w,
+{
+    fn from(window: Arc<W>) -> Self {
+        SurfaceTarget {
+            window: Box::new(window),
+        }
+    }
+}
+
+/// Raw handles of a window, whose validity has been promised by the caller of
+/// [`SurfaceTarget::from_raw`].
+struct RawHandles {
+    window: RawWindowHandle,
+    display: RawDisplayHandle,
+}
+
+unsafe impl HasRawWindowHandle for RawHandles {
+    fn raw_window_handle(&self) -> RawWindowHandle {
+        self.window
+    }
+}
+
+unsafe impl HasRawDisplayHandle for RawHandles {
+    fn raw_display_handle(&self) -> RawDisplayHandle {
+        self.display
+    }
+}
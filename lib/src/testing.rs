@@ -0,0 +1,90 @@
+//! Helpers for comparing rendered pictures against reference images, to catch visual regressions.
+
+use wgpu::{Backends, BufferAsyncError, CommandEncoderDescriptor, Device, Queue, Texture};
+
+use crate::{capture::read_rgba, Camera, Canvas, CanvasError, FractalKind};
+
+/// Renders the fractal as seen through `camera` into a fresh headless canvas of `width` times
+/// `height` pixels. Returns tightly packed RGBA8 bytes, row by row starting at the top left.
+///
+/// Every call starts from the default settings of the canvas, so the result only depends on the
+/// arguments and the adapter, not on anything rendered before. Fails if no adapter is available,
+/// which callers may want to treat as a reason to skip the test.
+pub async fn render_snapshot(
+    width: u32,
+    height: u32,
+    camera: &Camera,
+    iterations: i32,
+    kind: &FractalKind,
+) -> Result<Vec<u8>, CanvasError> {
+    let canvas = Canvas::new_headless(width, height, Backends::all()).await?;
+    let pixels = canvas
+        .capture_frame(camera, iterations, kind)
+        .expect("Reading back the snapshot from the GPU must succeed");
+    Ok(pixels)
+}
+
+/// Copies the pixels of `texture` to the CPU, e.g. after drawing into it with
+/// [`Canvas::render_into`]. The texture must hold four bytes per pixel and support
+/// [`wgpu::TextureUsages::COPY_SRC`]. Returns tightly packed RGBA8 bytes, row by row starting at the
+/// top left.
+///
+/// Blocks until the GPU finished all work submitted to `queue` before.
+pub fn read_texture(
+    device: &Device,
+    queue: &Queue,
+    texture: &Texture,
+) -> Result<Vec<u8>, BufferAsyncError> {
+    let encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+        label: Some("Texture Readback Encoder"),
+    });
+    read_rgba(
+        device,
+        queue,
+        encoder,
+        texture,
+        texture.width(),
+        texture.height(),
+        texture.format(),
+    )
+}
+
+/// Number of pixels in which any channel of `actual` and `expected` differs by more than
+/// `tolerance`. Both are tightly packed RGBA8 bytes of pictures with the same size.
+///
+/// Different adapters do not round floating point operations in exactly the same way, so a few
+/// pixels, e.g. close to the border of the set, are expected to differ between machines.
+///
+/// # Panics
+///
+/// If `actual` and `expected` differ in length.
+pub fn differing_pixels(actual: &[u8], expected: &[u8], tolerance: u8) -> usize {
+    assert_eq!(
+        expected.len(),
+        actual.len(),
+        "Snapshot and reference must have the same size"
+    );
+    actual
+        .chunks_exact(4)
+        .zip(expected.chunks_exact(4))
+        .filter(|(a, e)| {
+            a.iter()
+                .zip(e.iter())
+                .any(|(a, e)| a.abs_diff(*e) > tolerance)
+        })
+        .count()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::differing_pixels;
+
+    #[test]
+    fn small_deviations_are_tolerated() {
+        let expected = [10, 20, 30, 255, 0, 0, 0, 255];
+        let actual = [12, 20, 29, 255, 0, 9, 0, 255];
+
+        assert_eq!(1, differing_pixels(&actual, &expected, 2));
+        assert_eq!(0, differing_pixels(&actual, &expected, 9));
+    }
+}
@@ -0,0 +1,689 @@
+use std::sync::Arc;
+
+use fractal_wgpu_lib::{
+    testing::{differing_pixels, read_texture},
+    Backends, Camera, Canvas, CanvasBuilder, CanvasError, Color, ColorMapping, FitMode,
+    FractalKind, InteriorMode, PipelineMode, RenderState, FIRE_PALETTE, GRAYSCALE_PALETTE,
+    PRESET_PALETTES,
+};
+
+/// Canvas rendering into an offscreen texture, or `None` if the test should be skipped.
+fn headless(width: u32, height: u32) -> Option<Canvas<'static>> {
+    skip_without_adapter(pollster::block_on(Canvas::new_headless(
+        width,
+        height,
+        Backends::all(),
+    )))
+}
+
+/// Device and queue owned by the test rather than a canvas, or `None` if the test should be
+/// skipped.
+fn shared_device() -> Option<(Arc<wgpu::Device>, Arc<wgpu::Queue>)> {
+    let adapter = adapter()?;
+    let (device, queue) =
+        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+            .unwrap();
+    Some((Arc::new(device), Arc::new(queue)))
+}
+
+/// Adapter to request devices with custom settings from, or `None` if the test should be skipped.
+fn adapter() -> Option<wgpu::Adapter> {
+    let instance = wgpu::Instance::new(wgpu::InstanceDescriptor::default());
+    let adapter =
+        pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()));
+    if adapter.is_none() {
+        eprintln!("Skipping headless rendering test: No adapter available");
+    }
+    adapter
+}
+
+/// Machines running the tests may not have any graphics adapter. Rather than failing, we skip the
+/// rendering tests on these.
+fn skip_without_adapter(canvas: Result<Canvas<'static>, CanvasError>) -> Option<Canvas<'static>> {
+    match canvas {
+        Ok(canvas) => Some(canvas),
+        Err(error) => {
+            eprintln!("Skipping headless rendering test: {error:#}");
+            None
+        }
+    }
+}
+
+#[test]
+fn center_of_mandelbrot_set_is_black() {
+    const SIZE: u32 = 256;
+    let Some(canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+
+    let pixels = canvas
+        .capture_frame(&Camera::new(), 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    assert_eq!((SIZE * SIZE * 4) as usize, pixels.len());
+    // Initial camera is centered on -0.5, which is part of the Mandelbrot set
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[0, 0, 0, 255], &pixels[center..center + 4]);
+}
+
+#[test]
+fn frame_time_is_measured_once_two_frames_are_rendered() {
+    let Some(canvas) = headless(64, 64) else {
+        return;
+    };
+    let camera = Camera::new();
+
+    canvas
+        .render(&RenderState::new(camera, 64, FractalKind::Mandelbrot))
+        .unwrap();
+    assert!(canvas.last_frame_time().is_none());
+    canvas
+        .render(&RenderState::new(camera, 64, FractalKind::Mandelbrot))
+        .unwrap();
+
+    assert!(canvas.last_frame_time().is_some());
+}
+
+#[test]
+fn tiled_rendering_matches_rendering_in_one_go() {
+    let (width, height) = (96, 64);
+    let Some(canvas) = headless(width, height) else {
+        return;
+    };
+    let mut camera = Camera::new();
+    camera.zoom(2.);
+    let kind = FractalKind::Mandelbrot;
+
+    let whole = canvas.capture_frame(&camera, 64, &kind).unwrap();
+    // Tile size deliberately does not divide the picture size
+    let tiled = canvas
+        .render_tiled(&camera, 64, &kind, width, height, 40)
+        .unwrap();
+
+    assert_eq!(whole.len(), tiled.len());
+    // Tiles interpolate the position in the complex plane across a different triangle, which may
+    // round the last bit differently.
+    assert_eq!(0, differing_pixels(&tiled, &whole, 1));
+}
+
+#[test]
+fn broken_shader_leaves_canvas_intact() {
+    const SIZE: u32 = 64;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+
+    let result = pollster::block_on(canvas.reload_shader("fn fs_main( {"));
+
+    assert!(matches!(result, Err(CanvasError::ShaderCompile(_))));
+    let pixels = canvas
+        .capture_frame(&Camera::new(), 256, &FractalKind::Mandelbrot)
+        .unwrap();
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[0, 0, 0, 255], &pixels[center..center + 4]);
+}
+
+#[test]
+fn contain_fit_mode_leaves_bars_in_background_color() {
+    let (width, height) = (128, 64);
+    let Some(mut canvas) = headless(width, height) else {
+        return;
+    };
+    canvas.set_background(Color::RED);
+    canvas.set_fit_mode(FitMode::Contain);
+
+    let pixels = canvas
+        .capture_frame(&Camera::new(), 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    let pixel = |x: u32, y: u32| {
+        let offset = ((y * width + x) * 4) as usize;
+        &pixels[offset..offset + 4]
+    };
+    // Bars to the left and right of the square picture
+    assert_eq!(&[255, 0, 0, 255], pixel(0, height / 2));
+    assert_eq!(&[255, 0, 0, 255], pixel(width - 1, height / 2));
+    // Initial camera is centered on -0.5, which is part of the Mandelbrot set
+    assert_eq!(&[0, 0, 0, 255], pixel(width / 2, height / 2));
+}
+
+#[test]
+fn histogram_equalization_spreads_colors() {
+    const SIZE: u32 = 128;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    let camera = Camera::new();
+    let distinct_colors = |pixels: Vec<u8>| {
+        let mut colors: Vec<_> = pixels.chunks_exact(4).map(|pixel| pixel.to_vec()).collect();
+        colors.sort();
+        colors.dedup();
+        colors.len()
+    };
+
+    let plain = canvas
+        .capture_frame(&camera, 256, &FractalKind::Mandelbrot)
+        .unwrap();
+    canvas.set_histogram_equalization(true);
+    let equalized = canvas
+        .capture_frame(&camera, 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // Points within the set keep their color
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[0, 0, 0, 255], &equalized[center..center + 4]);
+    assert!(distinct_colors(equalized) > distinct_colors(plain));
+}
+
+#[test]
+fn interior_mode_colors_points_within_the_set() {
+    const SIZE: u32 = 64;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    canvas.set_interior_mode(InteriorMode::OrbitAverage);
+
+    let pixels = canvas
+        .capture_frame(&Camera::new(), 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // Initial camera is centered on -0.5, which is part of the Mandelbrot set
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_ne!(&[0, 0, 0, 255], &pixels[center..center + 4]);
+}
+
+#[test]
+fn render_into_texture_of_shared_device() {
+    const SIZE: u32 = 64;
+    let Some((device, queue)) = shared_device() else {
+        return;
+    };
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let canvas = pollster::block_on(Canvas::from_device(
+        SIZE,
+        SIZE,
+        device.clone(),
+        queue,
+        format,
+    ))
+    .unwrap();
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: SIZE,
+            height: SIZE,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    device.push_error_scope(wgpu::ErrorFilter::Validation);
+    canvas.render_into(&view, &Camera::new(), 64, &FractalKind::Mandelbrot);
+    let error = pollster::block_on(device.pop_error_scope());
+
+    assert!(error.is_none(), "{error:?}");
+    assert!(canvas.adapter_info().is_none());
+}
+
+#[test]
+fn larger_escape_radius_changes_smooth_coloring() {
+    const SIZE: u32 = 64;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    let camera = Camera::new();
+
+    let default_radius = canvas
+        .capture_frame(&camera, 256, &FractalKind::Mandelbrot)
+        .unwrap();
+    canvas.set_escape_radius(128.);
+    let large_radius = canvas
+        .capture_frame(&camera, 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // Points within the set stay black
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[0, 0, 0, 255], &large_radius[center..center + 4]);
+    assert_ne!(default_radius, large_radius);
+}
+
+#[test]
+fn color_inversion_turns_the_set_white() {
+    const SIZE: u32 = 64;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    canvas.set_color_inversion(true);
+    // Inversion must survive switching the palette
+    canvas.set_palette(FIRE_PALETTE).unwrap();
+
+    let pixels = canvas
+        .capture_frame(&Camera::new(), 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[255, 255, 255, 255], &pixels[center..center + 4]);
+}
+
+#[test]
+fn compute_pipeline_matches_fragment_pipeline() {
+    let (width, height) = (96, 64);
+    let Some(mut canvas) = headless(width, height) else {
+        return;
+    };
+    let mut camera = Camera::new();
+    camera.zoom(2.);
+    let kind = FractalKind::Mandelbrot;
+    canvas.set_supersampling(2);
+    // Bars next to the picture must be cleared in both modes
+    canvas.set_fit_mode(FitMode::Contain);
+    canvas.set_background(Color::RED);
+
+    let fragment = canvas.capture_frame(&camera, 64, &kind).unwrap();
+    if let Err(error) = pollster::block_on(canvas.set_pipeline_mode(PipelineMode::Compute)) {
+        eprintln!("Skipping compute pipeline test: {error:#}");
+        return;
+    }
+    let compute = canvas.capture_frame(&camera, 64, &kind).unwrap();
+
+    assert_eq!(fragment.len(), compute.len());
+    // Derivatives are approximated differently in both stages, which may shift colors slightly.
+    let differing = fragment
+        .iter()
+        .zip(&compute)
+        .filter(|(a, b)| a.abs_diff(**b) > 2)
+        .count();
+    assert_eq!(0, differing);
+}
+
+#[test]
+fn iterations_are_read_back_row_by_row() {
+    const ITERATIONS: i32 = 64;
+    // Not square, so rows and columns can not be mixed up.
+    let (width, height) = (48, 32);
+    let Some(mut canvas) = headless(width, height) else {
+        return;
+    };
+    // Off the real axis, since the set is symmetric to it and flipped rows would go unnoticed.
+    let camera = Camera::new_at(-0.5, 0.4, 1.);
+    let kind = FractalKind::Mandelbrot;
+    assert!(matches!(
+        canvas.read_iterations(&camera, ITERATIONS, &kind),
+        Err(CanvasError::NoComputePipeline)
+    ));
+    if let Err(error) = pollster::block_on(canvas.set_pipeline_mode(PipelineMode::Compute)) {
+        eprintln!("Skipping compute pipeline test: {error:#}");
+        return;
+    }
+
+    let iterations = canvas.read_iterations(&camera, ITERATIONS, &kind).unwrap();
+    let pixels = canvas.capture_frame(&camera, ITERATIONS, &kind).unwrap();
+
+    assert_eq!((width * height) as usize, iterations.len());
+    assert!(iterations
+        .iter()
+        .all(|&n| n == -1. || (0. ..=ITERATIONS as f32).contains(&n)));
+    // Points within the set are the ones drawn black.
+    let interior = iterations.iter().filter(|&&n| n == -1.).count();
+    assert!(interior > 0 && interior < iterations.len());
+    for (n, pixel) in iterations.iter().zip(pixels.chunks_exact(4)) {
+        if *n == -1. {
+            assert_eq!(&[0, 0, 0, 255], pixel);
+        }
+    }
+}
+
+#[test]
+fn log_color_mapping_changes_colors_far_from_the_set() {
+    const SIZE: u32 = 64;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    let camera = Camera::new();
+
+    let linear = canvas
+        .capture_frame(&camera, 256, &FractalKind::Mandelbrot)
+        .unwrap();
+    canvas.set_color_mapping(ColorMapping::Log);
+    let log = canvas
+        .capture_frame(&camera, 256, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // Points within the set stay black
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[0, 0, 0, 255], &log[center..center + 4]);
+    // The top left corner escapes after a few iterations, which linear mapping can hardly tell
+    // apart from the points within the set.
+    assert_ne!(&linear[0..4], &log[0..4]);
+}
+
+#[test]
+fn fractal_covers_every_pixel_of_odd_sized_canvases() {
+    for (width, height) in [(97, 13), (13, 97), (1, 1), (255, 256)] {
+        let Some(mut canvas) = headless(width, height) else {
+            return;
+        };
+        // Grayscale contains no red, so any red pixel has been left uncovered.
+        canvas.set_palette(GRAYSCALE_PALETTE).unwrap();
+        canvas.set_background(Color::RED);
+
+        let pixels = canvas
+            .capture_frame(&Camera::new(), 64, &FractalKind::Mandelbrot)
+            .unwrap();
+
+        let uncovered = pixels
+            .chunks_exact(4)
+            .filter(|pixel| *pixel == [255, 0, 0, 255])
+            .count();
+        assert_eq!(0, uncovered, "{width}x{height}");
+    }
+}
+
+#[test]
+fn multisampling_keeps_the_picture_within_the_fractal() {
+    let Some(mut canvas) = headless(64, 64) else {
+        return;
+    };
+    let camera = Camera::new();
+    let single_sample = canvas
+        .capture_frame(&camera, 64, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    assert!(matches!(
+        canvas.set_sample_count(3),
+        Err(CanvasError::UnsupportedSampleCount(3))
+    ));
+    if let Err(error) = canvas.set_sample_count(4) {
+        eprintln!("Skipping multisampling test: {error:#}");
+        return;
+    }
+    let multisampled = canvas
+        .capture_frame(&camera, 64, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // The fractal is computed once per pixel, so only the edges of the geometry could change. The
+    // picture covers the entire canvas, so it has none.
+    assert_eq!(single_sample, multisampled);
+}
+
+#[test]
+fn reduced_render_scale_keeps_picture_and_bars_in_place() {
+    let (width, height) = (96, 64);
+    let Some(mut canvas) = headless(width, height) else {
+        return;
+    };
+    canvas.set_fit_mode(FitMode::Contain);
+    canvas.set_background(Color::RED);
+
+    canvas.set_render_scale(0.5);
+    let pixels = canvas
+        .capture_frame(&Camera::new(), 64, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // Still scaled up to the full size of the canvas
+    assert_eq!((width * height * 4) as usize, pixels.len());
+    let pixel = |x: u32, y: u32| {
+        let start = ((y * width + x) * 4) as usize;
+        &pixels[start..start + 4]
+    };
+    // Left bar and the center of the Mandelbrot set
+    assert_eq!(&[255, 0, 0, 255], pixel(2, height / 2));
+    assert_eq!(&[0, 0, 0, 255], pixel(width / 2, height / 2));
+}
+
+#[test]
+fn split_screen_renders_each_half_like_a_canvas_of_half_the_width() {
+    const HALF: u32 = 64;
+    const HEIGHT: u32 = 48;
+    let Some((device, queue)) = shared_device() else {
+        return;
+    };
+    let format = wgpu::TextureFormat::Rgba8UnormSrgb;
+    let split = pollster::block_on(Canvas::from_device(
+        2 * HALF,
+        HEIGHT,
+        device.clone(),
+        queue.clone(),
+        format,
+    ))
+    .unwrap();
+    let half = pollster::block_on(Canvas::from_device(
+        HALF,
+        HEIGHT,
+        device.clone(),
+        queue.clone(),
+        format,
+    ))
+    .unwrap();
+    let left = RenderState::new(Camera::new(), 64, FractalKind::Mandelbrot);
+    let julia = FractalKind::Julia { c: [-0.8, 0.156] };
+    let right = RenderState::new(Camera::overview(&julia), 64, julia);
+    let texture = device.create_texture(&wgpu::TextureDescriptor {
+        label: None,
+        size: wgpu::Extent3d {
+            width: 2 * HALF,
+            height: HEIGHT,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format,
+        usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+        view_formats: &[],
+    });
+    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+    split.render_split_into(&view, &left, &right);
+
+    let pixels = read_texture(&device, &queue, &texture).unwrap();
+    let row_len = (2 * HALF * 4) as usize;
+    let half_of = |start: usize| -> Vec<u8> {
+        pixels
+            .chunks_exact(row_len)
+            .flat_map(|row| &row[start..start + (HALF * 4) as usize])
+            .copied()
+            .collect()
+    };
+    let expected_left = half
+        .capture_frame(&left.camera, left.iterations, &left.kind)
+        .unwrap();
+    let expected_right = half
+        .capture_frame(&right.camera, right.iterations, &right.kind)
+        .unwrap();
+
+    // Each half is drawn across a viewport of its own, which may round the last bit differently.
+    // Close to the border of the Julia set this may change the color of a few pixels.
+    assert_eq!(0, differing_pixels(&half_of(0), &expected_left, 1));
+    assert!(differing_pixels(&half_of((HALF * 4) as usize), &expected_right, 1) < 16);
+}
+
+#[test]
+fn disabling_back_face_culling_keeps_the_picture() {
+    let builder = CanvasBuilder::new(64, 64).back_face_culling(false);
+    let Some(mut canvas) = skip_without_adapter(pollster::block_on(builder.build_headless()))
+    else {
+        return;
+    };
+    let camera = Camera::new();
+
+    let unculled = canvas
+        .capture_frame(&camera, 64, &FractalKind::Mandelbrot)
+        .unwrap();
+    canvas.set_back_face_culling(true);
+    let culled = canvas
+        .capture_frame(&camera, 64, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // The triangle faces the viewer, so culling its back face never discards anything.
+    assert_eq!(unculled, culled);
+}
+
+#[test]
+fn banded_coloring_uses_one_color_per_iteration_count() {
+    const ITERATIONS: i32 = 16;
+    let Some(mut canvas) = headless(64, 64) else {
+        return;
+    };
+    let distinct_colors = |pixels: Vec<u8>| {
+        let mut colors: Vec<&[u8]> = pixels.chunks_exact(4).collect();
+        colors.sort_unstable();
+        colors.dedup();
+        colors.len()
+    };
+    let camera = Camera::new();
+
+    let smooth = canvas
+        .capture_frame(&camera, ITERATIONS, &FractalKind::Mandelbrot)
+        .unwrap();
+    canvas.set_smooth_coloring(false);
+    let banded = canvas
+        .capture_frame(&camera, ITERATIONS, &FractalKind::Mandelbrot)
+        .unwrap();
+
+    // One color for the set and one for each number of iterations it takes to escape.
+    assert!(distinct_colors(banded) <= ITERATIONS as usize + 1);
+    assert!(distinct_colors(smooth) > ITERATIONS as usize + 1);
+}
+
+#[test]
+fn invalid_powers_are_ignored() {
+    let Some(mut canvas) = headless(64, 64) else {
+        return;
+    };
+    let camera = Camera::new();
+    let kind = FractalKind::Mandelbrot;
+
+    let default = canvas.capture_frame(&camera, 64, &kind).unwrap();
+    for power in [1., 0.5, f32::NAN, f32::INFINITY] {
+        canvas.set_power(power);
+        assert_eq!(default, canvas.capture_frame(&camera, 64, &kind).unwrap());
+    }
+}
+
+#[test]
+fn zero_color_cycle_speed_keeps_the_last_color_of_the_palette() {
+    const SIZE: u32 = 64;
+    const ITERATIONS: i32 = 16;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    // Zoomed out far enough for the corners to escape in the first iteration, which maps them to
+    // the very end of the palette.
+    let camera = Camera::new_at(-0.5, 0., 0.1);
+    let kind = FractalKind::Mandelbrot;
+    canvas.set_smooth_coloring(false);
+    canvas.set_palette(&[[1., 0., 0.], [0., 0., 1.]]).unwrap();
+
+    let before = canvas.capture_frame(&camera, ITERATIONS, &kind).unwrap();
+    canvas.set_color_cycle_speed(1.);
+    canvas.set_color_cycle_speed(0.);
+    let after = canvas.capture_frame(&camera, ITERATIONS, &kind).unwrap();
+
+    // Blue, rather than wrapping around to the first color of the palette.
+    assert_eq!(&[0, 0, 255, 255], &before[..4]);
+    assert_eq!(before, after);
+}
+
+#[test]
+fn cycling_colors_shifts_the_palette_over_time() {
+    const SIZE: u32 = 64;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    let camera = Camera::new();
+    let kind = FractalKind::Mandelbrot;
+
+    let still = canvas.capture_frame(&camera, 64, &kind).unwrap();
+    // Slow enough not to complete an entire cycle, even on slow adapters.
+    canvas.set_color_cycle_speed(0.25);
+    std::thread::sleep(std::time::Duration::from_millis(200));
+    let cycled = canvas.capture_frame(&camera, 64, &kind).unwrap();
+
+    assert_ne!(still, cycled);
+}
+
+#[test]
+fn iterations_exceeding_the_storage_buffer_limit_are_an_error() {
+    let Some(adapter) = adapter() else {
+        return;
+    };
+    // Too small for the 48 times 32 values, let alone the header in front of them.
+    let limits = wgpu::Limits {
+        max_storage_buffer_binding_size: 4096,
+        ..adapter.limits()
+    };
+    let descriptor = wgpu::DeviceDescriptor {
+        limits,
+        ..Default::default()
+    };
+    let (device, queue) = pollster::block_on(adapter.request_device(&descriptor, None)).unwrap();
+    let mut canvas = pollster::block_on(Canvas::from_device(
+        48,
+        32,
+        Arc::new(device),
+        Arc::new(queue),
+        wgpu::TextureFormat::Rgba8UnormSrgb,
+    ))
+    .unwrap();
+    if let Err(error) = pollster::block_on(canvas.set_pipeline_mode(PipelineMode::Compute)) {
+        eprintln!("Skipping compute pipeline test: {error:#}");
+        return;
+    }
+
+    let result = canvas.read_iterations(&Camera::new(), 64, &FractalKind::Mandelbrot);
+
+    assert!(matches!(
+        result,
+        Err(CanvasError::BufferTooLarge {
+            size: 6152,
+            max_size: 4096
+        })
+    ));
+}
+
+#[test]
+fn thumbnail_shows_the_view_at_the_given_size() {
+    const SIZE: u32 = 32;
+    // Different size than the thumbnail, which must not matter.
+    let Some(canvas) = headless(96, 64) else {
+        return;
+    };
+
+    let pixels = canvas
+        .render_thumbnail(&RenderState::default(), SIZE)
+        .unwrap();
+
+    assert_eq!((SIZE * SIZE * 4) as usize, pixels.len());
+    // Initial camera is centered on -0.5, which is part of the Mandelbrot set
+    let center = ((SIZE / 2 * SIZE + SIZE / 2) * 4) as usize;
+    assert_eq!(&[0, 0, 0, 255], &pixels[center..center + 4]);
+}
+
+#[test]
+fn render_state_switches_to_its_preset_palette() {
+    const SIZE: u32 = 32;
+    let Some(mut canvas) = headless(SIZE, SIZE) else {
+        return;
+    };
+    let state = RenderState::default();
+    canvas.set_palette(FIRE_PALETTE).unwrap();
+    let fire = canvas.render_thumbnail(&state, SIZE).unwrap();
+
+    let preset_default = canvas
+        .render_thumbnail(&state.with_palette(0), SIZE)
+        .unwrap();
+    let preset_fire = canvas
+        .render_thumbnail(&state.with_palette(3), SIZE)
+        .unwrap();
+
+    assert_eq!(FIRE_PALETTE, PRESET_PALETTES[3]);
+    assert_ne!(fire, preset_default);
+    assert_eq!(fire, preset_fire);
+}
@@ -0,0 +1,60 @@
+//! Compares rendered pictures against reference images committed to the repository. Run with
+//! `UPDATE_SNAPSHOTS=1` to replace the reference images after an intended change of the picture.
+
+use std::{env, path::PathBuf};
+
+use fractal_wgpu_lib::{
+    testing::{differing_pixels, render_snapshot},
+    Camera, FractalKind,
+};
+
+const SIZE: u32 = 64;
+
+/// Maximum deviation of a single color channel, to account for different rounding on different
+/// adapters.
+const TOLERANCE: u8 = 8;
+
+/// Fraction of pixels allowed to exceed the tolerance, e.g. because they are right on the border of
+/// the set and flip between escaping and not escaping.
+const MAX_DIFFERING_FRACTION: f64 = 0.01;
+
+#[test]
+fn default_mandelbrot_view_matches_reference() {
+    let pixels = match pollster::block_on(render_snapshot(
+        SIZE,
+        SIZE,
+        &Camera::new(),
+        256,
+        &FractalKind::Mandelbrot,
+    )) {
+        Ok(pixels) => pixels,
+        Err(error) => {
+            eprintln!("Skipping snapshot test: {error:#}");
+            return;
+        }
+    };
+
+    assert_matches_reference("default_mandelbrot.png", &pixels);
+}
+
+fn assert_matches_reference(name: &str, pixels: &[u8]) {
+    let path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/snapshots")
+        .join(name);
+    if env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        image::save_buffer(&path, pixels, SIZE, SIZE, image::ColorType::Rgba8).unwrap();
+        return;
+    }
+    let reference = image::open(&path)
+        .unwrap_or_else(|e| panic!("Could not open reference image {}: {e}", path.display()))
+        .into_rgba8();
+
+    let differing = differing_pixels(pixels, reference.as_raw(), TOLERANCE);
+
+    let max_differing = (MAX_DIFFERING_FRACTION * f64::from(SIZE * SIZE)) as usize;
+    assert!(
+        differing <= max_differing,
+        "{differing} pixels differ from {}",
+        path.display()
+    );
+}
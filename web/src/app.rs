@@ -0,0 +1,294 @@
+//! Renders the fractal into a canvas element of the page and handles its input events.
+use std::{sync::Arc, time::Duration};
+
+use fractal_wgpu_lib::{Backends, Camera, Canvas, Controls, FractalKind, RenderState};
+use instant::Instant;
+use log::error;
+use wasm_bindgen::{prelude::wasm_bindgen, JsValue};
+use winit::{
+    dpi::{LogicalSize, PhysicalPosition},
+    event::{ElementState, Event, MouseScrollDelta, WindowEvent},
+    event_loop::{ControlFlow, EventLoop},
+    platform::web::WindowExtWebSys,
+    window::WindowBuilder,
+};
+
+use crate::url_hash;
+
+/// Size of the canvas on the page in CSS pixels.
+const WIDTH: u32 = 400;
+const HEIGHT: u32 = 400;
+/// Number of iterations, unless specified in the URL.
+const DEFAULT_ITERATIONS: f32 = 256.;
+/// Minimum time between two updates of the URL while the picture changes.
+const URL_UPDATE_INTERVAL: Duration = Duration::from_millis(500);
+
+#[wasm_bindgen(start)]
+pub async fn start() {
+    // Show panics in web logging console
+    std::panic::set_hook(Box::new(console_error_panic_hook::hook));
+    console_log::init_with_level(log::Level::Info).expect("Couldn't initialize logger");
+
+    let event_loop = EventLoop::new();
+    // Shared with the canvas, since both are moved into the event loop. Sized in logical pixels, so
+    // the canvas keeps its size on the page. Its backing surface holds `devicePixelRatio` times as
+    // many pixels along each axis, so the fractal stays crisp on HiDPI displays.
+    let window = Arc::new(
+        WindowBuilder::new()
+            .with_inner_size(LogicalSize::new(f64::from(WIDTH), f64::from(HEIGHT)))
+            .build(&event_loop)
+            .unwrap(),
+    );
+
+    web_sys::window()
+        .and_then(|win| win.document())
+        .and_then(|doc| {
+            let dst = doc.get_element_by_id("fractal-canvas")?;
+            let canvas = web_sys::Element::from(window.canvas());
+            dst.append_child(&canvas).ok()?;
+            Some(())
+        })
+        .expect("Couldn't append canvas to document body.");
+
+    // Physical size of the backing surface, i.e. the logical size scaled by `devicePixelRatio`.
+    let size = window.inner_size();
+    let mut canvas =
+        match Canvas::new(size.width, size.height, window.clone(), Backends::all()).await {
+            Ok(canvas) => canvas,
+            Err(e) => {
+                error!("Error requesting device for drawing: {e}");
+                return;
+            }
+        };
+
+    // Camera position and zoom level. Determines which part of the fractal we see.
+    //
+    // Number of iterations used to determine wether a point converges or not. How fast a point
+    // converges is used to determine the color of a pixel.
+    //
+    // We use a floating point variable to track the number of iterations, so we can easier adapt
+    // the number of iterations smoothly by pressing buttons for a period of time. This implies we
+    // need to keep track of differences smaller than 1 between frames.
+    //
+    // Both are taken from the fragment of the URL, if it describes a view, so links can be shared.
+    let (mut camera, mut iterations) = read_url_hash()
+        .and_then(|hash| url_hash::parse(&hash, DEFAULT_ITERATIONS))
+        .unwrap_or((Camera::new(), DEFAULT_ITERATIONS));
+    let mut kind = FractalKind::Mandelbrot;
+    let mut controls = Controls::new();
+    // The view changed since we last wrote it to the URL.
+    let mut url_outdated = false;
+    let mut last_url_update = Instant::now();
+
+    // Set if the picture must be rendered again, even though it does not change. E.g. because the
+    // canvas has been resized, or rendering the last frame failed.
+    let mut redraw_requested = true;
+    // The picture changed during the last frame.
+    let mut in_motion = false;
+
+    event_loop.run(move |event, _target, control_flow| match event {
+        Event::WindowEvent {
+            window_id: _,
+            event: WindowEvent::CloseRequested,
+        } => {
+            *control_flow = ControlFlow::Exit;
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event: WindowEvent::Resized(physical_size),
+        } => {
+            canvas.resize(physical_size.width, physical_size.height);
+            redraw_requested = true;
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::ScaleFactorChanged {
+                    scale_factor: _,
+                    new_inner_size,
+                },
+        } => {
+            canvas.resize(new_inner_size.width, new_inner_size.height);
+            redraw_requested = true;
+        }
+        // Some platforms report keys which are already held down as synthetic presses, once the
+        // window gains focus. They have not been pressed for us, so they must not move the camera
+        // either.
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::KeyboardInput {
+                    device_id: _,
+                    input,
+                    is_synthetic,
+                },
+        } if !(is_synthetic && input.state == ElementState::Pressed) => {
+            controls.track_button_presses(input);
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event: WindowEvent::Focused(false),
+        } => {
+            // We miss the release of any key held while the window is in the background.
+            controls.release_all();
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::CursorMoved {
+                    device_id: _,
+                    position,
+                    ..
+                },
+        } => {
+            controls.track_cursor(position);
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::MouseWheel {
+                    device_id: _,
+                    delta,
+                    ..
+                },
+        } => {
+            controls.track_mouse_wheel(unscaled_wheel_delta(delta, window.scale_factor()));
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event:
+                WindowEvent::MouseInput {
+                    device_id: _,
+                    state,
+                    button,
+                    ..
+                },
+        } => {
+            controls.track_mouse_button(state, button);
+        }
+        Event::WindowEvent {
+            window_id: _,
+            event: WindowEvent::Touch(touch),
+        } => {
+            controls.track_touch(touch);
+        }
+        Event::RedrawRequested(_window_id) => {
+            let state = RenderState::new(camera, iterations.trunc() as i32, kind)
+                .with_palette(controls.palette_index());
+            match canvas.render(&state) {
+                Ok(_) => redraw_requested = false,
+                // Most errors (e.g. Timeout) should be resolved by the next frame, so we try again
+                // rather than leaving the canvas blank.
+                Err(e) => {
+                    error!("Could not render frame: {e}");
+                    redraw_requested = true;
+                }
+            }
+        }
+        Event::MainEventsCleared => {
+            if controls.take_exit_request() {
+                // There is no application to close in the browser, so we remove the canvas from
+                // the page instead.
+                window.canvas().remove();
+                *control_flow = ControlFlow::Exit;
+                return;
+            }
+            controls.update_scene(&mut camera, &mut iterations, &mut kind, canvas.size());
+            canvas.set_loupe(controls.loupe());
+            canvas.set_julia_inset(controls.julia_inset());
+            canvas.set_grid(controls.grid());
+            // Formatting the lines is only worth it, if they are displayed.
+            let hud_lines = if controls.hud() {
+                let [x, y] = camera.position();
+                vec![
+                    format!("Zoom: {:.3e}", camera.zoom_level()),
+                    format!("Iterations: {}", iterations.trunc()),
+                    format!("Center: {x:.12} {y:+.12}i"),
+                ]
+            } else {
+                Vec::new()
+            };
+            canvas.set_hud(controls.hud(), &hud_lines);
+            if let Some(inverted) = controls.take_inversion_change() {
+                canvas.set_color_inversion(inverted);
+            }
+            if let Some(factor) = controls.take_supersampling_change() {
+                canvas.set_supersampling(factor);
+            }
+            if let Some(coloring) = controls.take_coloring_change() {
+                canvas.set_coloring_mode(coloring);
+            }
+            if let Some(interior) = controls.take_interior_change() {
+                canvas.set_interior_mode(interior);
+            }
+            // Histogram equalization reads back from the GPU synchronously every frame, which the
+            // browser does not allow. So we ignore the key.
+            controls.take_equalization_change();
+            if let Some(fit_mode) = controls.take_fit_mode_change() {
+                canvas.set_fit_mode(fit_mode);
+            }
+            if let Some(speed) = controls.take_color_cycle_change() {
+                canvas.set_color_cycle_speed(speed);
+            }
+            if let Some(enabled) = controls.take_smooth_coloring_change() {
+                canvas.set_smooth_coloring(enabled);
+            }
+            if let Some(enabled) = controls.take_dynamic_resolution_change() {
+                canvas.set_dynamic_resolution(enabled);
+            }
+            // The last frame in motion may have been drawn at reduced resolution, so we draw the
+            // picture at rest once more.
+            redraw_requested |= in_motion && !controls.picture_changes();
+            in_motion = controls.picture_changes();
+            canvas.set_motion(in_motion);
+            url_outdated |= controls.picture_changes();
+            // Writing the URL every frame would slow us down. Once the picture settles we write the
+            // final view immediately, since we may wait a long time for the next event.
+            if url_outdated
+                && (!controls.picture_changes() || last_url_update.elapsed() >= URL_UPDATE_INTERVAL)
+            {
+                write_url_hash(&url_hash::format(&camera, iterations));
+                url_outdated = false;
+                last_url_update = Instant::now();
+            }
+            // Like the desktop app, we only poll while the picture changes or a frame is still due,
+            // and otherwise wait for the next event.
+            *control_flow = if redraw_requested || controls.picture_changes() {
+                window.request_redraw();
+                ControlFlow::Poll
+            } else {
+                ControlFlow::Wait
+            };
+        }
+        _ => (),
+    });
+}
+
+/// Browsers either report scrolling in lines (e.g. Firefox) or in pixels (e.g. Chrome and Safari).
+/// Pixels are scaled by `devicePixelRatio`, which we undo, so the wheel zooms at the same pace on
+/// HiDPI displays.
+fn unscaled_wheel_delta(delta: MouseScrollDelta, scale_factor: f64) -> MouseScrollDelta {
+    match delta {
+        MouseScrollDelta::PixelDelta(position) => {
+            let logical = position.to_logical::<f64>(scale_factor);
+            MouseScrollDelta::PixelDelta(PhysicalPosition::new(logical.x, logical.y))
+        }
+        line_delta @ MouseScrollDelta::LineDelta(..) => line_delta,
+    }
+}
+
+/// Fragment of the URL the page has been loaded with, e.g. `#x=-0.5&y=0&zoom=1`.
+fn read_url_hash() -> Option<String> {
+    web_sys::window()?.location().hash().ok()
+}
+
+/// Replaces the fragment of the URL. Replaces the current history entry, rather than adding one, so
+/// navigating does not flood the history of the browser.
+fn write_url_hash(hash: &str) {
+    let Some(history) = web_sys::window().and_then(|win| win.history().ok()) else {
+        return;
+    };
+    if let Err(e) = history.replace_state_with_url(&JsValue::NULL, "", Some(hash)) {
+        error!("Could not update URL: {e:?}");
+    }
+}
@@ -0,0 +1,74 @@
+//! Encodes the view in the fragment of the URL, so it can be shared as a link.
+use fractal_wgpu_lib::Camera;
+
+/// Parses a fragment like `#x=-0.743&y=0.126&zoom=2000&iter=512` into a camera and a number of
+/// iterations. `iter` is optional and defaults to `default_iterations`. Unknown keys are ignored.
+/// `None` if the fragment does not describe a valid view.
+pub fn parse(hash: &str, default_iterations: f32) -> Option<(Camera, f32)> {
+    let mut x = None;
+    let mut y = None;
+    let mut zoom = None;
+    let mut iterations = default_iterations;
+    for pair in hash.trim_start_matches('#').split('&') {
+        let (key, value) = pair.split_once('=')?;
+        match key {
+            "x" => x = Some(value.parse().ok()?),
+            "y" => y = Some(value.parse().ok()?),
+            "zoom" => zoom = Some(value.parse().ok()?),
+            "iter" => {
+                iterations = value
+                    .parse()
+                    .ok()
+                    .filter(|&iter: &f32| iter.is_finite() && iter >= 1.)?
+            }
+            _ => (),
+        }
+    }
+    let camera = Camera::try_at([x?, y?], zoom?).ok()?;
+    Some((camera, iterations))
+}
+
+/// Formats a fragment which can be read by [`parse`].
+pub fn format(camera: &Camera, iterations: f32) -> String {
+    let [x, y] = camera.position();
+    let zoom = camera.zoom_level();
+    let iterations = iterations.trunc();
+    format!("#x={x}&y={y}&zoom={zoom}&iter={iterations}")
+}
+
+#[cfg(test)]
+mod tests {
+    use fractal_wgpu_lib::Camera;
+
+    use super::{format, parse};
+
+    #[test]
+    fn formatted_view_is_parsed_back() {
+        let camera = Camera::try_at([-0.743, 0.126], 2000.).unwrap();
+
+        let parsed = parse(&format(&camera, 512.), 256.);
+
+        assert_eq!(Some((camera, 512.)), parsed);
+    }
+
+    #[test]
+    fn iterations_default_if_missing() {
+        let (_, iterations) = parse("#x=0&y=0&zoom=1", 256.).unwrap();
+
+        assert_eq!(256., iterations);
+    }
+
+    #[test]
+    fn values_which_are_not_finite_are_rejected() {
+        for hash in [
+            "#x=NaN&y=0&zoom=1",
+            "#x=0&y=inf&zoom=1",
+            "#x=0&y=0&zoom=inf",
+            "#x=0&y=0&zoom=0",
+            "#x=0&y=0&zoom=1&iter=inf",
+            "#x=0&y=0&zoom=1&iter=NaN",
+        ] {
+            assert_eq!(None, parse(hash, 256.), "{hash}");
+        }
+    }
+}